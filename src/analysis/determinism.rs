@@ -1,45 +1,424 @@
 use crate::ast::*;
+use crate::builtins;
+use crate::diagnostics::Diagnostic;
+use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
 
 #[derive(Debug, Clone)]
-pub struct SymbolTable { pub functions: HashMap<String, FunctionDef> }
+pub struct SymbolTable {
+    pub functions: HashMap<String, FunctionDef>,
+    pub schemas: HashMap<String, SchemaDef>,
+    pub structs: HashMap<String, StructDef>,
+}
 
+impl SymbolTable {
+    pub fn from_program(program: &Program) -> Self {
+        Self {
+            functions: program.functions().map(|f| (f.name.clone(), f.clone())).collect(),
+            schemas: program.schemas().map(|s| (s.name.clone(), s.clone())).collect(),
+            structs: program.structs().map(|s| (s.name.clone(), s.clone())).collect(),
+        }
+    }
+
+    // Struct ya da schema: ikisi de alanları olan kayıt tipleridir
+    pub fn record_fields(&self, name: &str) -> Option<Vec<(&str, &TypeRef)>> {
+        if let Some(s) = self.structs.get(name) {
+            return Some(s.fields.iter().map(|f| (f.name.as_str(), &f.field_type)).collect());
+        }
+        self.schemas.get(name).map(|s| s.fields.iter().map(|f| (f.name.as_str(), &f.field_type)).collect())
+    }
+}
+
+// Pass 1: Determinism Police
+// Deterministic bir fonksiyon; spawn/await/altyapı çağrısı yapamaz, nondeterministic
+// kullanıcı fonksiyonlarını veya builtin'leri (doğrudan ya da dolaylı) çağıramaz.
+// Fonksiyon değerlerinin saflığı gövdelerinden çıkarılır: `|x| DB.log(x)` saf olmayan bir
+// closure'dır ve onu çağıran (ya da map/filter/fold'a veren) fonksiyon da saf olmaz.
+// Saf olmayan bir fonksiyon değeri sadece çağrılabilir, `let` ile bağlanabilir ya da
+// map/filter/fold'a verilebilir; saklanamaz, döndürülemez, başka fonksiyona geçilemez.
+// Böylece parametre olarak gelen fonksiyon değerleri her zaman deterministiktir.
 pub struct DeterminismAnalyzer;
 
+// Saf olmayan bir işlemin yeri ve (varsa) çağrı zinciri
+enum Impurity {
+    Effect(Span, &'static str),
+    Builtin(Span, String),
+    Unresolved(Span, String),
+    // Çağrılan kullanıcı fonksiyonu ve onun saf olmama zinciri
+    Callee(Span, String, Vec<String>),
+    // Çağrılan ya da map/filter/fold'a verilen saf olmayan fonksiyon değeri (isimsizse closure) ve zinciri
+    ValueCall(Span, Option<String>, Vec<String>),
+    // Çağrı dışında kullanılan saf olmayan fonksiyon değeri: her fonksiyonda hatadır
+    Escape(Span, Option<String>, Vec<String>),
+}
+
+fn value_name(name: &Option<String>) -> String {
+    name.as_ref().map_or("closure".to_string(), |n| format!("function value `{}`", n))
+}
+
+type Chains = HashMap<String, Vec<String>>;
+
 impl DeterminismAnalyzer {
-    pub fn check(func: &FunctionDef, _symbols: &SymbolTable) -> Result<(), String> {
-        if let Purity::Deterministic = func.purity { if !Self::is_block_pure(&func.body) { return Err(format!("Impure function: {}", func.name)); } } Ok(())
-    }
-    fn is_block_pure(block: &Block) -> bool { block.statements.iter().all(Self::is_stmt_pure) }
-    fn is_stmt_pure(stmt: &Statement) -> bool {
-        match stmt {
-            Statement::Let(l) => Self::is_expr_pure(&l.value),
-            Statement::Assign { value, .. } => Self::is_expr_pure(value),
-            Statement::If { condition, then_block, else_block } => Self::is_expr_pure(condition) && Self::is_block_pure(then_block) && else_block.as_ref().map(|b| Self::is_block_pure(b)).unwrap_or(true),
-            Statement::While { condition, body } => Self::is_expr_pure(condition) && Self::is_block_pure(body),
-            Statement::For { start, end, step, body, .. } => {
-                Self::is_expr_pure(start) && 
-                Self::is_expr_pure(end) && 
-                step.as_ref().map(|s| Self::is_expr_pure(s)).unwrap_or(true) && 
-                Self::is_block_pure(body)
+    pub fn check(func: &FunctionDef, symbols: &SymbolTable) -> Result<(), Diagnostic> {
+        let chains = Self::impurity_chains(symbols);
+        // Nondeterministic fonksiyonlarda sadece fonksiyon değerlerinin kullanımı denetlenir
+        let walker = Walker::new(func, symbols, &chains, func.purity == Purity::Nondeterministic);
+        let Some(impurity) = walker.block(&func.body) else { return Ok(()) };
+
+        let err = match impurity {
+            Impurity::Effect(span, what) => Diagnostic::error("determinism", format!("Impure function: {} ({} in a deterministic function)", func.name, what), span)
+                .with_note("deterministic functions cannot spawn, await or make infra calls"),
+            Impurity::Builtin(span, name) => Diagnostic::error("determinism", format!("Deterministic function `{}` calls nondeterministic builtin `{}`", func.name, name), span)
+                .with_note(format!("call chain: {} -> {}", func.name, name)),
+            Impurity::Unresolved(span, name) => Diagnostic::error("determinism", format!("Deterministic function `{}` calls unknown function `{}`", func.name, name), span)
+                .with_note("deterministic functions may only call known deterministic functions"),
+            Impurity::Callee(span, name, chain) => {
+                let msg = match symbols.functions.get(&name).map(|f| &f.purity) {
+                    Some(Purity::Nondeterministic) => format!("Deterministic function `{}` calls nondeterministic function `{}`", func.name, name),
+                    _ => format!("Deterministic function `{}` calls impure function `{}`", func.name, name),
+                };
+                Diagnostic::error("determinism", msg, span)
+                    .with_note(format!("call chain: {} -> {}", func.name, chain.join(" -> ")))
+            }
+            Impurity::ValueCall(span, name, chain) => Diagnostic::error("determinism", format!("Deterministic function `{}` calls impure {}", func.name, value_name(&name)), span)
+                .with_note(format!("call chain: {} -> {}", func.name, chain.join(" -> "))),
+            Impurity::Escape(span, name, chain) => Diagnostic::error("determinism", format!("Impure {} can only be called, bound with `let` or passed to map/filter/fold", value_name(&name)), span)
+                .with_note(format!("call chain: {}", chain.join(" -> ")))
+                .with_note("function values that are stored, returned or passed to other functions must be deterministic"),
+        };
+        Err(err)
+    }
+
+    // Her saf olmayan kullanıcı fonksiyonu için, kendisinden saf olmayan işleme giden zincir.
+    // Karşılıklı özyinelemede (f -> g -> f) sabit noktaya kadar yayılır.
+    fn impurity_chains(symbols: &SymbolTable) -> Chains {
+        let mut chains = Chains::new();
+        for f in symbols.functions.values() {
+            if f.purity == Purity::Nondeterministic {
+                chains.insert(f.name.clone(), vec![format!("{} (nondeterministic)", f.name)]);
+            }
+        }
+        // İsimler sıralı gezilir: aynı program her derlemede aynı zinciri raporlar
+        let mut names: Vec<&String> = symbols.functions.keys().collect();
+        names.sort();
+        loop {
+            let mut changed = false;
+            for name in &names {
+                if chains.contains_key(*name) { continue; }
+                let func = &symbols.functions[*name];
+                if let Some(impurity) = Walker::new(func, symbols, &chains, false).block(&func.body) {
+                    let mut chain = vec![func.name.clone()];
+                    Self::extend_chain(&mut chain, impurity);
+                    chains.insert(func.name.clone(), chain);
+                    changed = true;
+                }
+            }
+            if !changed { return chains; }
+        }
+    }
+
+    fn extend_chain(chain: &mut Vec<String>, impurity: Impurity) {
+        match impurity {
+            Impurity::Effect(_, what) => chain.push(what.to_string()),
+            Impurity::Builtin(_, n) | Impurity::Unresolved(_, n) => chain.push(n),
+            Impurity::Callee(_, _, rest) | Impurity::ValueCall(_, _, rest) | Impurity::Escape(_, _, rest) => chain.extend(rest),
+        }
+    }
+}
+
+// Bir fonksiyon gövdesini gezer. Görünen yerel isimler (parametreler, let, döngü ve match
+// bağlamaları, closure parametreleri) bir çağrının fonksiyon değeri çağrısı mı olduğunu,
+// bir ismin değişken mi fonksiyon mu olduğunu belirler. Saf olmayan bir fonksiyon değeri
+// tutan yerel isim, o değerin saf olmama zincirini taşır.
+struct Walker<'a> {
+    symbols: &'a SymbolTable,
+    chains: &'a Chains,
+    locals: RefCell<Vec<HashMap<String, Option<Vec<String>>>>>,
+    // Nondeterministic fonksiyon: sadece fonksiyon değerlerinin kullanımı denetlenir
+    values_only: Cell<bool>,
+}
+
+impl<'a> Walker<'a> {
+    fn new(func: &FunctionDef, symbols: &'a SymbolTable, chains: &'a Chains, values_only: bool) -> Self {
+        // Parametreler her zaman saftır: saf olmayan değer argüman olarak geçilemez
+        let params = func.params.iter().map(|p| (p.name.clone(), None)).collect();
+        Walker { symbols, chains, locals: RefCell::new(vec![params]), values_only: Cell::new(values_only) }
+    }
+
+    fn is_local(&self, name: &str) -> bool {
+        self.locals.borrow().iter().any(|s| s.contains_key(name))
+    }
+
+    // İsim saf olmayan bir fonksiyon değeri tutuyorsa zinciri (içteki tanım dıştakini gölgeler)
+    fn local_chain(&self, name: &str) -> Option<Vec<String>> {
+        self.locals.borrow().iter().rev().find_map(|s| s.get(name)).cloned().flatten()
+    }
+
+    fn bind(&self, name: &str, chain: Option<Vec<String>>) {
+        if let Some(scope) = self.locals.borrow_mut().last_mut() { scope.insert(name.to_string(), chain); }
+    }
+
+    // Yeni bir kapsamda (verilen isimler bağlı olarak) gezer
+    fn scoped<'n, T>(&self, names: impl IntoIterator<Item = &'n str>, f: impl FnOnce() -> T) -> T {
+        self.locals.borrow_mut().push(names.into_iter().map(|n| (n.to_string(), None)).collect());
+        let result = f();
+        self.locals.borrow_mut().pop();
+        result
+    }
+
+    fn with_mode<T>(&self, values_only: bool, f: impl FnOnce() -> T) -> T {
+        let previous = self.values_only.replace(values_only);
+        let result = f();
+        self.values_only.set(previous);
+        result
+    }
+
+    // Fonksiyon değerlerinin kaçması dışındaki saf olmayan işlemler sadece tam denetimde sayılır
+    fn effect(&self, impurity: Impurity) -> Option<Impurity> {
+        if self.values_only.get() { None } else { Some(impurity) }
+    }
+
+    // Saf olmayan bir fonksiyon değerinin adı (closure ise None) ve zinciri
+    fn function_value(&self, expr: &Expr) -> Option<(Option<String>, Vec<String>)> {
+        match &expr.kind {
+            ExprKind::Identifier(name) if self.is_local(name) => self.local_chain(name).map(|chain| (Some(name.clone()), chain)),
+            ExprKind::Identifier(name) => self.chains.get(name).map(|chain| (Some(name.clone()), chain.clone())),
+            // Closure'ın saflığı gövdesinden çıkarılır
+            ExprKind::Lambda(params, body) => {
+                let impurity = self.with_mode(false, || self.scoped(params.iter().map(|(n, _)| n.as_str()), || self.expr(body)))?;
+                let mut chain = vec!["closure".to_string()];
+                DeterminismAnalyzer::extend_chain(&mut chain, impurity);
+                Some((None, chain))
+            }
+            _ => None,
+        }
+    }
+
+    // Closure gövdesinde kaçan fonksiyon değeri (closure'ın kendi saflığından bağımsız olarak hatadır)
+    fn closure_escapes(&self, expr: &Expr) -> Option<Impurity> {
+        match &expr.kind {
+            ExprKind::Lambda(params, body) => self.with_mode(true, || self.scoped(params.iter().map(|(n, _)| n.as_str()), || self.expr(body))),
+            _ => None,
+        }
+    }
+
+    // `let f = <değer>`: fonksiyon değeri saf değilse `f` onun zincirini taşır
+    fn bind_value(&self, name: &str, value: &Expr) -> Option<Impurity> {
+        if !matches!(value.kind, ExprKind::Identifier(_) | ExprKind::Lambda(..)) {
+            let impurity = self.expr(value);
+            self.bind(name, None);
+            return impurity;
+        }
+        let impurity = self.closure_escapes(value);
+        let chain = self.function_value(value).map(|(value_name, chain)| match value_name {
+            // Closure zincirin başında `f` adıyla görünür
+            None => std::iter::once(name.to_string()).chain(chain.into_iter().skip(1)).collect(),
+            Some(_) => std::iter::once(name.to_string()).chain(chain).collect(),
+        });
+        self.bind(name, chain);
+        impurity
+    }
+
+    // map/filter/fold'a verilen fonksiyon orada çağrılır
+    fn applied(&self, arg: &Expr) -> Option<Impurity> {
+        if !matches!(arg.kind, ExprKind::Identifier(_) | ExprKind::Lambda(..)) { return self.expr(arg); }
+        self.closure_escapes(arg)
+            .or_else(|| self.function_value(arg).and_then(|(name, chain)| self.effect(Impurity::ValueCall(arg.span, name, chain))))
+    }
+
+    // Saf olmayan ilk işlemi döndürür
+    fn block(&self, block: &Block) -> Option<Impurity> {
+        self.scoped([], || block.statements.iter().find_map(|s| self.stmt(s)))
+    }
+
+    fn stmt(&self, stmt: &Statement) -> Option<Impurity> {
+        let block = |b: &Block| self.block(b);
+        let expr = |e: &Expr| self.expr(e);
+        match &stmt.kind {
+            StmtKind::Let(l) => self.bind_value(&l.name, &l.value),
+            StmtKind::Assign { value, .. } => expr(value),
+            StmtKind::If { condition, then_block, else_block } => expr(condition)
+                .or_else(|| block(then_block))
+                .or_else(|| else_block.as_ref().and_then(block)),
+            StmtKind::While { condition, body } => expr(condition).or_else(|| block(body)),
+            StmtKind::For { var, start, end, step, body } => {
+                expr(start)
+                    .or_else(|| expr(end))
+                    .or_else(|| step.as_ref().and_then(expr))
+                    .or_else(|| self.scoped([var.as_str()], || block(body)))
             },
-            Statement::ScopeBlock { body, .. } => Self::is_block_pure(body),
-            Statement::ValidateBlock { success_scope, .. } => Self::is_block_pure(success_scope),
-            Statement::ExprStmt(expr) | Statement::Return(Some(expr)) => Self::is_expr_pure(expr),
-            Statement::Return(None) => true,
-        }
-    }
-    fn is_expr_pure(expr: &Expr) -> bool {
-        match expr {
-            Expr::Literal(_) | Expr::Identifier(_) => true,
-            Expr::Binary(l, _, r) => Self::is_expr_pure(l) && Self::is_expr_pure(r),
-            Expr::Call(_, args) => args.iter().all(Self::is_expr_pure),
-            Expr::JsonField(source, _) => Self::is_expr_pure(source),
+            // Map gezinmesi anahtar sırasıyladır: deterministic fonksiyonlarda da serbest
+            StmtKind::ForIn { binding, iterable, body } => expr(iterable).or_else(|| self.scoped(binding.names(), || block(body))),
+            StmtKind::ScopeBlock { body, .. } => block(body),
+            StmtKind::ValidateBlock { on_fail, success_scope, .. } => on_fail.as_deref().and_then(block).or_else(|| block(success_scope)),
+            StmtKind::ValidateLet { name, on_fail, .. } => {
+                let impurity = block(on_fail);
+                self.bind(name, None);
+                impurity
+            }
+            StmtKind::ExprStmt(e) | StmtKind::Return(Some(e)) => expr(e),
+            StmtKind::AwaitAll => self.effect(Impurity::Effect(stmt.span, "await")),
+            StmtKind::Return(None) => None,
+        }
+    }
+
+    fn expr(&self, expr: &Expr) -> Option<Impurity> {
+        let sub = |e: &Expr| self.expr(e);
+        match &expr.kind {
+            ExprKind::Literal(_) => None,
+            // Çağrı, `let` ve map/filter/fold dışında kullanılan fonksiyon değeri saf olmalı
+            ExprKind::Identifier(_) => self.function_value(expr).map(|(name, chain)| Impurity::Escape(expr.span, name, chain)),
+            ExprKind::Binary(l, _, r) => sub(l).or_else(|| sub(r)),
+            ExprKind::Unary(_, e) => sub(e),
+            ExprKind::Call(name, args) => {
+                // Önce argümanlar: f(DB.log(x)) hatası içteki çağrıyı göstersin
+                if let Some(i) = args.iter().find_map(sub) { return Some(i); }
+                if self.symbols.functions.contains_key(name) {
+                    return self.chains.get(name).and_then(|chain| self.effect(Impurity::Callee(expr.span, name.clone(), chain.clone())));
+                }
+                match builtins::lookup(name) {
+                    Some(b) if b.purity == Purity::Deterministic => None,
+                    Some(_) => self.effect(Impurity::Builtin(expr.span, name.clone())),
+                    // Fonksiyon değeri çağrısı: değerin saflığı bağlandığı yerde çıkarıldı
+                    None if self.is_local(name) => self.local_chain(name).and_then(|chain| self.effect(Impurity::ValueCall(expr.span, Some(name.clone()), chain))),
+                    None => self.effect(Impurity::Unresolved(expr.span, name.clone())),
+                }
+            }
+            ExprKind::JsonField(source, _) => sub(source),
             // YENİ: Array ve Index kontrolü eklendi
-            Expr::ArrayLiteral(elems) => elems.iter().all(Self::is_expr_pure),
-            Expr::Index(arr, idx) => Self::is_expr_pure(arr) && Self::is_expr_pure(idx),
-            
-            Expr::Spawn(_) | Expr::Await(_) | Expr::Infra(_) => false,
+            ExprKind::ArrayLiteral(elems) => elems.iter().find_map(sub),
+            ExprKind::MapLiteral(entries) => entries.iter().find_map(|(k, v)| sub(k).or_else(|| sub(v))),
+            ExprKind::Index(arr, idx) => sub(arr).or_else(|| sub(idx)),
+            ExprKind::Slice(arr, from, to) => sub(arr).or_else(|| sub(from)).or_else(|| sub(to)),
+            // Dizi ve map metotları saftır; map/filter/fold verilen fonksiyonu çağırır
+            ExprKind::MethodCall(target, method, args) if HIGHER_ORDER_METHODS.contains(&method.as_str()) => sub(target).or_else(|| args.iter().find_map(|a| self.applied(a))),
+            ExprKind::MethodCall(target, _, args) => sub(target).or_else(|| args.iter().find_map(sub)),
+            ExprKind::Interpolated(parts) => parts.iter().filter_map(StrPart::expr).find_map(sub),
+            ExprKind::Field(e, _) => sub(e),
+            ExprKind::StructLiteral(_, fields) => fields.iter().find_map(|(_, v)| sub(v)),
+            ExprKind::Ctor(_, payload) => payload.as_deref().and_then(sub),
+            ExprKind::Match(scrutinee, arms) => sub(scrutinee).or_else(|| arms.iter().find_map(|arm| {
+                let binding = match &arm.pattern {
+                    Pattern::Ctor(_, Some(name)) => Some(name.as_str()),
+                    _ => None,
+                };
+                self.scoped(binding, || match &arm.body {
+                    ArmBody::Expr(e) => sub(e),
+                    ArmBody::Block(b) => self.block(b),
+                })
+            })),
+            ExprKind::Lambda(..) => self.closure_escapes(expr)
+                .or_else(|| self.function_value(expr).map(|(name, chain)| Impurity::Escape(expr.span, name, chain))),
+
+            ExprKind::Spawn(e) => self.effect(Impurity::Effect(expr.span, "spawn")).or_else(|| sub(e)),
+            ExprKind::Await(e) => self.effect(Impurity::Effect(expr.span, "await")).or_else(|| sub(e)),
+            ExprKind::Infra(call) => self.effect(Impurity::Effect(expr.span, "infra call"))
+                .or_else(|| call.args.iter().find_map(sub))
+                .or_else(|| call.config.fallback.as_deref().and_then(sub)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser;
+
+    // `name` fonksiyonunun determinism denetimi
+    fn check(source: &str, name: &str) -> Result<(), Diagnostic> {
+        let (program, errors) = parser::parse_program(source);
+        assert!(errors.is_empty(), "{:?}", errors);
+        let symbols = SymbolTable::from_program(&program);
+        DeterminismAnalyzer::check(&symbols.functions[name], &symbols)
+    }
+
+    #[test]
+    fn reports_the_call_chain_of_transitive_impurity() {
+        let source = "deterministic fn f(x: i64) -> i64 { return g(x) + 1 }\ndeterministic fn g(x: i64) -> i64 {\n    DB.log(x)\n    return x\n}\n";
+        let err = check(source, "g").unwrap_err();
+        assert_eq!(err.message, "Deterministic function `g` calls nondeterministic builtin `DB.log`");
+        assert_eq!(err.notes, ["call chain: g -> DB.log"]);
+        let err = check(source, "f").unwrap_err();
+        assert_eq!(err.message, "Deterministic function `f` calls impure function `g`");
+        assert_eq!(err.notes, ["call chain: f -> g -> DB.log"]);
+        assert_eq!(&source[err.span.start..err.span.end], "g(x)");
+
+        let source = "nondeterministic fn fetch() -> i64 { return 1 }\ndeterministic fn f() -> i64 { return fetch() }\n";
+        let err = check(source, "f").unwrap_err();
+        assert_eq!(err.message, "Deterministic function `f` calls nondeterministic function `fetch`");
+        assert_eq!(err.notes, ["call chain: f -> fetch (nondeterministic)"]);
+    }
+
+    #[test]
+    fn follows_mutual_recursion() {
+        let pure = "deterministic fn even(n: i64) -> bool { if n == 0 { return true } return odd(n - 1) }\ndeterministic fn odd(n: i64) -> bool { if n == 0 { return false } return even(n - 1) }\n";
+        assert!(check(pure, "even").is_ok());
+        assert!(check(pure, "odd").is_ok());
+        let impure = "deterministic fn even(n: i64) -> bool { if n == 0 { return Util.now() == 0 } return odd(n - 1) }\ndeterministic fn odd(n: i64) -> bool { if n == 0 { return false } return even(n - 1) }\n";
+        let err = check(impure, "odd").unwrap_err();
+        assert_eq!(err.message, "Deterministic function `odd` calls impure function `even`");
+        assert_eq!(err.notes, ["call chain: odd -> even -> Util.now"]);
+        assert!(check(impure, "even").is_err());
+    }
+
+    #[test]
+    fn rejects_effects_and_unknown_calls() {
+        let err = check("deterministic fn f() -> i64 { let t = call Util.now() { timeout: 1s } return t }\n", "f").unwrap_err();
+        assert_eq!(err.message, "Impure function: f (infra call in a deterministic function)");
+        let err = check("deterministic fn f() -> i64 { return missing(1) }\n", "f").unwrap_err();
+        assert_eq!(err.message, "Deterministic function `f` calls unknown function `missing`");
+        // Nondeterministic fonksiyonlar her şeyi çağırabilir
+        assert!(check("nondeterministic fn main() { DB.log(Util.now()) }\n", "main").is_ok());
+        assert!(check("deterministic fn f(s: String) -> i64 { return Util.to_int(s) + len([1]) }\n", "f").is_ok());
+    }
+
+
+    #[test]
+    fn impure_closures_are_allowed_in_nondeterministic_functions() {
+        let source = "nondeterministic fn fetch(n: i64) -> i64 { return n }\n\
+                      nondeterministic fn main(input: Untrusted) {\n    let log = |x: i64| DB.log(x)\n    log(1)\n    let xs = [1, 2]\n    xs.map(|x: i64| DB.log(x))\n    let ys = xs.map(fetch)\n    let again = |x: i64| log(x)\n    again(2)\n}\n";
+        assert!(check(source, "main").is_ok());
+        // Saf closure deterministic fonksiyonda da kullanılabilir
+        assert!(check("deterministic fn f(xs: Array<i64>) -> i64 { let inc = |x: i64| x + 1\n return xs.map(inc).fold(0, |a, b| a + b) }\n", "f").is_ok());
+        // Saf olmayan closure oluşturmak tek başına saflığı bozmaz; çağırmak bozar
+        assert!(check("deterministic fn f() -> i64 { let log = |x: i64| DB.log(x)\n return 1 }\n", "f").is_ok());
+    }
+
+    #[test]
+    fn calling_an_impure_closure_makes_the_caller_impure() {
+        let err = check("deterministic fn f() -> i64 {\n    let log = |x: i64| DB.log(x)\n    let twice = |x: i64| log(x)\n    twice(1)\n    return 1\n}\n", "f").unwrap_err();
+        assert_eq!(err.message, "Deterministic function `f` calls impure function value `twice`");
+        assert_eq!(err.notes, ["call chain: f -> twice -> log -> DB.log"]);
+
+        let source = "deterministic fn f(xs: Array<i64>) -> Array<i64> { return xs.map(|x: i64| x + Util.now()) }\ndeterministic fn g() -> i64 { let ys = f([1])\n return 0 }\n";
+        let err = check(source, "f").unwrap_err();
+        assert_eq!(err.message, "Deterministic function `f` calls impure closure");
+        assert_eq!(err.notes, ["call chain: f -> closure -> Util.now"]);
+        let err = check(source, "g").unwrap_err();
+        assert_eq!(err.notes, ["call chain: g -> f -> closure -> Util.now"]);
+
+        let err = check("nondeterministic fn fetch(n: i64) -> i64 { return n }\ndeterministic fn f(xs: Array<i64>) -> Array<i64> { return xs.map(fetch) }\n", "f").unwrap_err();
+        assert_eq!(err.message, "Deterministic function `f` calls impure function value `fetch`");
+        assert_eq!(err.notes, ["call chain: f -> fetch (nondeterministic)"]);
+    }
+
+    #[test]
+    fn impure_function_values_cannot_escape() {
+        let apply = "deterministic fn apply(f: fn(i64) -> i64, x: i64) -> i64 { return f(x) }\nnondeterministic fn fetch(n: i64) -> i64 { return n }\n";
+        let cases = [
+            ("apply(|x: i64| x + Util.now(), 1)", "Impure closure can only be called, bound with `let` or passed to map/filter/fold"),
+            ("apply(fetch, 1)", "Impure function value `fetch` can only be called, bound with `let` or passed to map/filter/fold"),
+            ("let log = |x: i64| DB.log(x)\n    let fs = [log]", "Impure function value `log` can only be called, bound with `let` or passed to map/filter/fold"),
+            ("let inc = |x: i64| x + 1\n    inc = |x: i64| x + Util.now()", "Impure closure can only be called, bound with `let` or passed to map/filter/fold"),
+            // Saf olmayan closure'ın içinden de kaçamaz
+            ("let f = |x: i64| apply(|y: i64| y + Util.now(), x)\n    f(1)", "Impure closure can only be called, bound with `let` or passed to map/filter/fold"),
+        ];
+        for (body, expected) in cases {
+            let source = format!("{}nondeterministic fn main(input: Untrusted) {{\n    {}\n}}\n", apply, body);
+            let err = check(&source, "main").unwrap_err();
+            assert_eq!(err.message, expected, "{}", body);
         }
+        let err = check(&format!("{}nondeterministic fn make() -> fn(i64) -> i64 {{ return fetch }}\n", apply), "make").unwrap_err();
+        assert_eq!(err.notes[0], "call chain: fetch (nondeterministic)");
     }
-}
\ No newline at end of file
+}
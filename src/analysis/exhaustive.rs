@@ -0,0 +1,159 @@
+use crate::ast::*;
+use crate::analysis::types::TypeTable;
+use crate::diagnostics::Diagnostic;
+
+// Pass: Exhaustiveness Police
+// Her `match`, scrutinee tipinin tüm durumlarını karşılamalı (Result: Ok + Err,
+// Option: Some + None) ya da `_` koluna sahip olmalı; hiçbir kol ulaşılamaz kalmamalı.
+// Scrutinee tipi tip denetiminin tablosundan okunur, bu yüzden o pass'ten sonra çalışır.
+pub struct ExhaustivenessAnalyzer<'a> {
+    types: &'a TypeTable,
+}
+
+// Bir kurucunun ait olduğu tipin tüm durumları
+fn family(c: Ctor) -> [Ctor; 2] {
+    match c {
+        Ctor::Ok | Ctor::Err => [Ctor::Ok, Ctor::Err],
+        Ctor::Some | Ctor::None => [Ctor::Some, Ctor::None],
+    }
+}
+
+fn describe(c: Ctor) -> String {
+    if c.has_payload() { format!("`{}(_)`", c.name()) } else { format!("`{}`", c.name()) }
+}
+
+impl<'a> ExhaustivenessAnalyzer<'a> {
+    pub fn check(func: &FunctionDef, types: &'a TypeTable) -> Result<(), Diagnostic> {
+        ExhaustivenessAnalyzer { types }.visit_block(&func.body)
+    }
+
+    fn visit_block(&self, block: &Block) -> Result<(), Diagnostic> {
+        block.statements.iter().try_for_each(|s| self.visit_stmt(s))
+    }
+
+    fn visit_stmt(&self, stmt: &Statement) -> Result<(), Diagnostic> {
+        match &stmt.kind {
+            StmtKind::Let(LetStmt { value, .. }) | StmtKind::Assign { value, .. } | StmtKind::ExprStmt(value) | StmtKind::Return(Some(value)) => self.visit_expr(value),
+            StmtKind::If { condition, then_block, else_block } => {
+                self.visit_expr(condition)?;
+                self.visit_block(then_block)?;
+                else_block.as_ref().map_or(Ok(()), |b| self.visit_block(b))
+            }
+            StmtKind::While { condition, body } => { self.visit_expr(condition)?; self.visit_block(body) }
+            StmtKind::For { start, end, step, body, .. } => {
+                self.visit_expr(start)?;
+                self.visit_expr(end)?;
+                if let Some(s) = step { self.visit_expr(s)?; }
+                self.visit_block(body)
+            }
+            StmtKind::ForIn { iterable, body, .. } => { self.visit_expr(iterable)?; self.visit_block(body) }
+            StmtKind::ScopeBlock { body, .. } => self.visit_block(body),
+            StmtKind::ValidateBlock { on_fail, success_scope, .. } => {
+                if let Some(b) = on_fail { self.visit_block(b)?; }
+                self.visit_block(success_scope)
+            }
+            StmtKind::ValidateLet { on_fail, .. } => self.visit_block(on_fail),
+            StmtKind::Return(None) | StmtKind::AwaitAll => Ok(()),
+        }
+    }
+
+    fn visit_expr(&self, expr: &Expr) -> Result<(), Diagnostic> {
+        match &expr.kind {
+            ExprKind::Identifier(_) | ExprKind::Literal(_) | ExprKind::Ctor(_, None) => Ok(()),
+            ExprKind::Binary(l, _, r) | ExprKind::Index(l, r) => { self.visit_expr(l)?; self.visit_expr(r) }
+            ExprKind::Slice(arr, from, to) => { self.visit_expr(arr)?; self.visit_expr(from)?; self.visit_expr(to) }
+            ExprKind::Call(_, args) | ExprKind::ArrayLiteral(args) => args.iter().try_for_each(|a| self.visit_expr(a)),
+            ExprKind::MethodCall(target, _, args) => { self.visit_expr(target)?; args.iter().try_for_each(|a| self.visit_expr(a)) }
+            ExprKind::MapLiteral(entries) => entries.iter().try_for_each(|(k, v)| { self.visit_expr(k)?; self.visit_expr(v) }),
+            ExprKind::Interpolated(parts) => parts.iter().filter_map(StrPart::expr).try_for_each(|e| self.visit_expr(e)),
+            ExprKind::Infra(call) => {
+                call.args.iter().try_for_each(|a| self.visit_expr(a))?;
+                call.config.fallback.as_ref().map_or(Ok(()), |f| self.visit_expr(f))
+            }
+            ExprKind::Spawn(e) | ExprKind::Await(e) | ExprKind::JsonField(e, _) | ExprKind::Field(e, _) | ExprKind::Ctor(_, Some(e)) | ExprKind::Unary(_, e) | ExprKind::Lambda(_, e) => self.visit_expr(e),
+            ExprKind::StructLiteral(_, fields) => fields.iter().try_for_each(|(_, v)| self.visit_expr(v)),
+            ExprKind::Match(scrutinee, arms) => {
+                self.visit_expr(scrutinee)?;
+                for arm in arms {
+                    match &arm.body {
+                        ArmBody::Expr(e) => self.visit_expr(e)?,
+                        ArmBody::Block(b) => self.visit_block(b)?,
+                    }
+                }
+                self.check_match(expr, scrutinee, arms)
+            }
+        }
+    }
+
+    fn check_match(&self, expr: &Expr, scrutinee: &Expr, arms: &[MatchArm]) -> Result<(), Diagnostic> {
+        // Tip bilinmiyorsa (ör. dış servis sonucu) durumlar desenlerden çıkarılır
+        let cases = match self.types.get(scrutinee) {
+            Some(TypeRef::Result(..)) => Some(family(Ctor::Ok)),
+            Some(TypeRef::Option(_)) => Some(family(Ctor::Some)),
+            _ => arms.iter().find_map(|arm| match arm.pattern { Pattern::Ctor(c, _) => Some(family(c)), Pattern::Wildcard => None }),
+        };
+
+        let mut covered: Vec<Ctor> = Vec::new();
+        let mut wildcard = false;
+        for arm in arms {
+            if wildcard {
+                return Err(Diagnostic::error("match", "Unreachable `match` arm".to_string(), arm.span)
+                    .with_note("an earlier `_` arm already matches every value"));
+            }
+            match arm.pattern {
+                Pattern::Wildcard => {
+                    if cases.is_some_and(|cases| cases.iter().all(|c| covered.contains(c))) {
+                        return Err(Diagnostic::error("match", "Unreachable `_` arm: every case is already matched".to_string(), arm.span));
+                    }
+                    wildcard = true;
+                }
+                Pattern::Ctor(c, _) => {
+                    if let Some(cases) = cases.filter(|cases| !cases.contains(&c)) {
+                        return Err(Diagnostic::error("match", format!("Pattern {} cannot appear with {} and {}", describe(c), describe(cases[0]), describe(cases[1])), arm.span));
+                    }
+                    if covered.contains(&c) {
+                        return Err(Diagnostic::error("match", format!("Unreachable `match` arm: {} is already matched", describe(c)), arm.span));
+                    }
+                    covered.push(c);
+                }
+            }
+        }
+
+        let missing: Vec<String> = match (wildcard, cases) {
+            (false, Some(cases)) => cases.iter().filter(|c| !covered.contains(c)).map(|c| describe(*c)).collect(),
+            _ => Vec::new(),
+        };
+        if !missing.is_empty() {
+            return Err(Diagnostic::error("match", format!("Non-exhaustive `match`: {} not covered", missing.join(" and ")), expr.span)
+                .with_note("add the missing arms or a `_ => ...` arm"));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::driver;
+
+    fn check(arms: &str) -> Result<(), String> {
+        let source = format!("deterministic fn f(r: Result<i64, String>, o: Option<i64>) -> i64 {{\n    let n = {}\n    return n\n}}\n", arms);
+        let program = driver::parse(&source).expect("parse");
+        driver::analyze(&program).map(|_| ()).map_err(|e| e.message)
+    }
+
+    #[test]
+    fn accepts_matches_that_cover_every_case() {
+        assert_eq!(check("match r { Ok(v) => v, Err(e) => 0 }"), Ok(()));
+        assert_eq!(check("match o { None => 0, Some(v) => v }"), Ok(()));
+        assert_eq!(check("match r { Ok(v) => v, _ => 0 }"), Ok(()));
+    }
+
+    #[test]
+    fn rejects_missing_and_unreachable_arms() {
+        assert_eq!(check("match r { Ok(v) => v }").unwrap_err(), "Non-exhaustive `match`: `Err(_)` not covered");
+        assert_eq!(check("match o { Some(v) => v }").unwrap_err(), "Non-exhaustive `match`: `None` not covered");
+        assert_eq!(check("match r { Ok(v) => v, Ok(w) => w, Err(e) => 0 }").unwrap_err(), "Unreachable `match` arm: `Ok(_)` is already matched");
+        assert_eq!(check("match o { Some(v) => v, None => 0, _ => 1 }").unwrap_err(), "Unreachable `_` arm: every case is already matched");
+        assert_eq!(check("match r { _ => 0, Ok(v) => v }").unwrap_err(), "Unreachable `match` arm");
+    }
+}
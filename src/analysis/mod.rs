@@ -1,3 +1,5 @@
 pub mod determinism;
 pub mod taint;
-pub mod scope;
\ No newline at end of file
+pub mod scope;
+pub mod types;
+pub mod exhaustive;
\ No newline at end of file
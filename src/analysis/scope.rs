@@ -1,92 +1,277 @@
+use crate::analysis::determinism::SymbolTable;
 use crate::ast::*;
-use std::collections::HashSet;
+use crate::diagnostics::Diagnostic;
+use std::collections::{HashMap, HashSet};
 
+// Pass 3: Scope Police
+// Tanımsız değişken kullanımını yakalar ve escape analizi yapar: `spawn` yalnızca bir
+// `scope` bloğu içinde yapılabilir; task handle'ı sadece bir değişkene bağlanabilir ya da
+// await edilebilir. Return ile, dıştaki bir değişkene atanarak ya da bir değerin (dizi,
+// Option, struct, metot argümanı) içinde scope'undan kaçamaz; bu yüzden scope bittikten
+// sonra await edilemez.
 pub struct ScopeAnalyzer {
-    defined_vars: HashSet<String>,
+    // Değişken -> tanımlandığı andaki scope derinliği
+    defined_vars: HashMap<String, usize>,
+    // Task handle tutan değişken -> handle'ın sahibi olan scope (derinlik, isim)
+    task_vars: HashMap<String, (usize, String)>,
+    // İç içe `scope` blokları (dıştan içe)
+    task_scopes: Vec<String>,
+    // Değişken olmayan isimler fonksiyon değeridir: `xs.map(double)`
+    functions: HashSet<String>,
+    // İçinde bulunulan closure sayısı: task handle'ı closure'a yakalanamaz
+    closures: usize,
+}
+
+impl Default for ScopeAnalyzer {
+    fn default() -> Self { Self::new() }
 }
 
 impl ScopeAnalyzer {
     pub fn new() -> Self {
-        Self { defined_vars: HashSet::new() }
+        Self { defined_vars: HashMap::new(), task_vars: HashMap::new(), task_scopes: Vec::new(), functions: HashSet::new(), closures: 0 }
     }
 
-    pub fn analyze(&mut self, func: &FunctionDef) -> Result<(), String> {
+    pub fn analyze(&mut self, func: &FunctionDef, symbols: &SymbolTable) -> Result<(), Diagnostic> {
         self.defined_vars.clear();
+        self.task_vars.clear();
+        self.task_scopes.clear();
+        self.functions = symbols.functions.keys().cloned().collect();
+        self.closures = 0;
         for param in &func.params {
-            self.defined_vars.insert(param.name.clone());
+            self.defined_vars.insert(param.name.clone(), 0);
         }
         self.visit_block(&func.body)
     }
 
-    fn visit_block(&mut self, block: &Block) -> Result<(), String> {
-        let backup = self.defined_vars.clone();
+    fn visit_block(&mut self, block: &Block) -> Result<(), Diagnostic> {
+        let backup = (self.defined_vars.clone(), self.task_vars.clone());
         for stmt in &block.statements {
             self.visit_stmt(stmt)?;
         }
-        self.defined_vars = backup;
+        (self.defined_vars, self.task_vars) = backup;
         Ok(())
     }
 
-    fn visit_stmt(&mut self, stmt: &Statement) -> Result<(), String> {
-        match stmt {
-            Statement::Let(l) => {
+    fn depth(&self) -> usize { self.task_scopes.len() }
+
+    // İfade bir task handle'ı mı üretiyor? (spawn ya da handle tutan değişken)
+    fn task_owner(&self, expr: &Expr) -> Option<(usize, String)> {
+        match &expr.kind {
+            ExprKind::Spawn(_) => self.task_scopes.last().map(|name| (self.depth(), name.clone())),
+            ExprKind::Identifier(name) => self.task_vars.get(name).cloned(),
+            _ => None,
+        }
+    }
+
+    // Değer olarak kullanılan ifade: içinde task handle'ı taşınamaz (takip edilemezdi)
+    fn visit_value(&mut self, expr: &Expr) -> Result<(), Diagnostic> {
+        self.visit_expr(expr)?;
+        match self.task_owner(expr) {
+            Some((_, scope)) => Err(Diagnostic::error("scope", "Task handle can only be awaited or bound to a variable".to_string(), expr.span)
+                .with_note(format!("stored in a value it could outlive scope `{}`; `await` it first", scope))),
+            None => Ok(()),
+        }
+    }
+
+    fn visit_stmt(&mut self, stmt: &Statement) -> Result<(), Diagnostic> {
+        match &stmt.kind {
+            StmtKind::Let(l) => {
                 self.visit_expr(&l.value)?;
-                self.defined_vars.insert(l.name.clone());
-            }
-            Statement::Assign { name, value } => {
-                if !self.defined_vars.contains(name) {
-                    return Err(format!("Undefined variable: {}", name));
+                self.defined_vars.insert(l.name.clone(), self.depth());
+                match self.task_owner(&l.value) {
+                    Some(owner) => { self.task_vars.insert(l.name.clone(), owner); }
+                    None => { self.task_vars.remove(&l.name); }
                 }
+            }
+            StmtKind::Assign { name, value } => {
+                let Some(&var_depth) = self.defined_vars.get(name) else {
+                    return Err(Diagnostic::error("scope", format!("Undefined variable: {}", name), stmt.span));
+                };
                 self.visit_expr(value)?;
+                match self.task_owner(value) {
+                    Some((owner_depth, scope)) if var_depth < owner_depth => {
+                        return Err(Diagnostic::error("scope", format!("Task handle escapes scope `{}` through assignment to `{}`", scope, name), stmt.span)
+                            .with_note(format!("`{}` is declared outside `{}` and would outlive the task", name, scope)));
+                    }
+                    Some(owner) => { self.task_vars.insert(name.clone(), owner); }
+                    None => { self.task_vars.remove(name); }
+                }
             }
-            Statement::If { condition, then_block, else_block } => {
-                self.visit_expr(condition)?;
+            StmtKind::If { condition, then_block, else_block } => {
+                self.visit_value(condition)?;
                 self.visit_block(then_block)?;
                 if let Some(else_b) = else_block { self.visit_block(else_b)?; }
             }
-            Statement::While { condition, body } => {
-                self.visit_expr(condition)?;
+            StmtKind::While { condition, body } => {
+                self.visit_value(condition)?;
                 self.visit_block(body)?;
             }
-            Statement::For { var, start, end, step, body } => {
-                self.visit_expr(start)?;
-                self.visit_expr(end)?;
-                if let Some(s) = step { self.visit_expr(s)?; } 
-                self.defined_vars.insert(var.clone());
+            StmtKind::For { var, start, end, step, body } => {
+                self.visit_value(start)?;
+                self.visit_value(end)?;
+                if let Some(s) = step { self.visit_value(s)?; }
+                self.defined_vars.insert(var.clone(), self.depth());
                 self.visit_block(body)?;
             }
-            Statement::ScopeBlock { body, .. } => self.visit_block(body)?,
-            Statement::ValidateBlock { target, success_scope, .. } => {
-                if !self.defined_vars.contains(target) {
-                    return Err(format!("Undefined variable in validate: {}", target));
+            StmtKind::ForIn { binding, iterable, body } => {
+                self.visit_value(iterable)?;
+                for name in binding.names() { self.defined_vars.insert(name.to_string(), self.depth()); }
+                self.visit_block(body)?;
+            }
+            StmtKind::ScopeBlock { name, body } => {
+                self.task_scopes.push(name.clone());
+                let result = self.visit_block(body);
+                self.task_scopes.pop();
+                result?;
+            }
+            StmtKind::AwaitAll if self.task_scopes.is_empty() => {
+                return Err(Diagnostic::error("scope", "`await all` outside of a `scope` block".to_string(), stmt.span)
+                    .with_note("`await all` waits for the tasks of the enclosing scope"));
+            }
+            StmtKind::AwaitAll => {}
+            StmtKind::ValidateBlock { target, on_fail, success_scope, .. } => {
+                if !self.defined_vars.contains_key(target) {
+                    return Err(Diagnostic::error("scope", format!("Undefined variable in validate: {}", target), stmt.span));
                 }
+                if let Some(b) = on_fail { self.visit_block(b)?; }
                 self.visit_block(success_scope)?;
             }
-            Statement::ExprStmt(expr) => self.visit_expr(expr)?,
-            Statement::Return(Some(e)) => self.visit_expr(e)?,
-            Statement::Return(None) => {},
+            StmtKind::ValidateLet { name, target, on_fail, .. } => {
+                if !self.defined_vars.contains_key(target) {
+                    return Err(Diagnostic::error("scope", format!("Undefined variable in validate: {}", target), stmt.span));
+                }
+                self.visit_block(on_fail)?;
+                self.defined_vars.insert(name.clone(), self.depth());
+            }
+            StmtKind::ExprStmt(expr) => self.visit_expr(expr)?,
+            StmtKind::Return(Some(e)) => {
+                self.visit_expr(e)?;
+                if let Some((_, scope)) = self.task_owner(e) {
+                    return Err(Diagnostic::error("scope", format!("Task handle cannot be returned from scope `{}`", scope), e.span)
+                        .with_note("the scope cancels its tasks on exit; return `await <task>` instead"));
+                }
+            }
+            StmtKind::Return(None) => {},
         }
         Ok(())
     }
 
-    fn visit_expr(&self, expr: &Expr) -> Result<(), String> {
-        match expr {
-            Expr::Identifier(name) => {
-                if !self.defined_vars.contains(name) {
-                    return Err(format!("Undefined variable used: {}", name));
+    fn visit_expr(&mut self, expr: &Expr) -> Result<(), Diagnostic> {
+        match &expr.kind {
+            ExprKind::Identifier(name) if !self.defined_vars.contains_key(name) && !self.functions.contains(name) => {
+                return Err(Diagnostic::error("scope", format!("Undefined variable used: {}", name), expr.span));
+            }
+            ExprKind::Identifier(name) if self.closures > 0 && self.task_vars.contains_key(name) => {
+                return Err(Diagnostic::error("scope", format!("Task handle `{}` cannot be captured by a closure", name), expr.span)
+                    .with_note("the closure could outlive the scope that owns the task"));
+            }
+            ExprKind::Binary(l, _, r) => { self.visit_value(l)?; self.visit_value(r)?; }
+            ExprKind::Unary(_, e) => self.visit_value(e)?,
+            ExprKind::Call(_, args) => { for arg in args { self.visit_value(arg)?; } }
+            ExprKind::Spawn(e) => {
+                if self.task_scopes.is_empty() {
+                    return Err(Diagnostic::error("scope", "`spawn` outside of a `scope` block".to_string(), expr.span)
+                        .with_note("wrap it in `scope Name { ... }` so the task is joined or cancelled when the scope ends"));
+                }
+                if self.closures > 0 {
+                    return Err(Diagnostic::error("scope", "`spawn` cannot be used inside a closure".to_string(), expr.span)
+                        .with_note("the closure could run after its scope has ended"));
                 }
+                self.visit_value(e)?
             }
-            Expr::Binary(l, _, r) => { self.visit_expr(l)?; self.visit_expr(r)?; }
-            Expr::Call(_, args) => { for arg in args { self.visit_expr(arg)?; } }
-            Expr::Spawn(e) => self.visit_expr(e)?,
-            Expr::Await(e) => self.visit_expr(e)?,
-            Expr::Infra(call) => { for arg in &call.args { self.visit_expr(arg)?; } }
-            Expr::JsonField(source, _) => self.visit_expr(source)?,
+            // Handle'ın doğrudan await edilmesi serbesttir (değişken hâlâ kendi scope'undadır)
+            ExprKind::Await(e) => self.visit_expr(e)?,
+            ExprKind::Infra(call) => {
+                for arg in &call.args { self.visit_value(arg)?; }
+                if let Some(f) = &call.config.fallback { self.visit_value(f)?; }
+            }
+            ExprKind::JsonField(source, _) => self.visit_value(source)?,
             // YENİ: Array ve Index içini gezme
-            Expr::ArrayLiteral(elems) => { for e in elems { self.visit_expr(e)?; } }
-            Expr::Index(arr, idx) => { self.visit_expr(arr)?; self.visit_expr(idx)?; }
+            ExprKind::ArrayLiteral(elems) => { for e in elems { self.visit_value(e)?; } }
+            ExprKind::MapLiteral(entries) => { for (k, v) in entries { self.visit_value(k)?; self.visit_value(v)?; } }
+            ExprKind::Index(arr, idx) => { self.visit_value(arr)?; self.visit_value(idx)?; }
+            ExprKind::Slice(arr, from, to) => { self.visit_value(arr)?; self.visit_value(from)?; self.visit_value(to)?; }
+            ExprKind::MethodCall(target, _, args) => { self.visit_value(target)?; for a in args { self.visit_value(a)?; } }
+            ExprKind::Interpolated(parts) => { for e in parts.iter().filter_map(StrPart::expr) { self.visit_value(e)?; } }
+            ExprKind::Field(e, _) => self.visit_value(e)?,
+            ExprKind::StructLiteral(_, fields) => { for (_, v) in fields { self.visit_value(v)?; } }
+            ExprKind::Ctor(_, Some(payload)) => self.visit_value(payload)?,
+            ExprKind::Match(scrutinee, arms) => {
+                self.visit_value(scrutinee)?;
+                // Desenin bağladığı isim sadece kendi kolunda görünür
+                for arm in arms {
+                    let backup = self.defined_vars.clone();
+                    if let Pattern::Ctor(_, Some(binding)) = &arm.pattern { self.defined_vars.insert(binding.clone(), self.depth()); }
+                    match &arm.body {
+                        ArmBody::Expr(e) => self.visit_value(e)?,
+                        ArmBody::Block(b) => self.visit_block(b)?,
+                    }
+                    self.defined_vars = backup;
+                }
+            }
+            ExprKind::Lambda(params, body) => {
+                let backup = (self.defined_vars.clone(), self.task_vars.clone());
+                for (name, _) in params {
+                    self.defined_vars.insert(name.clone(), self.depth());
+                    self.task_vars.remove(name);
+                }
+                self.closures += 1;
+                let result = self.visit_value(body);
+                self.closures -= 1;
+                (self.defined_vars, self.task_vars) = backup;
+                result?;
+            }
             _ => {}
         }
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser;
+
+    fn check(body: &str) -> Result<(), String> {
+        let source = format!("deterministic fn work(n: i64) -> i64 {{ return n }}\nstruct Box {{ n: i64 }}\nnondeterministic fn main(input: Untrusted) {{\n    {}\n}}\n", body);
+        let (program, errors) = parser::parse_program(&source);
+        assert!(errors.is_empty(), "{:?}", errors);
+        let symbols = SymbolTable::from_program(&program);
+        let main = program.functions().find(|f| f.name == "main").unwrap();
+        ScopeAnalyzer::new().analyze(main, &symbols).map_err(|e| e.message)
+    }
+
+    #[test]
+    fn handles_can_be_bound_and_awaited_inside_their_scope() {
+        assert_eq!(check("scope S {\n        let a = spawn work(1)\n        let b = a\n        spawn DB.log(\"x\")\n        DB.log(await b)\n        await all\n    }"), Ok(()));
+        assert_eq!(check("scope S {\n        let a = spawn work(1)\n        let b = spawn work(2)\n        if true { b = a }\n        let n = await b\n    }"), Ok(()));
+        assert_eq!(check("let xs = [1]\n    let ys = xs.map(|x| x + 1)"), Ok(()));
+    }
+
+    #[test]
+    fn rejects_spawn_and_await_all_outside_a_scope() {
+        assert_eq!(check("let a = spawn work(1)"), Err("`spawn` outside of a `scope` block".to_string()));
+        assert_eq!(check("await all"), Err("`await all` outside of a `scope` block".to_string()));
+        assert_eq!(check("DB.log(x)"), Err("Undefined variable used: x".to_string()));
+    }
+
+    #[test]
+    fn rejects_handles_that_escape_their_scope() {
+        let escaped = "Task handle can only be awaited or bound to a variable";
+        let cases = [
+            ("let t = spawn work(1)\n        let ts = [t]", escaped),
+            ("let o = Some(spawn work(1))", escaped),
+            ("let t = spawn work(1)\n        let b = Box { n: t }", escaped),
+            ("let ts = [0]\n        let t = spawn work(1)\n        ts.push(t)", escaped),
+            ("let t = spawn work(1)\n        let m = { \"a\": t }", escaped),
+            ("let t = spawn work(1)\n        let f = |x: i64| await t", "Task handle `t` cannot be captured by a closure"),
+            ("let xs = [1]\n        let ys = xs.map(|x| spawn work(x))", "`spawn` cannot be used inside a closure"),
+        ];
+        for (body, expected) in cases {
+            assert_eq!(check(&format!("scope S {{\n        {}\n    }}", body)), Err(expected.to_string()), "{}", body);
+        }
+        assert_eq!(check("let t = 0\n    scope S {\n        t = spawn work(1)\n    }"), Err("Task handle escapes scope `S` through assignment to `t`".to_string()));
+        let source = "scope S {\n        let t = spawn work(1)\n        return t\n    }";
+        assert_eq!(check(source), Err("Task handle cannot be returned from scope `S`".to_string()));
+    }
+}
@@ -1,56 +1,448 @@
 use crate::ast::*;
+use crate::diagnostics::Diagnostic;
+use crate::analysis::determinism::SymbolTable;
+use crate::builtins;
+use std::collections::HashMap;
 
-pub struct TaintAnalyzer;
+// Dış dünyadan veri getiren builtin'ler (bkz. builtins::Builtin::taint_source)
+fn is_taint_source(name: &str) -> bool { builtins::lookup(name).map(|b| b.taint_source).unwrap_or(false) }
 
-impl TaintAnalyzer {
-    pub fn check(func: &FunctionDef) -> Result<(), String> { Self::visit_block(&func.body) }
+// Pass 2: Taint Police
+// Untrusted parametreler ve dış kaynak sonuçları "kirli" işaretlenir; let/atama/ifade
+// üzerinden yayılır. Kirli veri validate bloğunun success kapsamından geçmeden
+// çağrı argümanı, return değeri veya altyapı argümanı olamaz.
+pub struct TaintAnalyzer<'a> {
+    symbols: &'a SymbolTable,
+    // Kapsam yığını: değişken -> kirliyse kirliliğin kaynağı (hata mesajı için)
+    scopes: Vec<HashMap<String, Option<String>>>,
+    returns_untrusted: bool,
+}
 
-    fn visit_block(block: &Block) -> Result<(), String> { 
-        for stmt in &block.statements { Self::visit_stmt(stmt)?; } 
-        Ok(()) 
+impl<'a> TaintAnalyzer<'a> {
+    pub fn check(func: &FunctionDef, symbols: &'a SymbolTable) -> Result<(), Diagnostic> {
+        let mut params = HashMap::new();
+        for param in &func.params {
+            let origin = (param.param_type == TypeRef::Untrusted).then(|| format!("parameter `{}` (Untrusted)", param.name));
+            params.insert(param.name.clone(), origin);
+        }
+        let mut pass = TaintAnalyzer { symbols, scopes: vec![params], returns_untrusted: func.return_type == TypeRef::Untrusted };
+        pass.visit_block(&func.body)
+    }
+
+    fn visit_block(&mut self, block: &Block) -> Result<(), Diagnostic> {
+        self.visit_block_with(block, HashMap::new())
+    }
+
+    fn visit_block_with(&mut self, block: &Block, locals: HashMap<String, Option<String>>) -> Result<(), Diagnostic> {
+        self.scopes.push(locals);
+        let result = block.statements.iter().try_for_each(|stmt| self.visit_stmt(stmt));
+        self.scopes.pop();
+        result
+    }
+
+    fn lookup(&self, name: &str) -> Option<String> {
+        self.scopes.iter().rev().find_map(|s| s.get(name)).cloned().flatten()
     }
 
-    fn visit_stmt(stmt: &Statement) -> Result<(), String> {
-        match stmt {
-            Statement::Let(l) => Self::visit_expr(&l.value),
-            Statement::Assign { value, .. } => Self::visit_expr(value),
-            Statement::If { condition, then_block, else_block } => { 
-                Self::visit_expr(condition)?; 
-                Self::visit_block(then_block)?; 
-                if let Some(b) = else_block { Self::visit_block(b)?; } 
-                Ok(()) 
+    // Atama, değişkenin tanımlandığı en içteki kapsamı günceller
+    fn set(&mut self, name: &str, origin: Option<String>) {
+        match self.scopes.iter_mut().rev().find(|s| s.contains_key(name)) {
+            Some(scope) => { scope.insert(name.to_string(), origin); }
+            None => { self.scopes.last_mut().unwrap().insert(name.to_string(), origin); }
+        }
+    }
+
+    // İki dalın sonucunu birleştir: herhangi bir dalda kirlenen değişken kirlidir
+    fn join(&mut self, other: Vec<HashMap<String, Option<String>>>) {
+        for (scope, other_scope) in self.scopes.iter_mut().zip(other) {
+            for (name, origin) in other_scope {
+                if let Some(origin) = origin {
+                    let slot = scope.entry(name).or_insert(None);
+                    if slot.is_none() { *slot = Some(origin); }
+                }
+            }
+        }
+    }
+
+    fn visit_stmt(&mut self, stmt: &Statement) -> Result<(), Diagnostic> {
+        match &stmt.kind {
+            StmtKind::Let(l) => {
+                self.visit_expr(&l.value)?;
+                let origin = self.taint_of(&l.value);
+                self.scopes.last_mut().unwrap().insert(l.name.clone(), origin);
+                Ok(())
+            }
+            StmtKind::Assign { name, value } => {
+                self.visit_expr(value)?;
+                let origin = self.taint_of(value);
+                self.set(name, origin);
+                Ok(())
+            }
+            StmtKind::If { condition, then_block, else_block } => {
+                self.visit_expr(condition)?;
+                let before = self.scopes.clone();
+                self.visit_block(then_block)?;
+                let after_then = std::mem::replace(&mut self.scopes, before);
+                if let Some(b) = else_block { self.visit_block(b)?; }
+                self.join(after_then);
+                Ok(())
             },
-            Statement::While { condition, body } => { 
-                Self::visit_expr(condition)?; 
-                Self::visit_block(body)?; 
-                Ok(()) 
+            StmtKind::While { condition, body } => {
+                // Koşul, gövdenin bir önceki turda kirlettiği değişkenleri de görür
+                loop {
+                    let before = self.scopes.clone();
+                    self.visit_expr(condition)?;
+                    self.visit_block(body)?;
+                    self.join(before.clone());
+                    if self.scopes == before { return Ok(()); }
+                }
             },
-            Statement::For { start, end, step, body, .. } => {
-                Self::visit_expr(start)?;
-                Self::visit_expr(end)?;
-                if let Some(s) = step { Self::visit_expr(s)?; }
-                Self::visit_block(body)?;
-                Ok(())
+            StmtKind::For { start, end, step, body, var } => {
+                self.visit_expr(start)?;
+                self.visit_expr(end)?;
+                if let Some(s) = step { self.visit_expr(s)?; }
+                let origin = self.taint_of(start).or_else(|| self.taint_of(end));
+                loop {
+                    let before = self.scopes.clone();
+                    self.visit_block_with(body, HashMap::from([(var.clone(), origin.clone())]))?;
+                    self.join(before.clone());
+                    if self.scopes == before { return Ok(()); }
+                }
             },
-            Statement::ScopeBlock { body, .. } => Self::visit_block(body),
-            Statement::ValidateBlock { success_scope, .. } => Self::visit_block(success_scope),
+            StmtKind::ForIn { binding, iterable, body } => {
+                self.visit_expr(iterable)?;
+                let origin = self.taint_of(iterable);
+                loop {
+                    let before = self.scopes.clone();
+                    self.visit_block_with(body, binding.names().into_iter().map(|n| (n.to_string(), origin.clone())).collect())?;
+                    self.join(before.clone());
+                    if self.scopes == before { return Ok(()); }
+                }
+            },
+            StmtKind::ScopeBlock { body, .. } => self.visit_block(body),
+            StmtKind::ValidateBlock { target, schema, on_fail, success_scope } => {
+                self.check_schema_ref(schema, stmt.span)?;
+                if let Some(b) = on_fail { self.visit_block(b)?; }
+                // success kapsamı içinde hedef, temizlenmiş yeni bir bağ olarak görünür
+                self.visit_block_with(success_scope, HashMap::from([(target.clone(), None)]))
+            }
+            StmtKind::ValidateLet { name, schema, on_fail, .. } => {
+                self.check_schema_ref(schema, stmt.span)?;
+                self.visit_block(on_fail)?;
+                // on_fail akışı devam ettirirse doğrulanmamış değer `name`e sızardı
+                let diverges = matches!(on_fail.statements.last().map(|s| &s.kind), Some(StmtKind::Return(_)));
+                if !diverges {
+                    return Err(Diagnostic::error("taint", format!("`on_fail` of `let {} = validate ...` must leave the function", name), on_fail.span)
+                        .with_note("end the handler with `return`, e.g. `on_fail: return 0`"));
+                }
+                self.scopes.last_mut().unwrap().insert(name.clone(), None);
+                Ok(())
+            }
 
-            Statement::ExprStmt(e) | Statement::Return(Some(e)) => Self::visit_expr(e),
-            Statement::Return(None) => Ok(()),
+            StmtKind::ExprStmt(e) => self.visit_expr(e),
+            StmtKind::Return(Some(e)) => {
+                self.visit_expr(e)?;
+                if !self.returns_untrusted {
+                    if let Some(origin) = self.taint_of(e) {
+                        return Err(Self::error("a return value", e, origin));
+                    }
+                }
+                Ok(())
+            }
+            StmtKind::Return(None) | StmtKind::AwaitAll => Ok(()),
         }
     }
 
-    fn visit_expr(expr: &Expr) -> Result<(), String> {
-        match expr {
-            Expr::Binary(l, _, r) => { Self::visit_expr(l)?; Self::visit_expr(r) },
-            Expr::Call(_, args) => { for arg in args { Self::visit_expr(arg)?; } Ok(()) },
-            Expr::Spawn(e) | Expr::Await(e) => Self::visit_expr(e),
-            Expr::Infra(call) => { for arg in &call.args { Self::visit_expr(arg)?; } Ok(()) },
-            Expr::JsonField(source, _) => Self::visit_expr(source),
+    fn check_schema_ref(&self, schema: &Option<String>, span: Span) -> Result<(), Diagnostic> {
+        match schema {
+            Some(name) if self.symbols.record_fields(name).is_none() => Err(Diagnostic::error("schema", format!("Unknown schema: {}", name), span)),
+            _ => Ok(()),
+        }
+    }
+
+    // Schema tanımı: alan tipleri ve kısıtların tiplere uygunluğu
+    pub fn check_schema(schema: &SchemaDef) -> Result<(), Diagnostic> {
+        for field in &schema.fields {
+            if !matches!(field.field_type, TypeRef::Integer | TypeRef::String) {
+                return Err(Diagnostic::error("schema", format!("Schema field `{}` must be `i64` or `String`", field.name), field.span));
+            }
+            for c in &field.constraints {
+                let ok = match c {
+                    Constraint::Range(min, max) => field.field_type == TypeRef::Integer && min <= max,
+                    Constraint::Len(min, max) => field.field_type == TypeRef::String && 0 <= *min && min <= max,
+                    Constraint::Regex(re) => field.field_type == TypeRef::String && regex::Regex::new(re).is_ok(),
+                };
+                if !ok {
+                    return Err(Diagnostic::error("schema", format!("Invalid constraint `{}` on schema field `{}: {}`", c, field.name, field.field_type), field.span)
+                        .with_note("`range(min, max)` applies to i64, `len(min, max)` and `regex(\"..\")` to String"));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn visit_expr(&mut self, expr: &Expr) -> Result<(), Diagnostic> {
+        match &expr.kind {
+            ExprKind::Binary(l, _, r) => { self.visit_expr(l)?; self.visit_expr(r) },
+            ExprKind::Unary(_, e) => self.visit_expr(e),
+            ExprKind::Call(name, args) => {
+                let params = self.symbols.functions.get(name).map(|f| &f.params);
+                for (i, arg) in args.iter().enumerate() {
+                    self.visit_expr(arg)?;
+                    // Untrusted parametre bekleyen kullanıcı fonksiyonuna kirli veri verilebilir
+                    let accepts_untrusted = params.and_then(|p| p.get(i)).map(|p| p.param_type == TypeRef::Untrusted).unwrap_or(false);
+                    if accepts_untrusted { continue; }
+                    if let Some(origin) = self.taint_of(arg) {
+                        return Err(Self::error(&format!("an argument to `{}`", name), arg, origin));
+                    }
+                }
+                Ok(())
+            },
+            ExprKind::Spawn(e) | ExprKind::Await(e) => self.visit_expr(e),
+            ExprKind::Infra(call) => {
+                for arg in &call.args {
+                    self.visit_expr(arg)?;
+                    if let Some(origin) = self.taint_of(arg) {
+                        return Err(Self::error(&format!("an argument to infra call `{}.{}`", call.service, call.method), arg, origin));
+                    }
+                }
+                match &call.config.fallback { Some(f) => self.visit_expr(f), None => Ok(()) }
+            },
+            ExprKind::JsonField(source, _) => self.visit_expr(source),
             // YENİ: Array ve Index taint kontrolü
-            Expr::ArrayLiteral(elems) => { for e in elems { Self::visit_expr(e)?; } Ok(()) }
-            Expr::Index(arr, idx) => { Self::visit_expr(arr)?; Self::visit_expr(idx)?; Ok(()) }
+            ExprKind::ArrayLiteral(elems) => { for e in elems { self.visit_expr(e)?; } Ok(()) }
+            ExprKind::MapLiteral(entries) => { for (k, v) in entries { self.visit_expr(k)?; self.visit_expr(v)?; } Ok(()) }
+            ExprKind::Index(arr, idx) => { self.visit_expr(arr)?; self.visit_expr(idx)?; Ok(()) }
+            ExprKind::Slice(arr, from, to) => { self.visit_expr(arr)?; self.visit_expr(from)?; self.visit_expr(to)?; Ok(()) }
+            ExprKind::MethodCall(target, method, args) if HIGHER_ORDER_METHODS.contains(&method.as_str()) => {
+                self.visit_expr(target)?;
+                let Some((func, rest)) = args.split_last() else { return Ok(()) };
+                for a in rest { self.visit_expr(a)?; }
+                // Kirli bir dizinin elemanları (ya da kirli fold başlangıcı) fonksiyonun parametrelerini kirletir
+                let origin = self.taint_of(target).or_else(|| rest.iter().find_map(|a| self.taint_of(a)));
+                match (&func.kind, origin) {
+                    (ExprKind::Lambda(params, body), origin) => self.visit_lambda(params, body, origin),
+                    (_, Some(origin)) => Err(Self::error(&format!("an argument to the function passed to `{}`", method), func, origin)),
+                    (_, None) => self.visit_expr(func),
+                }
+            }
+            ExprKind::MethodCall(target, method, args) => {
+                self.visit_expr(target)?;
+                for a in args { self.visit_expr(a)?; }
+                // m.insert(k, x): kirli bir değer map'i de kirletir (atama gibi)
+                if let ExprKind::Identifier(name) = &target.kind {
+                    if MUTATING_METHODS.contains(&method.as_str()) {
+                        if let Some(origin) = args.iter().find_map(|a| self.taint_of(a)) { self.set(name, Some(origin)); }
+                    }
+                }
+                Ok(())
+            }
+            ExprKind::Interpolated(parts) => { for e in parts.iter().filter_map(StrPart::expr) { self.visit_expr(e)?; } Ok(()) }
+            ExprKind::Field(e, _) => self.visit_expr(e),
+            ExprKind::StructLiteral(_, fields) => { for (_, v) in fields { self.visit_expr(v)?; } Ok(()) }
+            ExprKind::Ctor(_, Some(payload)) => self.visit_expr(payload),
+            ExprKind::Match(scrutinee, arms) => {
+                self.visit_expr(scrutinee)?;
+                // Kirli bir Result/Option'dan çıkarılan değer de kirlidir
+                let origin = self.taint_of(scrutinee);
+                for arm in arms {
+                    let mut locals = HashMap::new();
+                    if let Pattern::Ctor(_, Some(binding)) = &arm.pattern { locals.insert(binding.clone(), origin.clone()); }
+                    match &arm.body {
+                        ArmBody::Expr(e) => {
+                            self.scopes.push(locals);
+                            let result = self.visit_expr(e);
+                            self.scopes.pop();
+                            result?;
+                        }
+                        ArmBody::Block(b) => self.visit_block_with(b, locals)?,
+                    }
+                }
+                Ok(())
+            }
+            ExprKind::Lambda(params, body) => self.visit_lambda(params, body, None),
             _ => Ok(()),
         }
     }
-}
\ No newline at end of file
+
+    // Closure gövdesi kendi parametreleriyle gezilir; `Untrusted` yazılan parametre kirlidir
+    fn visit_lambda(&mut self, params: &[(String, Option<TypeRef>)], body: &Expr, origin: Option<String>) -> Result<(), Diagnostic> {
+        let locals = params.iter().map(|(name, ty)| {
+            let origin = match ty {
+                Some(TypeRef::Untrusted) => Some(format!("parameter `{}` (Untrusted)", name)),
+                _ => origin.clone(),
+            };
+            (name.clone(), origin)
+        }).collect();
+        self.scopes.push(locals);
+        let result = self.visit_expr(body);
+        self.scopes.pop();
+        result
+    }
+
+    // İfadenin değeri kirliyse kirliliğin kaynağını döndürür
+    fn taint_of(&self, expr: &Expr) -> Option<String> {
+        match &expr.kind {
+            ExprKind::Identifier(name) => self.lookup(name),
+            ExprKind::Literal(_) => None,
+            ExprKind::Binary(l, _, r) => self.taint_of(l).or_else(|| self.taint_of(r)),
+            ExprKind::Unary(_, e) => self.taint_of(e),
+            ExprKind::Call(name, _) => {
+                if is_taint_source(name) { return Some(format!("result of `{}`", name)); }
+                match self.symbols.functions.get(name) {
+                    Some(f) if f.return_type == TypeRef::Untrusted => Some(format!("result of `{}` (returns Untrusted)", name)),
+                    Some(_) => None,
+                    // Kirli veri yakalamış bir closure'ın sonucu da kirlidir
+                    None => self.lookup(name),
+                }
+            }
+            ExprKind::Infra(call) => {
+                let name = format!("{}.{}", call.service, call.method);
+                if is_taint_source(&name) { return Some(format!("result of `{}`", name)); }
+                // Zaman aşımında fallback değeri döner
+                call.config.fallback.as_ref().and_then(|f| self.taint_of(f))
+            }
+            ExprKind::JsonField(_, key) => Some(format!("result of `json(.., \"{}\")`", key)),
+            ExprKind::Spawn(e) | ExprKind::Await(e) => self.taint_of(e),
+            ExprKind::ArrayLiteral(elems) => elems.iter().find_map(|e| self.taint_of(e)),
+            ExprKind::MapLiteral(entries) => entries.iter().find_map(|(k, v)| self.taint_of(k).or_else(|| self.taint_of(v))),
+            ExprKind::Index(arr, idx) => self.taint_of(arr).or_else(|| self.taint_of(idx)),
+            // Kirli sınırlarla alınan dilim de kirlidir (indeks gibi)
+            ExprKind::Slice(arr, from, to) => self.taint_of(arr).or_else(|| self.taint_of(from)).or_else(|| self.taint_of(to)),
+            // `m.get(k)` indeks gibi, `xs.map(f)` fonksiyonun yakaladıklarıyla kirlenir
+            ExprKind::MethodCall(target, _, args) => self.taint_of(target).or_else(|| args.iter().find_map(|a| self.taint_of(a))),
+            // Kirli bir parça tüm metni kirletir ("a" + x ile aynı)
+            ExprKind::Interpolated(parts) => parts.iter().filter_map(StrPart::expr).find_map(|e| self.taint_of(e)),
+            ExprKind::Field(e, _) => self.taint_of(e),
+            ExprKind::StructLiteral(_, fields) => fields.iter().find_map(|(_, v)| self.taint_of(v)),
+            ExprKind::Ctor(_, payload) => payload.as_ref().and_then(|p| self.taint_of(p)),
+            // Kollar kapsamın bağladığı değeri döndürebilir: scrutinee kirliyse sonuç da kirli
+            ExprKind::Match(scrutinee, arms) => self.taint_of(scrutinee).or_else(|| arms.iter().find_map(|arm| match &arm.body {
+                ArmBody::Expr(e) => self.taint_of(e),
+                ArmBody::Block(_) => None,
+            })),
+            // Closure yakaladığı kirli veriyi taşır (parametreleri gölgeler)
+            ExprKind::Lambda(params, body) => {
+                let mut scopes = self.scopes.clone();
+                scopes.push(params.iter().map(|(name, _)| (name.clone(), None)).collect());
+                TaintAnalyzer { symbols: self.symbols, scopes, returns_untrusted: false }.taint_of(body)
+            }
+        }
+    }
+
+    fn error(usage: &str, expr: &Expr, origin: String) -> Diagnostic {
+        Diagnostic::error("taint", format!("Untrusted data used as {} without validation", usage), expr.span)
+            .with_note(format!("tainted by: {}", origin))
+            .with_note("pass the value through `validate <name> { success: { ... } }` first")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser;
+
+    fn check(source: &str) -> Result<(), Diagnostic> {
+        let (program, errors) = parser::parse_program(source);
+        assert!(errors.is_empty(), "{:?}", errors);
+        let symbols = SymbolTable::from_program(&program);
+        let result = program.functions().try_for_each(|f| TaintAnalyzer::check(f, &symbols));
+        result
+    }
+
+    // Hata mesajı ve ilk not (kirliliğin kaynağı)
+    fn error(source: &str) -> (String, String) {
+        let err = check(source).expect_err("taint hatası bekleniyordu");
+        (err.message, err.notes[0].clone())
+    }
+
+    const SCHEMA: &str = "schema Payment { amount: i64 range(1, 100) }\n";
+
+    #[test]
+    fn propagates_through_let_binary_and_calls() {
+        let (message, origin) = error("nondeterministic fn main(raw: Untrusted) {\n    let a = raw\n    let b = \"x: \" + a\n    DB.log(b)\n}\n");
+        assert_eq!(message, "Untrusted data used as an argument to `DB.log` without validation");
+        assert_eq!(origin, "tainted by: parameter `raw` (Untrusted)");
+        let (message, origin) = error("nondeterministic fn main() {\n    let page = HTTP.get(\"http://x\")\n    DB.log(page)\n}\n");
+        assert_eq!(message, "Untrusted data used as an argument to `DB.log` without validation");
+        assert_eq!(origin, "tainted by: result of `HTTP.get`");
+        // Atama kirliliği taşır, temiz bir değerle üzerine yazmak temizler
+        let (_, origin) = error("deterministic fn id(x: i64) -> i64 { return x }\nnondeterministic fn main(raw: Untrusted) {\n    let n = 1\n    if true { n = raw }\n    DB.log(id(n))\n}\n");
+        assert_eq!(origin, "tainted by: parameter `raw` (Untrusted)");
+        assert!(check("nondeterministic fn main(raw: Untrusted) {\n    let n = raw\n    n = 1\n    DB.log(n + 1)\n}\n").is_ok());
+    }
+
+    #[test]
+    fn untrusted_parameters_and_returns_accept_tainted_values() {
+        assert!(check("nondeterministic fn keep(x: Untrusted) -> Untrusted { return x }\nnondeterministic fn main(raw: Untrusted) {\n    let y = keep(raw)\n}\n").is_ok());
+        let (message, origin) = error("nondeterministic fn keep(x: Untrusted) -> Untrusted { return x }\nnondeterministic fn main(raw: Untrusted) {\n    DB.log(keep(raw))\n}\n");
+        assert_eq!(message, "Untrusted data used as an argument to `DB.log` without validation");
+        assert_eq!(origin, "tainted by: result of `keep` (returns Untrusted)");
+        let (message, _) = error("deterministic fn leak(x: Untrusted) -> i64 { return x }\n");
+        assert_eq!(message, "Untrusted data used as a return value without validation");
+    }
+
+    #[test]
+    fn validated_values_are_clean_in_the_success_scope() {
+        let source = format!("{}nondeterministic fn main(raw: Untrusted) {{\n    validate raw {{ schema: Payment, success: {{ DB.log(raw) }} }}\n}}\n", SCHEMA);
+        assert!(check(&source).is_ok());
+        // success kapsamı dışında değer hâlâ kirlidir
+        let source = format!("{}nondeterministic fn main(raw: Untrusted) {{\n    validate raw {{ schema: Payment, success: {{ }} }}\n    DB.log(raw)\n}}\n", SCHEMA);
+        assert_eq!(error(&source).0, "Untrusted data used as an argument to `DB.log` without validation");
+        let source = format!("{}nondeterministic fn main(raw: Untrusted) {{\n    let p = validate raw {{ schema: Payment, on_fail: return }}\n    DB.log(p.amount)\n}}\n", SCHEMA);
+        assert!(check(&source).is_ok());
+        let err = check("nondeterministic fn main(raw: Untrusted) {\n    validate raw { schema: Missing, success: { } }\n}\n").unwrap_err();
+        assert_eq!(err.message, "Unknown schema: Missing");
+        assert_eq!(err.pass, "schema");
+    }
+
+    #[test]
+    fn schema_constraints_must_fit_the_field_type() {
+        let schema = |source: &str| {
+            let (program, errors) = parser::parse_program(source);
+            assert!(errors.is_empty(), "{:?}", errors);
+            let schema = program.items.iter().find_map(|i| match i { Item::Schema(s) => Some(s.clone()), _ => None }).unwrap();
+            TaintAnalyzer::check_schema(&schema)
+        };
+        assert!(schema(SCHEMA).is_ok());
+        let err = schema("schema Payment { amount: i64 range(10, 1) }
+").unwrap_err();
+        assert_eq!(err.message, "Invalid constraint `range(10, 1)` on schema field `amount: i64`");
+        assert_eq!(err.pass, "schema");
+        let err = schema("schema Payment { note: String regex(\"(\") }
+").unwrap_err();
+        assert_eq!(err.message, "Invalid constraint `regex(\"(\")` on schema field `note: String`");
+        let err = schema("schema Payment { ok: bool }
+").unwrap_err();
+        assert_eq!((err.message.as_str(), err.pass), ("Schema field `ok` must be `i64` or `String`", "schema"));
+    }
+
+    #[test]
+    fn on_fail_of_validate_let_must_diverge() {
+        let source = format!("{}nondeterministic fn main(raw: Untrusted) {{\n    let p = validate raw {{ schema: Payment, on_fail: {{ DB.log(\"bad\") }} }}\n    DB.log(p.amount)\n}}\n", SCHEMA);
+        let err = check(&source).unwrap_err();
+        assert_eq!(err.message, "`on_fail` of `let p = validate ...` must leave the function");
+        assert_eq!(err.notes, ["end the handler with `return`, e.g. `on_fail: return 0`"]);
+    }
+
+    #[test]
+    fn propagates_through_match_arms() {
+        let source = "nondeterministic fn main() {\n    let n = match HTTP.get(\"http://x\") {\n        Ok(body) => body,\n        Err(e) => \"\",\n    }\n    DB.log(n)\n}\n";
+        assert_eq!(error(source).1, "tainted by: result of `HTTP.get`");
+        let source = "nondeterministic fn main(raw: Untrusted) {\n    let r: Result<i64, String> = Ok(1)\n    match r {\n        Ok(n) => { DB.log(n) },\n        Err(e) => { DB.log(e) },\n    }\n}\n";
+        assert!(check(source).is_ok());
+    }
+
+    #[test]
+    fn propagates_through_closures() {
+        // Kirli bir dizinin elemanları closure parametresini kirletir
+        let source = "nondeterministic fn main(raw: Untrusted) {\n    let xs = [raw]\n    let ys = xs.map(|x| DB.log(x))\n}\n";
+        let (message, origin) = error(source);
+        assert_eq!(message, "Untrusted data used as an argument to `DB.log` without validation");
+        assert_eq!(origin, "tainted by: parameter `raw` (Untrusted)");
+        // Kirli veri yakalayan closure'ın sonucu da kirlidir
+        let source = "nondeterministic fn main(raw: Untrusted) {\n    let f = |x: i64| raw\n    DB.log(f(1))\n}\n";
+        assert_eq!(error(source).1, "tainted by: parameter `raw` (Untrusted)");
+        assert!(check("nondeterministic fn main(raw: Untrusted) {\n    let xs = [1, 2]\n    let ys = xs.map(|x| x * 2)\n    DB.log(ys.fold(0, |a, x| a + x))\n}\n").is_ok());
+    }
+}
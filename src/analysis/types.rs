@@ -0,0 +1,941 @@
+use crate::ast::*;
+use crate::analysis::determinism::SymbolTable;
+use crate::builtins;
+use crate::diagnostics::Diagnostic;
+use std::collections::{HashMap, HashSet};
+
+// Her ifadenin çıkarılan tipi (ExprId -> tip). Codegen, native/trait yolunu seçerken kullanır.
+#[derive(Debug, Clone, Default)]
+pub struct TypeTable {
+    exprs: HashMap<ExprId, TypeRef>,
+    // Değişken değil, değer olarak kullanılan fonksiyon olan isimler: `xs.map(double)`
+    functions: HashSet<ExprId>,
+}
+
+impl TypeTable {
+    pub fn get(&self, expr: &Expr) -> Option<&TypeRef> { self.exprs.get(&expr.id) }
+
+    pub fn is_function(&self, expr: &Expr) -> bool { self.functions.contains(&expr.id) }
+
+    pub fn with_type<'a>(&'a self, t: &'a TypeRef) -> impl Iterator<Item = ExprId> + 'a {
+        self.exprs.iter().filter(move |(_, et)| *et == t).map(|(id, _)| *id)
+    }
+}
+
+// Parse ile codegen arasındaki tip denetimi: Rust derleyicisine ulaşmadan önce
+// tip hatalarını Gojo seviyesinde raporlar. Tipi bilinemeyen ifadeler (spawn handle'ı,
+// boş dizi, dış servis çağrısı) `None` olarak taşınır ve serbestçe kullanılabilir.
+pub struct TypeChecker<'a> {
+    symbols: &'a SymbolTable,
+    table: &'a mut TypeTable,
+    vars: Vec<HashMap<String, Option<TypeRef>>>,
+    return_type: TypeRef,
+    // İç içe closure'ların gövde kapsamlarının `vars` içindeki yeri
+    closures: Vec<usize>,
+}
+
+fn err(message: String, span: Span) -> Diagnostic { Diagnostic::error("type", message, span) }
+
+// Untrusted da çalışma zamanında bir String'dir
+fn normalize(t: &TypeRef) -> TypeRef {
+    match t {
+        TypeRef::Untrusted => TypeRef::String,
+        TypeRef::Custom(n) if n == "String" => TypeRef::String,
+        TypeRef::Array(inner) => TypeRef::Array(Box::new(normalize(inner))),
+        TypeRef::Result(ok, e) => TypeRef::Result(Box::new(normalize(ok)), Box::new(normalize(e))),
+        TypeRef::Option(inner) => TypeRef::Option(Box::new(normalize(inner))),
+        TypeRef::Map(k, v) => TypeRef::Map(Box::new(normalize(k)), Box::new(normalize(v))),
+        TypeRef::Task(inner) => TypeRef::Task(Box::new(normalize(inner))),
+        TypeRef::Func(params, ret) => TypeRef::Func(params.iter().map(normalize).collect(), Box::new(normalize(ret))),
+        t => t.clone(),
+    }
+}
+
+fn compatible(expected: &TypeRef, actual: &TypeRef) -> bool { normalize(expected) == normalize(actual) }
+
+fn is_stringy(t: &TypeRef) -> bool { normalize(t) == TypeRef::String }
+
+// BTreeMap anahtarları sıralanabilir olmalı (f64 olamaz)
+fn is_key(t: &TypeRef) -> bool { matches!(normalize(t), TypeRef::Integer | TypeRef::Byte | TypeRef::Bool | TypeRef::String) }
+
+fn key_error(t: &TypeRef, span: Span) -> Diagnostic {
+    err(format!("Type {} cannot be used as a map key", t), span).with_note("map keys must be i64, u8, bool or String")
+}
+
+fn is_numeric(t: &TypeRef) -> bool { matches!(t, TypeRef::Integer | TypeRef::Float | TypeRef::Byte) }
+
+// Fonksiyon değerleri karşılaştırılamaz ve yazdırılamaz
+fn has_func(t: &TypeRef) -> bool {
+    match t {
+        TypeRef::Func(..) => true,
+        TypeRef::Array(inner) | TypeRef::Option(inner) | TypeRef::Task(inner) => has_func(inner),
+        TypeRef::Result(a, b) | TypeRef::Map(a, b) => has_func(a) || has_func(b),
+        _ => false,
+    }
+}
+
+// Üretilen Rust'ta Display'i olan tipler: DB.log ve `${..}` sadece bunları yazdırır
+fn is_printable(t: &TypeRef) -> bool {
+    matches!(normalize(t), TypeRef::Integer | TypeRef::Float | TypeRef::Byte | TypeRef::String | TypeRef::Bool | TypeRef::Custom(_))
+}
+
+fn function_type(f: &FunctionDef) -> TypeRef {
+    TypeRef::Func(f.params.iter().map(|p| p.param_type.clone()).collect(), Box::new(f.return_type.clone()))
+}
+
+impl<'a> TypeChecker<'a> {
+    pub fn check_struct(def: &StructDef, symbols: &SymbolTable) -> Result<(), Diagnostic> {
+        let mut seen = HashSet::new();
+        for field in &def.fields {
+            if !seen.insert(&field.name) {
+                return Err(err(format!("Duplicate field `{}` in struct `{}`", field.name, def.name), field.span));
+            }
+            Self::check_type_exists(&field.field_type, field.span, symbols)?;
+            if has_func(&field.field_type) {
+                return Err(err(format!("Field `{}` cannot hold a function", field.name), field.span)
+                    .with_note("struct values are compared and printed, functions cannot be"));
+            }
+        }
+        if symbols.schemas.contains_key(&def.name) || symbols.functions.contains_key(&def.name) {
+            return Err(err(format!("`{}` is defined more than once", def.name), def.name_span));
+        }
+        Ok(())
+    }
+
+    // Giriş noktası: derlenmiş shim ve yorumlayıcı main'e en fazla bir "Internet" argümanı verir
+    pub fn check_main(func: &FunctionDef) -> Result<(), Diagnostic> {
+        match func.params.as_slice() {
+            [] => Ok(()),
+            [p] if matches!(p.param_type, TypeRef::Untrusted | TypeRef::String) => Ok(()),
+            [p] => Err(err(format!("`main` parameter `{}` must be Untrusted or String, found {}", p.name, p.param_type), func.name_span)
+                .with_note("the program's input is passed to `main` as text")),
+            params => Err(err(format!("`main` takes at most one parameter, found {}", params.len()), func.name_span)
+                .with_note("declare `main()` or `main(input: Untrusted)`")),
+        }
+    }
+
+    fn check_type_exists(t: &TypeRef, span: Span, symbols: &SymbolTable) -> Result<(), Diagnostic> {
+        match t {
+            TypeRef::Array(inner) | TypeRef::Option(inner) => Self::check_type_exists(inner, span, symbols),
+            TypeRef::Result(ok, e) => { Self::check_type_exists(ok, span, symbols)?; Self::check_type_exists(e, span, symbols) }
+            TypeRef::Map(k, _) if !is_key(k) => Err(key_error(k, span)),
+            TypeRef::Map(_, v) => Self::check_type_exists(v, span, symbols),
+            TypeRef::Func(params, ret) => {
+                params.iter().try_for_each(|p| Self::check_type_exists(p, span, symbols))?;
+                Self::check_type_exists(ret, span, symbols)
+            }
+            TypeRef::Custom(name) if name != "String" && symbols.record_fields(name).is_none() => Err(err(format!("Unknown type: {}", name), span)),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn check(func: &FunctionDef, symbols: &'a SymbolTable, table: &'a mut TypeTable) -> Result<(), Diagnostic> {
+        for p in &func.params { Self::check_type_exists(&p.param_type, p.span, symbols)?; }
+        Self::check_type_exists(&func.return_type, func.name_span, symbols)?;
+
+        let params = func.params.iter().map(|p| (p.name.clone(), Some(p.param_type.clone()))).collect();
+        let mut pass = TypeChecker { symbols, table, vars: vec![params], return_type: func.return_type.clone(), closures: Vec::new() };
+        pass.visit_block(&func.body)?;
+
+        if func.return_type != TypeRef::Void && !Self::always_returns(&func.body) {
+            return Err(err(format!("Function `{}` may end without returning a value of type {}", func.name, func.return_type), func.name_span)
+                .with_note("every path must end with `return <value>`"));
+        }
+        Ok(())
+    }
+
+    // REPL: gövde denetlenir; son deyim bir ifadeyse değeri gösterileceği için kullanılmış
+    // sayılır (Unused `Result` hatası verilmez) ve tipi döndürülür
+    pub fn check_snippet(func: &FunctionDef, symbols: &'a SymbolTable, table: &'a mut TypeTable) -> Result<Option<TypeRef>, Diagnostic> {
+        let mut pass = TypeChecker { symbols, table, vars: vec![HashMap::new()], return_type: func.return_type.clone(), closures: Vec::new() };
+        let Some((last, rest)) = func.body.statements.split_last() else { return Ok(None) };
+        rest.iter().try_for_each(|s| pass.visit_stmt(s))?;
+        match &last.kind {
+            StmtKind::ExprStmt(e) => pass.infer(e),
+            _ => pass.visit_stmt(last).map(|_| None),
+        }
+    }
+
+    // Blok her yolda return ile bitiyor mu?
+    fn always_returns(block: &Block) -> bool {
+        match block.statements.last().map(|s| &s.kind) {
+            Some(StmtKind::Return(_)) => true,
+            Some(StmtKind::If { then_block, else_block: Some(else_block), .. }) => Self::always_returns(then_block) && Self::always_returns(else_block),
+            Some(StmtKind::ScopeBlock { body, .. }) => Self::always_returns(body),
+            Some(StmtKind::ValidateBlock { on_fail: Some(on_fail), success_scope, .. }) => Self::always_returns(on_fail) && Self::always_returns(success_scope),
+            Some(StmtKind::ExprStmt(Expr { kind: ExprKind::Match(_, arms), .. })) => arms.iter().all(|arm| matches!(&arm.body, ArmBody::Block(b) if Self::always_returns(b))),
+            _ => false,
+        }
+    }
+
+    fn visit_block(&mut self, block: &Block) -> Result<(), Diagnostic> {
+        self.visit_block_with(block, HashMap::new())
+    }
+
+    fn visit_block_with(&mut self, block: &Block, locals: HashMap<String, Option<TypeRef>>) -> Result<(), Diagnostic> {
+        self.vars.push(locals);
+        let result = block.statements.iter().try_for_each(|s| self.visit_stmt(s));
+        self.vars.pop();
+        result
+    }
+
+    fn lookup(&self, name: &str) -> Option<TypeRef> {
+        self.vars.iter().rev().find_map(|s| s.get(name)).cloned().flatten()
+    }
+
+    fn is_var(&self, name: &str) -> bool {
+        self.vars.iter().any(|s| s.contains_key(name))
+    }
+
+    // Closure gövdesinde, closure'ın dışında tanımlanmış (yakalanmış) değişken mi?
+    fn is_captured(&self, name: &str) -> bool {
+        self.closures.last().is_some_and(|&start| self.is_var(name) && !self.vars[start..].iter().any(|s| s.contains_key(name)))
+    }
+
+    fn captured_error(&self, name: &str, span: Span) -> Result<(), Diagnostic> {
+        if !self.is_captured(name) { return Ok(()); }
+        Err(err(format!("Cannot modify captured variable `{}` inside a closure", name), span)
+            .with_note("closures capture a copy of the variables they use; return the new value instead"))
+    }
+
+    fn define(&mut self, name: &str, t: Option<TypeRef>) {
+        self.vars.last_mut().unwrap().insert(name.to_string(), t);
+    }
+
+    // Beklenen tip bilindiğinde yukarıdan aşağı denetim: `Ok(1)` tek başına E'yi,
+    // `None` ise T'yi söylemez; tip bağlamdan (imza, return, let açıklaması) gelir.
+    fn expect(&mut self, expr: &Expr, expected: &TypeRef, context: &str) -> Result<(), Diagnostic> {
+        if normalize(expected) == TypeRef::Byte && self.byte_literal(expr)? { return Ok(()); }
+        match &expr.kind {
+            ExprKind::Ctor(c, payload) => {
+                let inner = match (c, normalize(expected)) {
+                    (Ctor::Ok, TypeRef::Result(t, _)) | (Ctor::Err, TypeRef::Result(_, t)) | (Ctor::Some, TypeRef::Option(t)) => Some(*t),
+                    (Ctor::None, TypeRef::Option(_)) => None,
+                    _ => return Err(err(format!("{} expects {}, found `{}`", context, expected, c.name()), expr.span)),
+                };
+                if let (Some(p), Some(t)) = (payload, inner) { self.expect(p, &t, &format!("`{}(..)`", c.name()))?; }
+                self.table.exprs.insert(expr.id, expected.clone());
+                Ok(())
+            }
+            ExprKind::Match(scrutinee, arms) => {
+                let t = self.check_match(scrutinee, arms, Some((expected, context)))?;
+                if let Some(t) = t { self.table.exprs.insert(expr.id, t); }
+                Ok(())
+            }
+            // Elemanlar tek tek beklenir: `let xs: Array<u8> = [1, 2]`
+            ExprKind::ArrayLiteral(elems) if matches!(normalize(expected), TypeRef::Array(_)) => {
+                let TypeRef::Array(inner) = normalize(expected) else { unreachable!() };
+                for e in elems { self.expect(e, &inner, &format!("Element of {}", context))?; }
+                self.table.exprs.insert(expr.id, expected.clone());
+                Ok(())
+            }
+            ExprKind::MapLiteral(entries) if matches!(normalize(expected), TypeRef::Map(..)) => {
+                let TypeRef::Map(k, v) = normalize(expected) else { unreachable!() };
+                for (key, value) in entries {
+                    self.expect(key, &k, &format!("Key of {}", context))?;
+                    self.expect(value, &v, &format!("Value of {}", context))?;
+                }
+                self.table.exprs.insert(expr.id, expected.clone());
+                Ok(())
+            }
+            // Parametre ve dönüş tipleri beklenen fonksiyon tipinden gelir: `let f: fn(i64) -> i64 = |x| x * 2`
+            ExprKind::Lambda(..) if matches!(normalize(expected), TypeRef::Func(..)) => {
+                let TypeRef::Func(params, ret) = normalize(expected) else { unreachable!() };
+                self.check_lambda(expr, Some(&params), Some(&ret), context)?;
+                Ok(())
+            }
+            _ => match self.infer(expr)? {
+                Some(actual) if !compatible(expected, &actual) => Err(err(format!("{} expects {}, found {}", context, expected, actual), expr.span)),
+                _ => Ok(()),
+            },
+        }
+    }
+
+    // Closure: tipi yazılmayan parametreler bağlamdan (beklenen fonksiyon tipi, map/filter/fold) gelir.
+    // Closure'ın tipini döndürür; gövdenin tipi bilinmiyorsa None.
+    fn check_lambda(&mut self, expr: &Expr, expected: Option<&[TypeRef]>, ret: Option<&TypeRef>, context: &str) -> Result<Option<TypeRef>, Diagnostic> {
+        let ExprKind::Lambda(params, body) = &expr.kind else { unreachable!() };
+        if let Some(expected) = expected.filter(|e| e.len() != params.len()) {
+            return Err(err(format!("{} expects a function of {} parameter(s), found a closure of {}", context, expected.len(), params.len()), expr.span));
+        }
+        let mut types = Vec::new();
+        for (i, (name, ty)) in params.iter().enumerate() {
+            let t = match (ty, expected.map(|e| &e[i])) {
+                (Some(t), Some(e)) if !compatible(t, e) => {
+                    return Err(err(format!("Closure parameter `{}` is declared as {}, but {} passes {}", name, t, context, e), expr.span));
+                }
+                (Some(t), _) => { Self::check_type_exists(t, expr.span, self.symbols)?; t.clone() }
+                (None, Some(e)) => e.clone(),
+                (None, None) => return Err(err(format!("Cannot infer the type of closure parameter `{}`", name), expr.span)
+                    .with_note(format!("annotate it, e.g. `|{}: i64| ...`", name))),
+            };
+            types.push(t);
+        }
+
+        self.vars.push(params.iter().map(|(n, _)| n.clone()).zip(types.iter().cloned().map(Some)).collect());
+        self.closures.push(self.vars.len() - 1);
+        let body_type = match ret {
+            Some(r) => self.expect(body, r, "Closure body").map(|_| Some(r.clone())),
+            None => self.infer(body),
+        };
+        self.closures.pop();
+        self.vars.pop();
+
+        let t = body_type?.map(|r| TypeRef::Func(types, Box::new(r)));
+        if let Some(t) = &t { self.table.exprs.insert(expr.id, t.clone()); }
+        Ok(t)
+    }
+
+    // map/filter/fold'un fonksiyon argümanı: closure ya da fonksiyon değeri. Dönüş tipini verir.
+    fn function_arg(&mut self, f: &Expr, params: &[TypeRef], ret: Option<&TypeRef>, context: &str) -> Result<Option<TypeRef>, Diagnostic> {
+        let actual = match &f.kind {
+            ExprKind::Lambda(..) => self.check_lambda(f, Some(params), ret, context)?,
+            _ => self.infer(f)?,
+        };
+        match actual.as_ref().map(normalize) {
+            Some(TypeRef::Func(ps, r)) => {
+                let expected = TypeRef::Func(params.to_vec(), Box::new(ret.cloned().unwrap_or_else(|| (*r).clone())));
+                let actual = TypeRef::Func(ps, r.clone());
+                if !compatible(&expected, &actual) {
+                    return Err(err(format!("{} expects {}, found {}", context, expected, actual), f.span));
+                }
+                Ok(Some(*r))
+            }
+            Some(other) => Err(err(format!("{} expects a function, found {}", context, other), f.span)),
+            None => Ok(None),
+        }
+    }
+
+    // Tamsayı literal'i u8 beklenen yerde byte olur (aralık dışıysa hata)
+    fn byte_literal(&mut self, expr: &Expr) -> Result<bool, Diagnostic> {
+        let ExprKind::Literal(Literal::Int(n)) = expr.kind else { return Ok(false) };
+        if !(0..=255).contains(&n) {
+            return Err(err(format!("Literal `{}` is out of range for u8", n), expr.span).with_note("u8 holds values 0..=255"));
+        }
+        self.table.exprs.insert(expr.id, TypeRef::Byte);
+        Ok(true)
+    }
+
+    // match: scrutinee Result ya da Option olmalı, desenler tipe uymalı, kolların tipi aynı olmalı.
+    // Return ile biten blok kolları değer üretmez, her tiple uyumludur.
+    fn check_match(&mut self, scrutinee: &Expr, arms: &[MatchArm], expected: Option<(&TypeRef, &str)>) -> Result<Option<TypeRef>, Diagnostic> {
+        let scrutinee_type = self.infer(scrutinee)?.map(|t| normalize(&t));
+        if let Some(t) = &scrutinee_type {
+            if !matches!(t, TypeRef::Result(..) | TypeRef::Option(_)) {
+                return Err(err(format!("Cannot match on a value of type {}", t), scrutinee.span)
+                    .with_note("`match` works on `Result<T, E>` and `Option<T>`"));
+            }
+        }
+
+        let mut result: Option<TypeRef> = expected.map(|(t, _)| t.clone());
+        for arm in arms {
+            let mut locals = HashMap::new();
+            if let Pattern::Ctor(c, binding) = &arm.pattern {
+                let payload = match (c, &scrutinee_type) {
+                    (_, None) => None,
+                    (Ctor::Ok, Some(TypeRef::Result(t, _))) | (Ctor::Err, Some(TypeRef::Result(_, t))) | (Ctor::Some, Some(TypeRef::Option(t))) => Some((**t).clone()),
+                    (Ctor::None, Some(TypeRef::Option(_))) => None,
+                    (_, Some(t)) => return Err(err(format!("Pattern `{}` does not fit a value of type {}", c.name(), t), arm.span)),
+                };
+                if let Some(b) = binding { locals.insert(b.clone(), payload); }
+            }
+
+            let (arm_type, span) = match &arm.body {
+                ArmBody::Expr(e) => {
+                    self.vars.push(locals);
+                    let t = match expected {
+                        Some((t, context)) => self.expect(e, t, context).map(|_| Some(t.clone())),
+                        None => self.infer(e),
+                    };
+                    self.vars.pop();
+                    (t?, e.span)
+                }
+                ArmBody::Block(b) => {
+                    self.visit_block_with(b, locals)?;
+                    if Self::always_returns(b) { continue; }
+                    (Some(TypeRef::Void), b.span)
+                }
+            };
+            match (&result, arm_type) {
+                (Some(first), Some(t)) if !compatible(first, &t) => {
+                    return Err(err(format!("`match` arms have different types: expected {}, found {}", first, t), span));
+                }
+                (None, t) => result = t,
+                _ => {}
+            }
+        }
+        Ok(result)
+    }
+
+    fn visit_stmt(&mut self, stmt: &Statement) -> Result<(), Diagnostic> {
+        match &stmt.kind {
+            StmtKind::Let(l) => {
+                let t = match &l.ty {
+                    Some(ty) => {
+                        Self::check_type_exists(ty, stmt.span, self.symbols)?;
+                        self.expect(&l.value, ty, &format!("`{}`", l.name))?;
+                        Some(ty.clone())
+                    }
+                    None => self.infer(&l.value)?,
+                };
+                if t == Some(TypeRef::Void) {
+                    return Err(err(format!("`{}` cannot hold the result of a Void expression", l.name), l.value.span));
+                }
+                if t.is_none() && matches!(l.value.kind, ExprKind::Ctor(..)) {
+                    return Err(err(format!("Cannot infer the type of `{}`", l.name), l.value.span)
+                        .with_note(format!("add a type annotation, e.g. `let {}: Result<i64, String> = ...`", l.name)));
+                }
+                self.define(&l.name, t);
+            }
+            StmtKind::Assign { name, value } => {
+                self.captured_error(name, stmt.span)?;
+                match self.lookup(name) {
+                    Some(expected) => self.expect(value, &expected, &format!("Assignment to `{}`", name))?,
+                    None => { self.infer(value)?; }
+                }
+            }
+            StmtKind::If { condition, then_block, else_block } => {
+                self.expect(condition, &TypeRef::Bool, "`if` condition")?;
+                self.visit_block(then_block)?;
+                if let Some(b) = else_block { self.visit_block(b)?; }
+            }
+            StmtKind::While { condition, body } => {
+                self.expect(condition, &TypeRef::Bool, "`while` condition")?;
+                self.visit_block(body)?;
+            }
+            StmtKind::For { var, start, end, step, body } => {
+                self.expect(start, &TypeRef::Integer, "`for` range start")?;
+                self.expect(end, &TypeRef::Integer, "`for` range end")?;
+                if let Some(s) = step { self.expect(s, &TypeRef::Integer, "`for` step")?; }
+                self.visit_block_with(body, HashMap::from([(var.clone(), Some(TypeRef::Integer))]))?;
+            }
+            StmtKind::ForIn { binding, iterable, body } => {
+                let vars = match (binding, self.infer(iterable)?.as_ref().map(normalize)) {
+                    (ForBinding::Item(x), Some(TypeRef::Array(t))) => HashMap::from([(x.clone(), Some(*t))]),
+                    (ForBinding::Indexed(i, x), Some(TypeRef::Array(t))) => HashMap::from([(i.clone(), Some(TypeRef::Integer)), (x.clone(), Some(*t))]),
+                    (ForBinding::Entry(k, v), Some(TypeRef::Map(kt, vt))) => HashMap::from([(k.clone(), Some(*kt)), (v.clone(), Some(*vt))]),
+                    (ForBinding::Entry(..), Some(other)) => return Err(err(format!("Cannot iterate over a value of type {}", other), iterable.span)
+                        .with_note("`for (k, v) in m` iterates over a Map")),
+                    (_, Some(other)) => return Err(err(format!("Cannot iterate over a value of type {}", other), iterable.span)
+                        .with_note("`for x in xs` and `for i, x in xs` iterate over an Array")),
+                    (ForBinding::Entry(..), None) => return Err(err("Cannot iterate over a value whose type is unknown".to_string(), iterable.span)
+                        .with_note("annotate the value, e.g. `let m: Map<String, i64> = ...`")),
+                    (_, None) => return Err(err("Cannot iterate over a value whose type is unknown".to_string(), iterable.span)
+                        .with_note("annotate the value, e.g. `let xs: Array<i64> = ...`")),
+                };
+                self.visit_block_with(body, vars)?;
+            }
+            StmtKind::ScopeBlock { body, .. } => self.visit_block(body)?,
+            StmtKind::AwaitAll => {}
+            StmtKind::ValidateBlock { target, schema, on_fail, success_scope } => {
+                if let Some(b) = on_fail { self.visit_block(b)?; }
+                // success içinde hedef, doğrulanmış schema tipindedir
+                let validated = schema.clone().map(TypeRef::Custom).unwrap_or(TypeRef::String);
+                self.visit_block_with(success_scope, HashMap::from([(target.clone(), Some(validated))]))?;
+            }
+            StmtKind::ValidateLet { name, schema, on_fail, .. } => {
+                self.visit_block(on_fail)?;
+                self.define(name, Some(schema.clone().map(TypeRef::Custom).unwrap_or(TypeRef::String)));
+            }
+            StmtKind::ExprStmt(e) => {
+                // Hatalar sessizce yutulmaz: Result döndüren ifade match ile karşılanmalı
+                if let Some(TypeRef::Result(..)) = self.infer(e)? {
+                    return Err(err("Unused `Result`: the failure case must be handled".to_string(), e.span)
+                        .with_note("handle both cases with `match <expr> { Ok(v) => ..., Err(e) => ... }`"));
+                }
+            }
+            StmtKind::Return(_) if !self.closures.is_empty() => {
+                return Err(err("`return` cannot be used inside a closure".to_string(), stmt.span)
+                    .with_note("the value of a closure is its body expression"));
+            }
+            StmtKind::Return(Some(e)) => {
+                if self.return_type == TypeRef::Void {
+                    return Err(err("Cannot return a value from a Void function".to_string(), e.span));
+                }
+                let expected = self.return_type.clone();
+                self.expect(e, &expected, "Return value")?;
+            }
+            StmtKind::Return(None) => {
+                if self.return_type != TypeRef::Void {
+                    return Err(err(format!("Missing return value of type {}", self.return_type), stmt.span));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn infer(&mut self, expr: &Expr) -> Result<Option<TypeRef>, Diagnostic> {
+        let t = self.infer_kind(expr)?;
+        if let Some(t) = &t { self.table.exprs.insert(expr.id, t.clone()); }
+        Ok(t)
+    }
+
+    fn infer_kind(&mut self, expr: &Expr) -> Result<Option<TypeRef>, Diagnostic> {
+        let t = match &expr.kind {
+            ExprKind::Literal(Literal::Int(_)) => Some(TypeRef::Integer),
+            ExprKind::Literal(Literal::Float(_)) => Some(TypeRef::Float),
+            ExprKind::Literal(Literal::Str(_)) => Some(TypeRef::String),
+            ExprKind::Literal(Literal::Bool(_)) => Some(TypeRef::Bool),
+            // Değişken değilse bir fonksiyon değeridir
+            ExprKind::Identifier(name) if !self.is_var(name) && self.symbols.functions.contains_key(name) => {
+                self.table.functions.insert(expr.id);
+                Some(function_type(&self.symbols.functions[name]))
+            }
+            ExprKind::Identifier(name) => self.lookup(name),
+            ExprKind::Binary(l, op, r) => {
+                let mut lt = self.infer(l)?;
+                let mut rt = self.infer(r)?;
+                // `b + 1`, `b == 255`: literal karşı taraftaki u8'e uyar
+                if rt == Some(TypeRef::Byte) && self.byte_literal(l)? { lt = rt.clone(); }
+                if lt == Some(TypeRef::Byte) && self.byte_literal(r)? { rt = lt.clone(); }
+                match (lt, rt) {
+                    (Some(lt), Some(rt)) => Some(Self::binary_type(op, &lt, &rt).ok_or_else(|| {
+                        err(format!("Operator `{}` cannot be applied to {} and {}", op.symbol(), lt, rt), expr.span)
+                    })?),
+                    // Bir taraf bilinmiyorsa: karşılaştırmalar yine bool'dur
+                    _ => match op {
+                        op if op.is_comparison() => Some(TypeRef::Bool),
+                        BinaryOp::And | BinaryOp::Or => Some(TypeRef::Bool),
+                        _ => None,
+                    },
+                }
+            }
+            ExprKind::Unary(op, e) => {
+                match (op, self.infer(e)?.as_ref().map(normalize)) {
+                    (UnaryOp::Not, None | Some(TypeRef::Bool)) => Some(TypeRef::Bool),
+                    (UnaryOp::Neg, None) => Some(TypeRef::Integer),
+                    (UnaryOp::Neg, Some(t @ (TypeRef::Integer | TypeRef::Float))) => Some(t),
+                    (_, Some(t)) => return Err(err(format!("Operator `{}` cannot be applied to {}", op.symbol(), t), expr.span)),
+                }
+            }
+            ExprKind::Call(name, args) => self.check_call(name, args, expr.span)?,
+            ExprKind::Spawn(e) => self.infer(e)?.map(|t| TypeRef::Task(Box::new(t))),
+            ExprKind::Await(e) => match self.infer(e)? {
+                Some(TypeRef::Task(t)) => Some(*t),
+                Some(other) => return Err(err(format!("Cannot await a value of type {}", other), e.span)
+                    .with_note("only task handles returned by `spawn` can be awaited")),
+                None => None,
+            },
+            ExprKind::Infra(call) => {
+                let name = format!("{}.{}", call.service, call.method);
+                if call.config.timeout_ms.is_none() {
+                    return Err(err(format!("Infra call `{}` has no timeout", name), expr.span)
+                        .with_note("every infra call needs a deadline, e.g. `call S.m(..) { timeout: 200ms }`"));
+                }
+                let value = match builtins::lookup(&name) {
+                    Some(_) => self.check_call(&name, &call.args, expr.span)?,
+                    // Dış servis: argümanlar yine denetlenir, sonuç tipi bilinmez
+                    None => { for a in &call.args { self.infer(a)?; } None }
+                };
+                // Zaman aşımı servisin kendi hatasıyla birleşir: Result<T, String>
+                let value = value.map(|t| match t { TypeRef::Result(ok, _) => *ok, t => t });
+                // Fallback verilmişse hata durumunda onun değeri döner
+                match (&call.config.fallback, value) {
+                    (Some(f), Some(t)) => { self.expect(f, &t, &format!("`fallback` of `{}`", name))?; Some(t) }
+                    (Some(f), None) => self.infer(f)?,
+                    (None, t) => t.map(|t| TypeRef::Result(Box::new(t), Box::new(TypeRef::String))),
+                }
+            }
+            ExprKind::JsonField(source, _) => {
+                self.expect(source, &TypeRef::String, "`json(..)` source")?;
+                Some(TypeRef::Result(Box::new(TypeRef::String), Box::new(TypeRef::String)))
+            }
+            ExprKind::ArrayLiteral(elems) => {
+                let mut elem_type: Option<TypeRef> = None;
+                for e in elems {
+                    let Some(t) = self.infer(e)? else { continue };
+                    match &elem_type {
+                        Some(first) if !compatible(first, &t) => {
+                            return Err(err(format!("Array elements must have the same type: expected {}, found {}", first, t), e.span));
+                        }
+                        Some(_) => {}
+                        None => elem_type = Some(t),
+                    }
+                }
+                elem_type.map(|t| TypeRef::Array(Box::new(t)))
+            }
+            ExprKind::MapLiteral(entries) => {
+                let (mut key_type, mut value_type): (Option<TypeRef>, Option<TypeRef>) = (None, None);
+                for (k, v) in entries {
+                    for (e, seen, what) in [(k, &mut key_type, "keys"), (v, &mut value_type, "values")] {
+                        let Some(t) = self.infer(e)? else { continue };
+                        match seen {
+                            Some(first) if !compatible(first, &t) => {
+                                return Err(err(format!("Map {} must have the same type: expected {}, found {}", what, first, t), e.span));
+                            }
+                            Some(_) => {}
+                            None => *seen = Some(t),
+                        }
+                    }
+                }
+                if let Some(k) = &key_type {
+                    if !is_key(k) { return Err(key_error(k, entries[0].0.span)); }
+                }
+                key_type.zip(value_type).map(|(k, v)| TypeRef::Map(Box::new(k), Box::new(v)))
+            }
+            ExprKind::Index(target, idx) => {
+                let target_type = self.infer(target)?;
+                // Map'te anahtar olmayabilir: m["a"] bir Option'dır
+                if let Some(TypeRef::Map(k, v)) = target_type.as_ref().map(normalize) {
+                    self.expect(idx, &k, "Map key")?;
+                    return Ok(Some(TypeRef::Option(v)));
+                }
+                self.expect(idx, &TypeRef::Integer, "Array index")?;
+                match target_type {
+                    Some(TypeRef::Array(inner)) => Some(*inner),
+                    Some(other) => return Err(err(format!("Cannot index into a value of type {}", other), target.span)),
+                    // Derlenmiş kod sadece dizileri ve map'leri indeksleyebilir
+                    None => return Err(err("Cannot index into a value whose type is unknown".to_string(), target.span)
+                        .with_note("only arrays and maps can be indexed; annotate the value, e.g. `let xs: Array<i64> = ...`")),
+                }
+            }
+            // xs[1..3] aynı tipte yeni bir dizidir
+            ExprKind::Slice(target, from, to) => {
+                let target_type = self.infer(target)?;
+                self.expect(from, &TypeRef::Integer, "Slice start")?;
+                self.expect(to, &TypeRef::Integer, "Slice end")?;
+                match target_type {
+                    Some(t @ TypeRef::Array(_)) => Some(t),
+                    Some(other) => return Err(err(format!("Cannot slice a value of type {}", other), target.span)
+                        .with_note("only arrays can be sliced")),
+                    None => return Err(err("Cannot slice a value whose type is unknown".to_string(), target.span)
+                        .with_note("annotate the value, e.g. `let xs: Array<i64> = ...`")),
+                }
+            }
+            ExprKind::MethodCall(target, method, args) => {
+                let target_type = self.infer(target)?;
+                if let (ExprKind::Identifier(name), true) = (&target.kind, MUTATING_METHODS.contains(&method.as_str())) {
+                    self.captured_error(name, expr.span)?;
+                }
+                match (target_type.as_ref().map(normalize), method.as_str(), args.as_slice()) {
+                    // Sınır dışı ya da negatif indeks None döner
+                    (Some(TypeRef::Array(inner)), "get", [idx]) => {
+                        self.expect(idx, &TypeRef::Integer, "`get` index")?;
+                        Some(TypeRef::Option(inner))
+                    }
+                    (Some(TypeRef::Array(inner)), "contains", [item]) => {
+                        self.expect(item, &inner, "`contains` argument")?;
+                        Some(TypeRef::Bool)
+                    }
+                    (Some(TypeRef::Array(_) | TypeRef::Map(..)), method, _) if MUTATING_METHODS.contains(&method) && !matches!(target.kind, ExprKind::Identifier(_)) => {
+                        return Err(err(format!("`{}` modifies its target, which must be a variable", method), target.span)
+                            .with_note("assign the value to a variable first, e.g. `let xs = ...`"));
+                    }
+                    (Some(TypeRef::Array(inner)), "push", [item]) => {
+                        self.expect(item, &inner, "`push` argument")?;
+                        Some(TypeRef::Void)
+                    }
+                    // Boş dizide None döner
+                    (Some(TypeRef::Array(inner)), "pop", []) => Some(TypeRef::Option(inner)),
+                    (Some(TypeRef::Array(inner)), "sort", []) if !is_key(&inner) => {
+                        return Err(err(format!("Cannot sort a value of type Array<{}>", inner), target.span)
+                            .with_note("only arrays of i64, u8, bool or String can be sorted"));
+                    }
+                    (Some(TypeRef::Array(_)), "sort" | "reverse", []) => Some(TypeRef::Void),
+                    // xs.map(|x| x * 2): parametre tipi eleman tipinden gelir
+                    (Some(TypeRef::Array(inner)), "map", [f]) => {
+                        self.function_arg(f, &[*inner], None, "`map`")?.map(|t| TypeRef::Array(Box::new(t)))
+                    }
+                    (Some(TypeRef::Array(inner)), "filter", [f]) => {
+                        self.function_arg(f, &[(*inner).clone()], Some(&TypeRef::Bool), "`filter`")?;
+                        Some(TypeRef::Array(inner))
+                    }
+                    // xs.fold(0, |acc, x| acc + x): biriktiricinin tipi başlangıç değerinden gelir
+                    (Some(TypeRef::Array(inner)), "fold", [init, f]) => {
+                        let Some(acc) = self.infer(init)? else {
+                            return Err(err("Cannot infer the type of the `fold` initial value".to_string(), init.span)
+                                .with_note("assign it to an annotated variable first, e.g. `let zero: Option<i64> = None`"));
+                        };
+                        self.function_arg(f, &[acc.clone(), *inner], Some(&acc), "`fold`")?;
+                        Some(acc)
+                    }
+                    (Some(TypeRef::Array(_)), "get" | "contains" | "push" | "pop" | "sort" | "reverse" | "map" | "filter" | "fold", _) => {
+                        let expected = match method.as_str() {
+                            "get" | "contains" | "push" | "map" | "filter" => 1,
+                            "fold" => 2,
+                            _ => 0,
+                        };
+                        return Err(err(format!("`{}` expects {} argument(s), found {}", method, expected, args.len()), expr.span));
+                    }
+                    (Some(TypeRef::Map(k, v)), "get" | "remove", [key]) => {
+                        self.expect(key, &k, &format!("`{}` key", method))?;
+                        Some(TypeRef::Option(v))
+                    }
+                    // Eski değer varsa döner
+                    (Some(TypeRef::Map(k, v)), "insert", [key, value]) => {
+                        self.expect(key, &k, "`insert` key")?;
+                        self.expect(value, &v, "`insert` value")?;
+                        Some(TypeRef::Option(v))
+                    }
+                    (Some(TypeRef::Map(..)), "get" | "remove" | "insert", _) => {
+                        let expected = if method == "insert" { 2 } else { 1 };
+                        return Err(err(format!("`{}` expects {} argument(s), found {}", method, expected, args.len()), expr.span));
+                    }
+                    (Some(t), _, _) => return Err(err(format!("No method `{}` on type {}", method, t), expr.span)),
+                    (None, _, _) => return Err(err(format!("Cannot call `.{}(..)` on a value whose type is unknown", method), target.span)),
+                }
+            }
+            ExprKind::Interpolated(parts) => {
+                for e in parts.iter().filter_map(StrPart::expr) {
+                    if let Some(t) = self.infer(e)?.filter(|t| !is_printable(t)) {
+                        return Err(err(format!("Cannot interpolate a value of type {}", normalize(&t)), e.span)
+                            .with_note("only numbers, bool, String and struct values can appear inside `${..}`"));
+                    }
+                }
+                Some(TypeRef::String)
+            }
+            ExprKind::Field(target, field) => {
+                match self.infer(target)? {
+                    Some(TypeRef::Custom(name)) => match self.symbols.record_fields(&name) {
+                        Some(fields) => match fields.iter().find(|(n, _)| n == field) {
+                            Some((_, t)) => Some((*t).clone()),
+                            None => return Err(err(format!("Type `{}` has no field `{}`", name, field), expr.span)),
+                        },
+                        None => None,
+                    },
+                    Some(other) => return Err(err(format!("Field access `.{}` on non-struct value of type {}", field, other), expr.span)),
+                    None => None,
+                }
+            }
+            ExprKind::StructLiteral(name, fields) => {
+                let Some(declared) = self.symbols.record_fields(name) else {
+                    return Err(err(format!("Unknown struct: {}", name), expr.span));
+                };
+                let declared: Vec<(String, TypeRef)> = declared.into_iter().map(|(n, t)| (n.to_string(), t.clone())).collect();
+                let mut seen = HashSet::new();
+                for (field, value) in fields {
+                    let Some((_, expected)) = declared.iter().find(|(n, _)| n == field) else {
+                        return Err(err(format!("Struct `{}` has no field `{}`", name, field), value.span));
+                    };
+                    if !seen.insert(field) {
+                        return Err(err(format!("Field `{}` is given more than once", field), value.span));
+                    }
+                    self.expect(value, expected, &format!("Field `{}.{}`", name, field))?;
+                }
+                let missing: Vec<&str> = declared.iter().map(|(n, _)| n.as_str()).filter(|n| !fields.iter().any(|(f, _)| f == n)).collect();
+                if !missing.is_empty() {
+                    return Err(err(format!("Missing fields in `{}` literal: {}", name, missing.join(", ")), expr.span));
+                }
+                Some(TypeRef::Custom(name.clone()))
+            }
+            ExprKind::Ctor(c, payload) => match (c, payload) {
+                (Ctor::Some, Some(p)) => self.infer(p)?.map(|t| TypeRef::Option(Box::new(t))),
+                // Ok/Err/None: eksik tip parçası bağlamdan gelmeli (bkz. expect)
+                (_, Some(p)) => { self.infer(p)?; None }
+                (_, None) => None,
+            },
+            ExprKind::Match(scrutinee, arms) => self.check_match(scrutinee, arms, None)?,
+            ExprKind::Lambda(..) => self.check_lambda(expr, None, None, "Closure")?,
+        };
+        Ok(t)
+    }
+
+    fn check_call(&mut self, name: &str, args: &[Expr], span: Span) -> Result<Option<TypeRef>, Diagnostic> {
+        let variable = self.is_var(name).then(|| self.lookup(name).map(|t| normalize(&t)));
+        let named = self.symbols.functions.contains_key(name) || builtins::lookup(name).is_some();
+        if let (Some(Some(TypeRef::Func(..))), true) = (&variable, named) {
+            return Err(err(format!("Call to `{}` is ambiguous: it is both a function and a variable", name), span)
+                .with_note("rename the variable"));
+        }
+        let (params, returns): (Vec<Option<TypeRef>>, TypeRef) = if let Some(f) = self.symbols.functions.get(name) {
+            (f.params.iter().map(|p| Some(p.param_type.clone())).collect(), f.return_type.clone())
+        } else if let Some(b) = builtins::lookup(name) {
+            (b.params.to_vec(), b.return_type())
+        } else if let Some(t) = variable {
+            // Fonksiyon değeri çağrısı: `f(x)`
+            match t {
+                Some(TypeRef::Func(params, ret)) => (params.into_iter().map(Some).collect(), *ret),
+                Some(other) => return Err(err(format!("`{}` is a value of type {}, not a function", name, other), span)),
+                None => return Err(err(format!("Cannot call `{}`: its type is unknown", name), span)
+                    .with_note(format!("annotate it, e.g. `let {}: fn(i64) -> i64 = ...`", name))),
+            }
+        } else {
+            return Err(err(format!("Unknown function: {}", name), span));
+        };
+
+        if params.len() != args.len() {
+            return Err(err(format!("`{}` takes {} argument(s), {} given", name, params.len(), args.len()), span));
+        }
+        for (i, (arg, param)) in args.iter().zip(&params).enumerate() {
+            match param {
+                Some(expected) => self.expect(arg, expected, &format!("Argument {} of `{}`", i + 1, name))?,
+                // `len`in argümanı aşağıda denetlenir
+                None if name == "len" => { self.infer(arg)?; }
+                // Diğer tipsiz builtin parametreleri (DB.log) değeri yazdırır
+                None => {
+                    if let Some(t) = self.infer(arg)?.filter(|t| !is_printable(t)) {
+                        return Err(err(format!("Cannot pass a value of type {} to `{}`", t, name), arg.span)
+                            .with_note("only numbers, bool, String and struct values can be printed; use `${..}` or `for` to print the parts"));
+                    }
+                }
+            }
+        }
+        // len(..) her tipi değil, sadece dizileri ve map'leri kabul eder
+        if let ("len", [arg], None) = (name, args, self.symbols.functions.get(name)) {
+            match self.table.get(arg).map(normalize) {
+                Some(TypeRef::Array(_) | TypeRef::Map(..)) => {}
+                Some(other) => return Err(err(format!("Cannot take the length of a value of type {}", other), arg.span)
+                    .with_note("`len` works on arrays and maps")),
+                None => return Err(err("Cannot take the length of a value whose type is unknown".to_string(), arg.span)
+                    .with_note("annotate the value, e.g. `let xs: Array<i64> = ...`")),
+            }
+        }
+        Ok(Some(returns))
+    }
+
+    // GojoAdd / GojoMul ve native operatörlerin desteklediği kombinasyonlar
+    fn binary_type(op: &BinaryOp, l: &TypeRef, r: &TypeRef) -> Option<TypeRef> {
+        let (l, r) = (normalize(l), normalize(r));
+        match op {
+            BinaryOp::Add => match (&l, &r) {
+                (l, r) if l == r && is_numeric(l) => Some(l.clone()),
+                (TypeRef::String, TypeRef::Integer | TypeRef::String | TypeRef::Custom(_)) => Some(TypeRef::String),
+                _ => None,
+            },
+            BinaryOp::Mul => match (&l, &r) {
+                (l, r) if l == r && is_numeric(l) => Some(l.clone()),
+                (TypeRef::String, TypeRef::Integer) => Some(TypeRef::String),
+                _ => None,
+            },
+            BinaryOp::Sub | BinaryOp::Div | BinaryOp::Mod => (l == r && is_numeric(&l)).then_some(l),
+            BinaryOp::Eq | BinaryOp::Neq => (l == r && !has_func(&l)).then_some(TypeRef::Bool),
+            BinaryOp::Gt | BinaryOp::Lt | BinaryOp::Gte | BinaryOp::Lte => {
+                (l == r && (is_numeric(&l) || is_stringy(&l))).then_some(TypeRef::Bool)
+            }
+            BinaryOp::And | BinaryOp::Or => (l == TypeRef::Bool && r == TypeRef::Bool).then_some(TypeRef::Bool),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser;
+
+    // Struct tanımları ve tüm fonksiyonlar; ilk hata döner
+    fn check(source: &str) -> Result<TypeTable, Diagnostic> {
+        let (program, errors) = parser::parse_program(source);
+        assert!(errors.is_empty(), "{:?}", errors);
+        let symbols = SymbolTable::from_program(&program);
+        program.structs().try_for_each(|def| TypeChecker::check_struct(def, &symbols))?;
+        let mut table = TypeTable::default();
+        for func in program.functions() { TypeChecker::check(func, &symbols, &mut table)?; }
+        Ok(table)
+    }
+
+    fn message(source: &str) -> String {
+        check(source).expect_err("tip hatası bekleniyordu").message
+    }
+
+    const USER: &str = "struct User { id: i64, name: String }\n";
+
+    #[test]
+    fn checks_struct_literals_and_field_access() {
+        assert!(check(&format!("{}deterministic fn f() -> String {{\n    let u = User {{ name: \"gojo\", id: 1 }}\n    return u.name + \"!\"\n}}\n", USER)).is_ok());
+        let cases = [
+            ("let u = User { id: 1 }", "Missing fields in `User` literal: name"),
+            ("let u = User { id: 1, name: \"a\", age: 3 }", "Struct `User` has no field `age`"),
+            ("let u = User { id: 1, id: 2, name: \"a\" }", "Field `id` is given more than once"),
+            ("let u = User { id: \"1\", name: \"a\" }", "Field `User.id` expects i64, found String"),
+            ("let u = Admin { id: 1 }", "Unknown struct: Admin"),
+            ("let u = User { id: 1, name: \"a\" }\n    let a = u.age", "Type `User` has no field `age`"),
+            ("let n = 5\n    let a = n.id", "Field access `.id` on non-struct value of type i64"),
+        ];
+        for (body, expected) in cases {
+            assert_eq!(message(&format!("{}deterministic fn f() {{\n    {}\n}}\n", USER, body)), expected, "{}", body);
+        }
+    }
+
+    #[test]
+    fn rejects_bad_struct_declarations() {
+        assert_eq!(message("struct P { a: i64, a: String }\n"), "Duplicate field `a` in struct `P`");
+        assert_eq!(message("struct P { a: Missing }\n"), "Unknown type: Missing");
+        assert_eq!(message("struct P { f: fn(i64) -> i64 }\n"), "Field `f` cannot hold a function");
+        assert!(check("struct P { a: i64 }\nstruct Q { p: P, ps: Array<P> }\n").is_ok());
+    }
+
+    #[test]
+    fn reports_type_errors_with_gojo_types() {
+        let cases = [
+            ("let a = 1 + \"x\"", "Operator `+` cannot be applied to i64 and String"),
+            ("let a = sq(\"x\")", "Argument 1 of `sq` expects i64, found String"),
+            ("let a = sq(1, 2)", "`sq` takes 1 argument(s), 2 given"),
+            ("let a = cube(2)", "Unknown function: cube"),
+            ("let a = 1.5 * 2", "Operator `*` cannot be applied to f64 and i64"),
+            ("let a = !3", "Operator `!` cannot be applied to i64"),
+            ("HTTP.get(\"http://x\")", "Unused `Result`: the failure case must be handled"),
+        ];
+        for (body, expected) in cases {
+            let source = format!("deterministic fn sq(n: i64) -> i64 {{ return n * n }}\nnondeterministic fn main() {{\n    {}\n}}\n", body);
+            assert_eq!(message(&source), expected, "{}", body);
+        }
+        let table = check("deterministic fn sq(n: i64) -> i64 { return n * n }\ndeterministic fn f(x: f64) -> f64 { return x * 2.0 + 1.5 }\n").unwrap();
+        assert_eq!(table.with_type(&TypeRef::Float).count(), 5);
+    }
+
+    #[test]
+    fn every_path_of_a_valued_function_returns() {
+        let ok = [
+            "if n > 0 { return 1 } else { return 2 }",
+            "if n > 0 { return 1 }\n    return 2",
+            "let r: Result<i64, String> = Ok(n)\n    match r {\n        Ok(v) => { return v },\n        Err(e) => { return 0 },\n    }",
+        ];
+        for body in ok {
+            assert!(check(&format!("deterministic fn f(n: i64) -> i64 {{\n    {}\n}}\n", body)).is_ok(), "{}", body);
+        }
+        let missing = [
+            "if n > 0 { return 1 }",
+            "while n > 0 { return 1 }",
+            "let r: Result<i64, String> = Ok(n)\n    match r {\n        Ok(v) => { return v },\n        Err(e) => { DB.log(e) },\n    }",
+        ];
+        for body in missing {
+            let err = check(&format!("nondeterministic fn f(n: i64) -> i64 {{\n    {}\n}}\n", body)).unwrap_err();
+            assert_eq!(err.message, "Function `f` may end without returning a value of type i64", "{}", body);
+        }
+        assert_eq!(message("deterministic fn f() -> i64 { return }\n"), "Missing return value of type i64");
+        assert_eq!(message("deterministic fn f() { return 1 }\n"), "Cannot return a value from a Void function");
+    }
+
+    #[test]
+    fn only_printable_values_can_be_logged() {
+        let ok = ["DB.log(1)", "DB.log(2.5)", "DB.log(\"a\")", "DB.log(true)", "DB.log(User { id: 1, name: \"a\" })", "DB.log(\"${len([1])}\")"];
+        for body in ok {
+            assert!(check(&format!("{}nondeterministic fn main() {{\n    {}\n}}\n", USER, body)).is_ok(), "{}", body);
+        }
+        // Üretilen Rust'ta Vec, BTreeMap ve Option'ın Display'i yoktur
+        let cases = [
+            ("let xs = [1, 2]\n    DB.log(xs[0..1])", "Cannot pass a value of type Array<i64> to `DB.log`"),
+            ("DB.log({ \"a\": 1 })", "Cannot pass a value of type Map<String, i64> to `DB.log`"),
+            ("DB.log([1].get(0))", "Cannot pass a value of type Option<i64> to `DB.log`"),
+            ("DB.log(|x: i64| x)", "Cannot pass a value of type fn(i64) -> i64 to `DB.log`"),
+            ("DB.log(\"${[1]}\")", "Cannot interpolate a value of type Array<i64>"),
+        ];
+        for (body, expected) in cases {
+            assert_eq!(message(&format!("nondeterministic fn main() {{\n    {}\n}}\n", body)), expected, "{}", body);
+        }
+    }
+
+    #[test]
+    fn infra_calls_need_a_timeout() {
+        let err = check("nondeterministic fn main() {\n    let t = call Util.now() { retry: 2 }\n}\n").unwrap_err();
+        assert_eq!(err.message, "Infra call `Util.now` has no timeout");
+        assert!(check("nondeterministic fn main() {\n    let t = call Util.now() { timeout: 1s, retry: 2, backoff: fixed(10ms) }\n}\n").is_ok());
+    }
+
+    #[test]
+    fn constructors_take_their_type_from_the_context() {
+        assert!(check("deterministic fn f() -> Option<i64> {\n    let r: Result<i64, String> = Err(\"x\")\n    let o: Option<i64> = None\n    return Some(1)\n}\n").is_ok());
+        assert_eq!(message("deterministic fn f() {\n    let o: Option<i64> = Ok(1)\n}\n"), "`o` expects Option<i64>, found `Ok`");
+        assert_eq!(message("deterministic fn f(o: Option<i64>) -> i64 {\n    return match o { Ok(v) => v, Err(e) => 0 }\n}\n"), "Pattern `Ok` does not fit a value of type Option<i64>");
+    }
+
+
+    #[test]
+    fn untrusted_is_text_inside_maps_and_tasks() {
+        let source = "deterministic fn size(m: Map<String, String>) -> i64 { return 1 }\ndeterministic fn f(m: Map<String, Untrusted>) -> i64 { return size(m) }\n";
+        assert!(check(source).is_ok(), "{:?}", check(source).err());
+        let task = |t: TypeRef| TypeRef::Task(Box::new(TypeRef::Array(Box::new(t))));
+        assert!(compatible(&task(TypeRef::String), &task(TypeRef::Untrusted)));
+        assert!(!compatible(&task(TypeRef::String), &task(TypeRef::Integer)));
+    }
+}
@@ -1,36 +1,158 @@
+// Bazı alanlar (span, schema...) her pass tarafından okunmaz; araçlar (LSP, formatter) için taşınır.
+#![allow(dead_code)]
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// Kaynak dosyadaki byte aralığı (start dahil, end hariç)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self { Self { start, end } }
+    // İki span'i kapsayan en küçük span
+    pub fn to(self, other: Span) -> Span { Span::new(self.start.min(other.start), self.end.max(other.end)) }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum TypeRef {
-    Void, 
-    Integer, 
-    String, 
-    Untrusted, 
+    Void,
+    Integer,
+    // f64 ve u8 (byte); u8 aritmetiği i64 gibi denetimlidir
+    Float,
+    Byte,
+    String,
+    Bool,
+    Untrusted,
     // YENİ: Dizi Tipi
     Array(Box<TypeRef>),
+    // Açık hata yönetimi: Result<T, E> ve Option<T> (match ile karşılanır)
+    Result(Box<TypeRef>, Box<TypeRef>),
+    Option(Box<TypeRef>),
+    // Map<String, i64>: anahtar sıralı (BTreeMap), gezinme deterministiktir
+    Map(Box<TypeRef>, Box<TypeRef>),
+    // spawn'ın döndürdüğü handle (imzalarda yazılamaz, scope dışına çıkamaz)
+    Task(Box<TypeRef>),
+    // fn(i64) -> i64: closure ya da isimli fonksiyon (saflığı determinism pass'inde çıkarılır)
+    Func(Vec<TypeRef>, Box<TypeRef>),
     Custom(String),
 }
 
+impl std::fmt::Display for TypeRef {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            TypeRef::Void => write!(f, "Void"),
+            TypeRef::Integer => write!(f, "i64"),
+            TypeRef::Float => write!(f, "f64"),
+            TypeRef::Byte => write!(f, "u8"),
+            TypeRef::String => write!(f, "String"),
+            TypeRef::Bool => write!(f, "bool"),
+            TypeRef::Untrusted => write!(f, "Untrusted"),
+            TypeRef::Array(inner) => write!(f, "Array<{}>", inner),
+            TypeRef::Result(ok, err) => write!(f, "Result<{}, {}>", ok, err),
+            TypeRef::Option(inner) => write!(f, "Option<{}>", inner),
+            TypeRef::Map(k, v) => write!(f, "Map<{}, {}>", k, v),
+            TypeRef::Task(inner) => write!(f, "Task<{}>", inner),
+            TypeRef::Func(params, ret) => {
+                let params: Vec<String> = params.iter().map(|p| p.to_string()).collect();
+                write!(f, "fn({}) -> {}", params.join(", "), ret)
+            }
+            TypeRef::Custom(name) => write!(f, "{}", name),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Param {
     pub name: String,
     pub param_type: TypeRef,
+    pub span: Span,
 }
 
 #[derive(Debug, Clone)]
 pub enum BinaryOp {
-    Add, Sub, Mul, Div, 
+    Add, Sub, Mul, Div,
     Eq, Neq, Gt, Lt,
     // YENİ: Büyük Eşit / Küçük Eşit
     Gte, Lte,
+    Mod,
+    // Kısa devre: sağ taraf gerektiğinde değerlendirilir
+    And, Or,
+}
+
+impl BinaryOp {
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            BinaryOp::Add => "+", BinaryOp::Sub => "-", BinaryOp::Mul => "*", BinaryOp::Div => "/",
+            BinaryOp::Eq => "==", BinaryOp::Neq => "!=",
+            BinaryOp::Gt => ">", BinaryOp::Lt => "<",
+            BinaryOp::Gte => ">=", BinaryOp::Lte => "<=",
+            BinaryOp::Mod => "%", BinaryOp::And => "&&", BinaryOp::Or => "||",
+        }
+    }
+
+    // Bağlama gücü: || < && < karşılaştırma < + - < * / %
+    pub fn precedence(&self) -> u8 {
+        match self {
+            BinaryOp::Or => 1,
+            BinaryOp::And => 2,
+            BinaryOp::Eq | BinaryOp::Neq | BinaryOp::Gt | BinaryOp::Lt | BinaryOp::Gte | BinaryOp::Lte => 3,
+            BinaryOp::Add | BinaryOp::Sub => 4,
+            BinaryOp::Mul | BinaryOp::Div | BinaryOp::Mod => 5,
+        }
+    }
+
+    // Karşılaştırmalar zincirlenemez: `a < b < c` reddedilir
+    pub fn is_comparison(&self) -> bool { self.precedence() == 3 }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnaryOp {
+    Not, Neg,
+}
+
+impl UnaryOp {
+    pub fn symbol(&self) -> &'static str {
+        match self { UnaryOp::Not => "!", UnaryOp::Neg => "-" }
+    }
 }
 
 #[derive(Debug, Clone)]
 pub enum Literal {
-    Int(i64), Str(String), Bool(bool),
+    Int(i64), Float(f64), Str(String), Bool(bool),
+}
+
+// Denemeler arasındaki bekleme
+#[derive(Debug, Clone, PartialEq)]
+pub enum Backoff {
+    // Her denemede aynı süre (ms)
+    Fixed(u64),
+    // Başlangıç süresi (ms), her denemede iki katına çıkar
+    Exponential(u64),
 }
 
+// call Service.method(...) { timeout: 2s, retry: 3, backoff: exponential, fallback: "..." }
 #[derive(Debug, Clone)]
 pub struct InfraConfig {
-    pub timeout_ms: u64,
+    // Zorunlu: yazılmazsa tip denetimi derlemeyi reddeder
+    pub timeout_ms: Option<u64>,
+    // İlk denemeden sonraki ek deneme sayısı
+    pub retry: u32,
+    pub backoff: Backoff,
+    // Tüm denemeler zaman aşımına uğrarsa dönen değer
+    pub fallback: Option<Box<Expr>>,
+}
+
+impl InfraConfig {
+    pub const DEFAULT_BACKOFF_MS: u64 = 100;
+}
+
+impl Default for InfraConfig {
+    fn default() -> Self {
+        Self { timeout_ms: None, retry: 0, backoff: Backoff::Fixed(Self::DEFAULT_BACKOFF_MS), fallback: None }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -41,58 +163,332 @@ pub struct InfraCall {
     pub config: InfraConfig,
 }
 
+// Her ifadenin benzersiz kimliği: tip tablosu gibi yan tablolar bununla indekslenir
+pub type ExprId = usize;
+
+static NEXT_EXPR_ID: AtomicUsize = AtomicUsize::new(0);
+
 #[derive(Debug, Clone)]
-pub enum Expr {
+pub struct Expr {
+    pub id: ExprId,
+    pub kind: ExprKind,
+    pub span: Span,
+}
+
+impl Expr {
+    pub fn new(kind: ExprKind, span: Span) -> Self { Self { id: NEXT_EXPR_ID.fetch_add(1, Ordering::Relaxed), kind, span } }
+}
+
+#[derive(Debug, Clone)]
+pub enum ExprKind {
     Identifier(String),
     Literal(Literal),
     Binary(Box<Expr>, BinaryOp, Box<Expr>),
+    // !ok, -x
+    Unary(UnaryOp, Box<Expr>),
     Call(String, Vec<Expr>),
     Spawn(Box<Expr>),
     Await(Box<Expr>),
-    Infra(InfraCall),
+    Infra(Box<InfraCall>),
     JsonField(Box<Expr>, String),
-    
+
     // YENİ: Liste Oluşturma [1, 2, 3]
     ArrayLiteral(Vec<Expr>),
-    // YENİ: Listeden Okuma x[0]
+    // { "a": 1, "b": 2 }
+    MapLiteral(Vec<(Expr, Expr)>),
+    // YENİ: Listeden Okuma x[0] (map'te m["a"] bir Option'dır)
     Index(Box<Expr>, Box<Expr>),
+    // xs[1..3]: yeni bir dizi (sınır dışı aralık hatadır)
+    Slice(Box<Expr>, Box<Expr>, Box<Expr>),
+    // arr.get(0), m.insert("a", 1)
+    MethodCall(Box<Expr>, String, Vec<Expr>),
+    // "fib(${n}) = ${sonuc}"
+    Interpolated(Vec<StrPart>),
+    // u.name
+    Field(Box<Expr>, String),
+    // User { id: 1, name: "gojo" }
+    StructLiteral(String, Vec<(String, Expr)>),
+    // Ok(x), Err(e), Some(x), None
+    Ctor(Ctor, Option<Box<Expr>>),
+    // match r { Ok(v) => ..., Err(e) => ... }
+    Match(Box<Expr>, Vec<MatchArm>),
+    // |x| x * 2, |acc, x: i64| acc + x (tipi yazılmayan parametre bağlamdan çıkarılır)
+    Lambda(Vec<(String, Option<TypeRef>)>, Box<Expr>),
+}
+
+// Hedef değişkeni yerinde değiştiren metotlar: `m.insert(k, v)`, `xs.push(x)` bir değişken üzerinde çağrılmalı
+pub const MUTATING_METHODS: &[&str] = &["insert", "remove", "push", "pop", "sort", "reverse"];
+// Son argümanı bir fonksiyon olan dizi metotları: `xs.map(f)`, `xs.filter(f)`, `xs.fold(init, f)`
+pub const HIGHER_ORDER_METHODS: &[&str] = &["map", "filter", "fold"];
+
+// Enterpolasyonlu metnin parçaları (kaçış dizileri çözülmüş halde)
+#[derive(Debug, Clone)]
+pub enum StrPart {
+    Text(String),
+    Expr(Expr),
+}
+
+impl StrPart {
+    pub fn expr(&self) -> Option<&Expr> {
+        match self { StrPart::Expr(e) => Some(e), StrPart::Text(_) => None }
+    }
+}
+
+// Result / Option kurucuları: hem ifadede (Ok(x)) hem desende (Ok(v) =>) kullanılır
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ctor {
+    Ok, Err, Some, None,
+}
+
+impl Ctor {
+    pub fn name(&self) -> &'static str {
+        match self { Ctor::Ok => "Ok", Ctor::Err => "Err", Ctor::Some => "Some", Ctor::None => "None" }
+    }
+
+    // None dışındakiler bir değer taşır
+    pub fn has_payload(&self) -> bool { *self != Ctor::None }
+}
+
+#[derive(Debug, Clone)]
+pub enum Pattern {
+    // Ok(v), Err(_), Some(x), None  (bağlanan isim `_` ise None)
+    Ctor(Ctor, Option<String>),
+    Wildcard,
+}
+
+#[derive(Debug, Clone)]
+pub enum ArmBody {
+    Expr(Expr),
+    Block(Block),
+}
+
+// Ok(v) => v * 2
+#[derive(Debug, Clone)]
+pub struct MatchArm {
+    pub pattern: Pattern,
+    pub body: ArmBody,
+    pub span: Span,
 }
 
 #[derive(Debug, Clone)]
 pub struct LetStmt {
     pub name: String,
+    // let r: Result<i64, String> = Ok(1)
+    pub ty: Option<TypeRef>,
     pub value: Expr,
 }
 
 #[derive(Debug, Clone)]
-pub enum Statement {
+pub struct Statement {
+    pub kind: StmtKind,
+    pub span: Span,
+}
+
+impl Statement {
+    pub fn new(kind: StmtKind, span: Span) -> Self { Self { kind, span } }
+}
+
+#[derive(Debug, Clone)]
+pub enum StmtKind {
     Let(LetStmt),
     Assign { name: String, value: Expr },
     If { condition: Expr, then_block: Block, else_block: Option<Block> },
     While { condition: Expr, body: Block },
     // FOR LOOP (Step ile birlikte)
     For { var: String, start: Expr, end: Expr, step: Option<Expr>, body: Block },
+    // for x in xs / for i, x in xs / for (k, v) in m { ... }  (map'te anahtar sırasıyla)
+    ForIn { binding: ForBinding, iterable: Expr, body: Block },
+    // Scope kendi task'larının sahibidir: normal çıkışta hepsini bekler, erken çıkışta iptal eder
     ScopeBlock { name: String, body: Block },
-    ValidateBlock { target: String, schema: String, on_fail: Box<Block>, success_scope: Box<Block> },
-    ExprStmt(Expr), 
+    // await all
+    AwaitAll,
+    // validate x { schema: S, on_fail: ..., success: { ... } }  (schema ve on_fail opsiyonel)
+    ValidateBlock { target: String, schema: Option<String>, on_fail: Option<Box<Block>>, success_scope: Box<Block> },
+    // let req = validate raw { schema: S, on_fail: return ... }  (on_fail fonksiyondan çıkmalı)
+    ValidateLet { name: String, target: String, schema: Option<String>, on_fail: Box<Block> },
+    ExprStmt(Expr),
     Return(Option<Expr>),
 }
 
+// for-in döngüsünün bağladığı isimler
+#[derive(Debug, Clone)]
+pub enum ForBinding {
+    // x: dizi elemanları
+    Item(String),
+    // i, x: indeks (i64) ve eleman
+    Indexed(String, String),
+    // (k, v): map girdileri
+    Entry(String, String),
+}
+
+impl ForBinding {
+    pub fn names(&self) -> Vec<&str> {
+        match self {
+            ForBinding::Item(x) => vec![x],
+            ForBinding::Indexed(a, b) | ForBinding::Entry(a, b) => vec![a, b],
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Block {
     pub statements: Vec<Statement>,
+    pub span: Span,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Purity {
     Deterministic, Nondeterministic,
 }
 
+// i64 taşması: varsayılan olarak konumlu çalışma zamanı hatası, `wrapping fn` içinde sarar
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Overflow {
+    #[default]
+    Checked,
+    Wrapping,
+}
+
 #[derive(Debug, Clone)]
 pub struct FunctionDef {
     pub name: String,
     pub purity: Purity,
+    pub overflow: Overflow,
     pub params: Vec<Param>,
     pub return_type: TypeRef,
     pub body: Block,
-}
\ No newline at end of file
+    // Tüm tanım ve sadece isim (hata mesajlarında isim işaretlenir)
+    pub span: Span,
+    pub name_span: Span,
+}
+
+// Schema kısıtları: validate sırasında runtime'da kontrol edilir
+#[derive(Debug, Clone)]
+pub enum Constraint {
+    // String uzunluğu [min, max]
+    Len(i64, i64),
+    // Sayı aralığı [min, max]
+    Range(i64, i64),
+    Regex(String),
+}
+
+// Kaynaktaki yazımıyla: `range(1, 100)`
+impl std::fmt::Display for Constraint {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Constraint::Len(min, max) => write!(f, "len({}, {})", min, max),
+            Constraint::Range(min, max) => write!(f, "range({}, {})", min, max),
+            Constraint::Regex(re) => write!(f, "regex(\"{}\")", re),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SchemaField {
+    pub name: String,
+    pub field_type: TypeRef,
+    pub constraints: Vec<Constraint>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct SchemaDef {
+    pub name: String,
+    pub fields: Vec<SchemaField>,
+    pub span: Span,
+    pub name_span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct StructField {
+    pub name: String,
+    pub field_type: TypeRef,
+    pub span: Span,
+}
+
+// struct User { id: i64, name: String }
+#[derive(Debug, Clone)]
+pub struct StructDef {
+    pub name: String,
+    pub fields: Vec<StructField>,
+    pub span: Span,
+    pub name_span: Span,
+}
+
+// Dosya seviyesindeki tanımlar
+#[derive(Debug, Clone)]
+pub enum Item {
+    Function(FunctionDef),
+    Schema(SchemaDef),
+    Struct(StructDef),
+}
+
+impl Item {
+    pub fn name(&self) -> &str {
+        match self { Item::Function(f) => &f.name, Item::Schema(s) => &s.name, Item::Struct(s) => &s.name }
+    }
+
+    pub fn span(&self) -> Span {
+        match self { Item::Function(f) => f.span, Item::Schema(s) => s.span, Item::Struct(s) => s.span }
+    }
+}
+
+// `// ...` satır yorumu: AST'ye girmez, formatter kaynaktaki yerine göre geri yerleştirir
+#[derive(Debug, Clone)]
+pub struct Comment {
+    pub text: String,
+    pub span: Span,
+}
+
+// REPL girdisi: dosya seviyesinde bir tanım ya da tek bir deyim
+#[derive(Debug, Clone)]
+pub enum Snippet {
+    Item(Item),
+    Stmt(Statement),
+}
+
+// import "lib/payments.gj" ya da use payments.fees
+// Modülün fonksiyonları son parçanın adıyla çağrılır: payments.calculate_fees(x)
+#[derive(Debug, Clone)]
+pub struct Import {
+    pub target: ImportTarget,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ImportTarget {
+    // İçe aktaran dosyanın dizinine göre yol
+    File(String),
+    // Giriş dosyasının dizinine göre modül yolu: payments.fees -> payments/fees.gj
+    Module(Vec<String>),
+}
+
+impl Import {
+    pub fn namespace(&self) -> &str {
+        match &self.target {
+            ImportTarget::File(path) => std::path::Path::new(path).file_stem().and_then(|s| s.to_str()).unwrap_or(path),
+            ImportTarget::Module(path) => path.last().map_or("", String::as_str),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Program {
+    pub items: Vec<Item>,
+    // Dosyanın başındaki import/use satırları (modules::load çözer)
+    pub imports: Vec<Import>,
+}
+
+impl Program {
+    pub fn functions(&self) -> impl Iterator<Item = &FunctionDef> {
+        self.items.iter().filter_map(|i| match i { Item::Function(f) => Some(f), _ => None })
+    }
+
+    pub fn schemas(&self) -> impl Iterator<Item = &SchemaDef> {
+        self.items.iter().filter_map(|i| match i { Item::Schema(s) => Some(s), _ => None })
+    }
+
+    pub fn structs(&self) -> impl Iterator<Item = &StructDef> {
+        self.items.iter().filter_map(|i| match i { Item::Struct(s) => Some(s), _ => None })
+    }
+}
@@ -0,0 +1,36 @@
+use crate::ast::{Purity, TypeRef};
+
+// Runtime'ın sağladığı servis fonksiyonları (gojo-runtime'daki DB, Console, Util, HTTP) ve `len`.
+// Analiz pass'leri çağrı hedeflerini önce kullanıcı fonksiyonlarında, sonra burada arar;
+// yorumlayıcı da aynı isimlerle runtime fonksiyonlarını çağırır.
+#[derive(Debug, Clone)]
+pub struct Builtin {
+    pub name: &'static str,
+    pub purity: Purity,
+    // Sonucu dış dünyadan gelir, Untrusted kabul edilir
+    pub taint_source: bool,
+    // Parametre tipleri (None: her tip kabul edilir) ve dönüş tipi
+    pub params: &'static [Option<TypeRef>],
+    pub returns: TypeRef,
+    // Başarısız olabilir: gerçek dönüş tipi Result<returns, String>
+    pub fallible: bool,
+}
+
+impl Builtin {
+    pub fn return_type(&self) -> TypeRef {
+        if self.fallible { TypeRef::Result(Box::new(self.returns.clone()), Box::new(TypeRef::String)) } else { self.returns.clone() }
+    }
+}
+
+pub const BUILTINS: &[Builtin] = &[
+    Builtin { name: "DB.log", purity: Purity::Nondeterministic, taint_source: false, params: &[None], returns: TypeRef::Void, fallible: false },
+    Builtin { name: "Console.read", purity: Purity::Nondeterministic, taint_source: true, params: &[Some(TypeRef::String)], returns: TypeRef::String, fallible: false },
+    Builtin { name: "Util.now", purity: Purity::Nondeterministic, taint_source: false, params: &[], returns: TypeRef::Integer, fallible: false },
+    Builtin { name: "Util.to_int", purity: Purity::Deterministic, taint_source: false, params: &[Some(TypeRef::String)], returns: TypeRef::Integer, fallible: false },
+    Builtin { name: "len", purity: Purity::Deterministic, taint_source: false, params: &[None], returns: TypeRef::Integer, fallible: false },
+    Builtin { name: "HTTP.get", purity: Purity::Nondeterministic, taint_source: true, params: &[Some(TypeRef::String)], returns: TypeRef::String, fallible: true },
+];
+
+pub fn lookup(name: &str) -> Option<&'static Builtin> {
+    BUILTINS.iter().find(|b| b.name == name)
+}
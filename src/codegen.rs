@@ -1,11 +1,25 @@
 use crate::ast::*;
+use crate::builtins;
+use crate::analysis::types::TypeTable;
+use crate::diagnostics::SourceMap;
 use std::collections::HashSet;
 
 pub struct Codegen { 
     indent_level: usize,
     pure_functions: HashSet<String>,
+    // Tüm kullanıcı fonksiyonları (`len` gibi noktasız builtin'leri gölgeler)
+    functions: HashSet<String>,
     // YENİ: Şu an hangi fonksiyondayız, o fonksiyon saf mı?
     is_current_func_pure: bool, 
+    // Struct ve schema isimleri (Rust struct'ına dönüşen tipler)
+    records: HashSet<String>,
+    // Tip denetiminin çıkardığı ifade tipleri
+    types: TypeTable,
+    // Aritmetik hatalarının konumu ve içinde bulunulan fonksiyonun taşma davranışı
+    source: SourceMap,
+    overflow: Overflow,
+    // İçinde bulunulan closure sayısı: closure gövdesi senkrondur, async çağrılar block_on ile beklenir
+    closures: usize,
 }
 
 impl Codegen {
@@ -13,111 +27,112 @@ impl Codegen {
         Self { 
             indent_level: 0,
             pure_functions: HashSet::new(),
+            functions: HashSet::new(),
             is_current_func_pure: false, 
+            records: HashSet::new(),
+            types: TypeTable::default(),
+            source: SourceMap::default(),
+            overflow: Overflow::Checked,
+            closures: 0,
         } 
     }
 
     fn indent(&self) -> String { "    ".repeat(self.indent_level) }
 
+    // Servisler, schema doğrulama, altyapı çağrıları ve task'lar gojo-runtime kütüphanesindedir
     fn get_runtime_preamble(&self) -> String {
         r#"
-#![allow(dead_code, unused_imports, unused_variables, unused_parens, unused_mut)]
-use std::time::Duration;
-use std::io::{self, Write};
-use serde_json::Value;
-
-const RESET: &str = "\x1b[0m";
-const CYAN: &str = "\x1b[36m";   
-const GREEN: &str = "\x1b[32m";  
-const MAGENTA: &str = "\x1b[35m";
-const YELLOW: &str = "\x1b[33m"; 
-const BLUE: &str = "\x1b[34m";
-const RED: &str = "\x1b[31m";
-
-struct DB;
-impl DB {
-    async fn log<T: std::fmt::Display>(msg: T) { println!("  {}[DB] Log: {}{}", CYAN, msg, RESET); }
-}
-
-struct Console;
-impl Console {
-    async fn read(prompt: String) -> String {
-        print!("  {}[Console]  {}: {} ", BLUE, prompt, RESET);
-        io::stdout().flush().unwrap();
-        let mut buffer = String::new();
-        io::stdin().read_line(&mut buffer).unwrap();
-        buffer.trim().to_string()
-    }
-}
-
-struct Util;
-impl Util {
-    #[inline(always)]
-    async fn to_int(s: String) -> i64 { s.trim().parse::<i64>().unwrap_or(0) }
-    #[inline(always)]
-    async fn now() -> i64 {
-        std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_millis() as i64
-    }
-}
-
-struct HTTP;
-impl HTTP {
-    async fn get(url: String) -> String {
-        let client = reqwest::Client::builder().user_agent("GojoLang/1.0").build().unwrap();
-        match client.get(&url).send().await {
-            Ok(res) => res.text().await.unwrap_or_else(|e| format!("Error: {}", e)),
-            Err(e) => format!("Error: {}", e)
-        }
-    }
-}
-
-trait Validate { fn validate(&self) -> Result<String, String>; }
-impl Validate for String {
-    fn validate(&self) -> Result<String, String> { Ok(self.clone()) }
-}
-
-// TRAITLER (Inline optimize edildi)
-trait GojoAdd<Rhs> { type Output; fn g_add(self, rhs: Rhs) -> Self::Output; }
-impl GojoAdd<i64> for i64 { type Output = i64; #[inline(always)] fn g_add(self, rhs: i64) -> i64 { self + rhs } }
-impl GojoAdd<String> for String { type Output = String; #[inline(always)] fn g_add(self, rhs: String) -> String { self + &rhs } }
-impl<'a> GojoAdd<&'a str> for String { type Output = String; #[inline(always)] fn g_add(self, rhs: &'a str) -> String { self + rhs } }
-impl GojoAdd<i64> for String { type Output = String; #[inline(always)] fn g_add(self, rhs: i64) -> String { format!("{}{}", self, rhs) } }
-
-trait GojoMul<Rhs> { type Output; fn g_mul(self, rhs: Rhs) -> Self::Output; }
-impl GojoMul<i64> for i64 { type Output = i64; #[inline(always)] fn g_mul(self, rhs: i64) -> i64 { self * rhs } }
-impl GojoMul<i64> for String { type Output = String; fn g_mul(self, rhs: i64) -> String { self.repeat(rhs as usize) } }
-impl<'a> GojoMul<i64> for &'a str { type Output = String; fn g_mul(self, rhs: i64) -> String { self.repeat(rhs as usize) } }
+#![allow(dead_code, unused_imports, unused_variables, unused_parens, unused_mut, unreachable_code, clippy::all)]
+use gojo_runtime::prelude::*;
+use gojo_runtime::{serde, serde_json, tokio};
 "#.to_string()
     }
 
-    pub fn generate(&mut self, functions: &Vec<FunctionDef>) -> String {
+    pub fn generate(&mut self, program: &Program, types: &TypeTable, source: &SourceMap) -> String {
+        self.types = types.clone();
+        self.source = source.clone();
         self.pure_functions.clear();
-        for func in functions {
+        self.functions = program.functions().map(|f| f.name.clone()).collect();
+        for func in program.functions() {
             if let Purity::Deterministic = func.purity {
                 self.pure_functions.insert(func.name.clone());
             }
         }
 
+        self.records = program.schemas().map(|s| s.name.clone()).chain(program.structs().map(|s| s.name.clone())).collect();
+
         let mut code = self.get_runtime_preamble();
-        for func in functions {
+        for def in program.structs() {
+            let fields: Vec<(&str, &TypeRef)> = def.fields.iter().map(|f| (f.name.as_str(), &f.field_type)).collect();
+            code.push_str(&self.generate_record(&def.name, &fields));
+            code.push_str(&self.generate_field_specs(&def.name, def.fields.iter().map(|f| (f.name.as_str(), &f.field_type, &[][..]))));
+        }
+        for schema in program.schemas() {
+            let fields: Vec<(&str, &TypeRef)> = schema.fields.iter().map(|f| (f.name.as_str(), &f.field_type)).collect();
+            code.push_str(&self.generate_record(&schema.name, &fields));
+            code.push_str(&self.generate_field_specs(&schema.name, schema.fields.iter().map(|f| (f.name.as_str(), &f.field_type, &f.constraints[..]))));
+        }
+        for func in program.functions() {
             code.push_str(&self.generate_function(func));
         }
-        if let Some(_) = functions.iter().find(|f| f.name == "main") {
-             code.push_str(&self.generate_main_shim());
+        if let Some(main) = program.functions().find(|f| f.name == "main") {
+            code.push_str(&self.generate_main_shim(main));
+        }
+        code
+    }
+
+    // Denetimli i64 işlemi: Site::checked("run.gj:3:12").add(a, b)
+    fn site(&self, span: Span) -> String {
+        let mode = match self.overflow { Overflow::Checked => "checked", Overflow::Wrapping => "wrapping" };
+        format!("Site::{}({:?})", mode, self.source.locate(span))
+    }
+
+    fn schema_const(name: &str) -> String { format!("SCHEMA_{}", name.to_uppercase()) }
+
+    // Struct/schema -> serde ile JSON'dan okunabilen Rust struct'ı
+    fn generate_record(&self, name: &str, fields: &[(&str, &TypeRef)]) -> String {
+        let mut code = format!("#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]\n#[serde(crate = \"gojo_runtime::serde\")]\npub struct {} {{\n", name);
+        for (field, t) in fields {
+            code.push_str(&format!("    pub {}: {},\n", field, self.map_type(t)));
+        }
+        code.push_str("}\n");
+        code.push_str(&format!("impl std::fmt::Display for {} {{ fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {{ write!(f, \"{{}}\", serde_json::to_string(self).unwrap_or_default()) }} }}\n", name));
+        code.push_str(&format!("impl GojoAdd<{}> for String {{ type Output = String; fn g_add(self, rhs: {}, _: Site) -> String {{ format!(\"{{}}{{}}\", self, rhs) }} }}\n\n", name, name));
+        code
+    }
+
+    fn generate_field_specs<'a>(&self, name: &str, fields: impl Iterator<Item = (&'a str, &'a TypeRef, &'a [Constraint])>) -> String {
+        let mut code = format!("const {}: &[FieldSpec] = &[\n", Self::schema_const(name));
+        for (field, t, constraints) in fields {
+            let kind = match t { TypeRef::Integer => "FieldKind::Int", TypeRef::String => "FieldKind::Str", _ => "FieldKind::Any" };
+            let rules = constraints.iter().map(|c| match c {
+                Constraint::Len(min, max) => format!("FieldRule::Len({}, {})", min, max),
+                Constraint::Range(min, max) => format!("FieldRule::Range({}, {})", min, max),
+                Constraint::Regex(re) => format!("FieldRule::Regex({:?})", re),
+            }).collect::<Vec<_>>().join(", ");
+            code.push_str(&format!("    FieldSpec {{ name: {:?}, kind: {}, rules: &[{}] }},\n", field, kind, rules));
         }
+        code.push_str("];\n\n");
         code
     }
 
+    // validate çağrısı: schema varsa alan kontrolleri, yoksa düz Validate trait'i
+    fn validate_call(target: &str, schema: &Option<String>) -> String {
+        match schema {
+            Some(s) => format!("validate_schema::<{}>(&{}, {:?}, {})", s, target, s, Self::schema_const(s)),
+            None => format!("{}.validate()", target),
+        }
+    }
+
     fn generate_function(&mut self, func: &FunctionDef) -> String {
-        let real_func_name = if func.name == "main" { "user_main" } else { &func.name };
-        let params = func.params.iter().map(|p| format!("{}: {}", p.name, self.map_type(&p.param_type))).collect::<Vec<_>>().join(", ");
+        let real_func_name = if func.name == "main" { "user_main".to_string() } else { Self::function_name(&func.name) };
+        // Parametreler de değişken gibi atanabilir ve `m.insert(..)` ile değiştirilebilir
+        let params = func.params.iter().map(|p| format!("mut {}: {}", p.name, self.map_type(&p.param_type))).collect::<Vec<_>>().join(", ");
         
         // Hangi fonksiyonda olduğumuzu kaydedelim
         let is_pure = self.pure_functions.contains(&func.name);
         self.is_current_func_pure = is_pure;
+        self.overflow = func.overflow;
         
         let async_keyword = if is_pure { "" } else { "async " };
 
@@ -129,8 +144,14 @@ impl<'a> GojoMul<i64> for &'a str { type Output = String; fn g_mul(self, rhs: i6
         code
     }
 
-    fn generate_main_shim(&self) -> String {
-        r#"#[tokio::main] async fn main() { user_main("Internet".to_string()).await; tokio::time::sleep(std::time::Duration::from_millis(100)).await; }"#.to_string()
+    // Modül fonksiyonu Rust'ta düz bir isimdir: payments.fees.compute -> payments__fees__compute
+    fn function_name(name: &str) -> String { name.replace('.', "__") }
+
+    // Analiz main'in en fazla bir (metin) parametresi olduğunu garanti eder; yorumlayıcı da aynı argümanı verir
+    fn generate_main_shim(&self, main: &FunctionDef) -> String {
+        let args = if main.params.is_empty() { "" } else { "\"Internet\".to_string()" };
+        let await_suffix = if self.pure_functions.contains(&main.name) { "" } else { ".await" };
+        format!(r#"#[tokio::main(crate = "gojo_runtime::tokio")] async fn main() {{ user_main({}){}; }}"#, args, await_suffix)
     }
 
     fn generate_block(&mut self, block: &Block) -> String {
@@ -141,11 +162,14 @@ impl<'a> GojoMul<i64> for &'a str { type Output = String; fn g_mul(self, rhs: i6
 
     fn generate_stmt(&mut self, stmt: &Statement) -> String {
         let indent = self.indent();
-        match stmt {
-            Statement::Let(s) => format!("{}let mut {} = {};\n", indent, s.name, self.generate_expr(&s.value)),
-            Statement::Assign { name, value } => format!("{}{} = {};\n", indent, name, self.generate_expr(value)),
-            Statement::ExprStmt(e) => format!("{}{};\n", indent, self.generate_expr(e)),
-            Statement::While { condition, body } => {
+        match &stmt.kind {
+            StmtKind::Let(s) => {
+                let annotation = s.ty.as_ref().map(|t| format!(": {}", self.map_type(t))).unwrap_or_default();
+                format!("{}let mut {}{} = {};\n", indent, s.name, annotation, self.generate_expr(&s.value))
+            },
+            StmtKind::Assign { name, value } => format!("{}{} = {};\n", indent, name, self.generate_expr(value)),
+            StmtKind::ExprStmt(e) => format!("{}{};\n", indent, self.generate_expr(e)),
+            StmtKind::While { condition, body } => {
                 let mut s = format!("{}while {} {{\n", indent, self.generate_expr(condition));
                 self.indent_level += 1;
                 s.push_str(&self.generate_block(body));
@@ -153,7 +177,7 @@ impl<'a> GojoMul<i64> for &'a str { type Output = String; fn g_mul(self, rhs: i6
                 s.push_str(&format!("{}}}\n", indent));
                 s
             }
-            Statement::For { var, start, end, step, body } => {
+            StmtKind::For { var, start, end, step, body } => {
                 let step_expr = if let Some(s) = step { self.generate_expr(s) } else { "1".to_string() };
                 let mut s = format!("{}{{\n", indent); 
                 s.push_str(&format!("{}let mut {} = {};\n", self.indent(), var, self.generate_expr(start)));
@@ -162,20 +186,35 @@ impl<'a> GojoMul<i64> for &'a str { type Output = String; fn g_mul(self, rhs: i6
                 s.push_str(&format!("{}while (_gojo_step > 0 && {} < _gojo_end) || (_gojo_step < 0 && {} > _gojo_end) {{\n", self.indent(), var, var));
                 self.indent_level += 1;
                 s.push_str(&self.generate_block(body));
-                
-                // For döngüsü optimizasyonu
-                if self.is_current_func_pure {
-                     s.push_str(&format!("{}{} += _gojo_step;\n", self.indent(), var));
-                } else {
-                     s.push_str(&format!("{}{} = {}.g_add(_gojo_step);\n", self.indent(), var, var));
-                }
-                
+                // Sayaç da taşma kuralına uyar: son adım i64 sınırını aşarsa döngünün konumu raporlanır
+                s.push_str(&format!("{}{} = {}.add({}, _gojo_step);\n", self.indent(), var, self.site(stmt.span), var));
                 self.indent_level -= 1;
                 s.push_str(&format!("{}}}\n", indent));
                 s.push_str(&format!("{}}}\n", indent));
                 s
             }
-            Statement::If { condition, then_block, else_block } => {
+            StmtKind::ForIn { binding, iterable, body } => {
+                // Gövde diziyi/map'i değiştirebilir: gezinme bir kopya üzerinde (map'te anahtar sırasıyla)
+                let source = match &iterable.kind {
+                    ExprKind::Identifier(name) => format!("{}.clone()", name),
+                    _ => self.generate_expr(iterable),
+                };
+                let pattern = match binding {
+                    ForBinding::Item(x) => format!("mut {}", x),
+                    ForBinding::Indexed(i, x) | ForBinding::Entry(i, x) => format!("(mut {}, mut {})", i, x),
+                };
+                let source = match binding {
+                    ForBinding::Indexed(..) => format!("(0i64..).zip({})", source),
+                    _ => source,
+                };
+                let mut s = format!("{}for {} in {} {{\n", indent, pattern, source);
+                self.indent_level += 1;
+                s.push_str(&self.generate_block(body));
+                self.indent_level -= 1;
+                s.push_str(&format!("{}}}\n", indent));
+                s
+            }
+            StmtKind::If { condition, then_block, else_block } => {
                 let mut s = format!("{}if {} {{\n", indent, self.generate_expr(condition));
                 self.indent_level += 1;
                 s.push_str(&self.generate_block(then_block));
@@ -188,90 +227,367 @@ impl<'a> GojoMul<i64> for &'a str { type Output = String; fn g_mul(self, rhs: i6
                     self.indent_level -= 1;
                     s.push_str(&format!("{}}}", indent));
                 }
-                s.push_str("\n");
+                s.push('\n');
                 s
             }
-            Statement::ScopeBlock { name, body } => {
+            StmtKind::ScopeBlock { name, body } => {
                 let mut s = format!("{}// Scope: {}\n{}{{\n", indent, name, indent);
                 self.indent_level += 1;
+                s.push_str(&format!("{}let mut gojo_scope = TaskScope::new(\"{}\");\n", self.indent(), name));
                 s.push_str(&self.generate_block(body));
-                s.push_str(&format!("{}tokio::time::sleep(Duration::from_millis(50)).await;\n", self.indent()));
+                s.push_str(&format!("{}gojo_scope.join_all().await;\n", self.indent()));
                 self.indent_level -= 1;
                 s.push_str(&format!("{}}}\n", indent));
                 s
             }
-            Statement::ValidateBlock { target, success_scope, .. } => {
-                let mut s = format!("{}let {} = {}.validate().unwrap();\n", indent, target, target);
+            StmtKind::ValidateBlock { target, schema, on_fail, success_scope } => {
+                let mut s = format!("{}match {} {{\n", indent, Self::validate_call(target, schema));
+                self.indent_level += 1;
+                s.push_str(&format!("{}Ok({}) => {{\n", self.indent(), target));
+                self.indent_level += 1;
                 s.push_str(&self.generate_block(success_scope));
+                self.indent_level -= 1;
+                s.push_str(&format!("{}}}\n{}Err(gojo_err) => {{\n", self.indent(), self.indent()));
+                self.indent_level += 1;
+                match on_fail {
+                    Some(b) => s.push_str(&self.generate_block(b)),
+                    None => s.push_str(&format!("{}eprintln!(\"  {{}}[Validate] {}: {{}}{{}}\", RED, gojo_err, RESET);\n", self.indent(), target)),
+                }
+                self.indent_level -= 1;
+                s.push_str(&format!("{}}}\n", self.indent()));
+                self.indent_level -= 1;
+                s.push_str(&format!("{}}}\n", indent));
                 s
             }
-            Statement::Return(Some(e)) => format!("{}return {};\n", indent, self.generate_expr(e)),
-            Statement::Return(None) => format!("{}return;\n", indent),
+            StmtKind::ValidateLet { name, target, schema, on_fail } => {
+                let mut s = format!("{}let mut {} = match {} {{\n", indent, name, Self::validate_call(target, schema));
+                self.indent_level += 1;
+                s.push_str(&format!("{}Ok(gojo_ok) => gojo_ok,\n{}Err(gojo_err) => {{\n", self.indent(), self.indent()));
+                self.indent_level += 1;
+                s.push_str(&self.generate_block(on_fail));
+                self.indent_level -= 1;
+                s.push_str(&format!("{}}}\n", self.indent()));
+                self.indent_level -= 1;
+                s.push_str(&format!("{}}};\n", indent));
+                s
+            }
+            StmtKind::Return(Some(e)) => format!("{}return {};\n", indent, self.generate_expr(e)),
+            StmtKind::Return(None) => format!("{}return;\n", indent),
+            // Scope Police: await all yalnızca bir scope içinde, en içteki gojo_scope'a aittir
+            StmtKind::AwaitAll => format!("{}gojo_scope.join_all().await;\n", indent),
         }
     }
 
-    fn generate_expr_as_string(&self, expr: &Expr) -> String {
-        match expr {
-            Expr::Literal(Literal::Str(_)) => self.generate_expr(expr),
+    fn generate_expr_as_string(&mut self, expr: &Expr) -> String {
+        match &expr.kind {
+            ExprKind::Literal(Literal::Str(_)) => self.generate_expr(expr),
             _ => format!("format!(\"{{}}\", {})", self.generate_expr(expr))
         }
     }
 
-    fn generate_expr(&self, expr: &Expr) -> String {
-        match expr {
-            Expr::Identifier(s) => s.clone(),
-            Expr::Literal(l) => match l { Literal::Int(i) => i.to_string(), Literal::Str(s) => format!("\"{}\".to_string()", s), Literal::Bool(b) => b.to_string() },
-            Expr::Infra(call) => {
-                format!("tokio::time::timeout(Duration::from_millis({}), {}::{}({})).await.unwrap()", call.config.timeout_ms, call.service, call.method, call.args.iter().map(|a| self.generate_expr_as_string(a)).collect::<Vec<_>>().join(", "))
+    fn generate_expr(&mut self, expr: &Expr) -> String {
+        match &expr.kind {
+            // Gojo değerleri kopya semantiğine sahiptir: async kodda değişkeni taşımak (move) yerine klonla
+            // Deterministic kodda Copy olan sayılar ve bool'lar olduğu gibi kalır (map anahtarı olan String'ler klonlanır)
+            // Değer olarak kullanılan fonksiyon: `xs.map(double)`
+            ExprKind::Identifier(s) if self.types.is_function(expr) => match self.types.get(expr) {
+                Some(t) => format!("{{ let gojo_fn: {} = Arc::new({}); gojo_fn }}", self.map_type(t), Self::function_name(s)),
+                None => format!("Arc::new({})", Self::function_name(s)),
             },
-            Expr::JsonField(source, key) => {
-                format!(
-                    "serde_json::from_str::<serde_json::Value>(&{}).ok().and_then(|v| v.get(\"{}\").map(|x| if x.is_string() {{ x.as_str().unwrap().to_string() }} else {{ x.to_string() }})).unwrap_or(\"HATA\".to_string())", 
-                    self.generate_expr(source), key
-                )
+            ExprKind::Identifier(s) => match self.types.get(expr) {
+                Some(TypeRef::Integer | TypeRef::Float | TypeRef::Byte | TypeRef::Bool) | None if self.is_current_func_pure => s.clone(),
+                _ => format!("{}.clone()", s),
             },
-            Expr::ArrayLiteral(elements) => {
-                let elems: Vec<String> = elements.iter().map(|e| self.generate_expr(e)).collect();
-                format!("vec![{}]", elems.join(", "))
+            ExprKind::Literal(l) => match l { Literal::Int(i) => i.to_string(), Literal::Float(f) => format!("{:?}f64", f), Literal::Str(s) => format!("{:?}.to_string()", s), Literal::Bool(b) => b.to_string() },
+            ExprKind::Infra(call) => {
+                // Argümanlar bir kez hesaplanır, her denemede kopyası verilir
+                let name = format!("{}.{}", call.service, call.method);
+                let args: String = call.args.iter().enumerate().map(|(i, a)| format!("let gojo_arg{} = {}; ", i, self.generate_expr_as_string(a))).collect();
+                let params: Vec<String> = (0..call.args.len()).map(|i| format!("gojo_arg{}.clone()", i)).collect();
+                let backoff = match call.config.backoff {
+                    Backoff::Fixed(ms) => format!("Backoff::Fixed({})", ms),
+                    Backoff::Exponential(ms) => format!("Backoff::Exponential({})", ms),
+                };
+                let call_code = format!("infra_call(\"{}\", {}, {}, {}, || {}::{}({}))", name, call.config.timeout_ms.unwrap_or_default(), call.config.retry, backoff, call.service, call.method, params.join(", "));
+                let call_code = self.awaited(call_code);
+                match &call.config.fallback {
+                    Some(f) => format!("{{ {}match {} {{ Ok(gojo_ok) => gojo_ok, Err(_) => {} }} }}", args, call_code, self.generate_expr(f)),
+                    None => format!("{{ {}{} }}", args, call_code),
+                }
             },
-            Expr::Index(arr, idx) => {
-                format!("{}[({} as usize)]", self.generate_expr(arr), self.generate_expr(idx))
+            ExprKind::JsonField(source, key) => format!("json_field(&{}, {:?})", self.generate_expr(source), key),
+            ExprKind::Ctor(c, payload) => match payload {
+                Some(p) => format!("{}({})", c.name(), self.generate_expr(p)),
+                None => c.name().to_string(),
             },
-            // AKILLI HİBRİT SİSTEM 
-            Expr::Binary(left, op, right) => {
-                // Eğer fonksiyon SAF ise, direkt + - * / kullan (Native Hız)
-                // Değilse .g_add() kullan (String birleştirme desteği için)
-                if self.is_current_func_pure {
-                     let op_str = match op { 
-                        BinaryOp::Add => "+", BinaryOp::Sub => "-", BinaryOp::Mul => "*", BinaryOp::Div => "/",
-                        BinaryOp::Eq => "==", BinaryOp::Neq => "!=", 
-                        BinaryOp::Gt => ">", BinaryOp::Lt => "<",
-                        BinaryOp::Gte => ">=", BinaryOp::Lte => "<=",
+            ExprKind::Match(scrutinee, arms) => {
+                let indent = self.indent();
+                let mut s = format!("match {} {{\n", self.generate_expr(scrutinee));
+                self.indent_level += 1;
+                for arm in arms {
+                    let pattern = match &arm.pattern {
+                        Pattern::Wildcard => "_".to_string(),
+                        Pattern::Ctor(c, _) if !c.has_payload() => c.name().to_string(),
+                        Pattern::Ctor(c, binding) => format!("{}({})", c.name(), binding.as_deref().unwrap_or("_")),
                     };
-                    format!("({} {} {})", self.generate_expr(left), op_str, self.generate_expr(right))
-                } else {
-                    match op {
-                        BinaryOp::Add => format!("{}.g_add({})", self.generate_expr(left), self.generate_expr(right)),
-                        BinaryOp::Mul => format!("{}.g_mul({})", self.generate_expr(left), self.generate_expr(right)),
-                        _ => {
-                             let op_str = match op { 
-                                 BinaryOp::Sub => "-", BinaryOp::Div => "/",
-                                BinaryOp::Eq => "==", BinaryOp::Neq => "!=", 
-                                BinaryOp::Gt => ">", BinaryOp::Lt => "<",
-                                BinaryOp::Gte => ">=", BinaryOp::Lte => "<=",
-                                _ => unreachable!()
-                            };
-                            format!("({} {} {})", self.generate_expr(left), op_str, self.generate_expr(right))
+                    match &arm.body {
+                        ArmBody::Expr(e) => { let body = self.generate_expr(e); s.push_str(&format!("{}{} => {},\n", self.indent(), pattern, body)); }
+                        ArmBody::Block(b) => {
+                            s.push_str(&format!("{}{} => {{\n", self.indent(), pattern));
+                            self.indent_level += 1;
+                            s.push_str(&self.generate_block(b));
+                            self.indent_level -= 1;
+                            s.push_str(&format!("{}}}\n", self.indent()));
                         }
                     }
                 }
+                self.indent_level -= 1;
+                s.push_str(&format!("{}}}", indent));
+                s
+            },
+            ExprKind::ArrayLiteral(elements) => {
+                let elems: Vec<String> = elements.iter().map(|e| self.generate_expr(e)).collect();
+                format!("vec![{}]", elems.join(", "))
+            },
+            ExprKind::MapLiteral(entries) if entries.is_empty() => "BTreeMap::new()".to_string(),
+            ExprKind::MapLiteral(entries) => {
+                let entries: Vec<String> = entries.iter().map(|(k, v)| format!("({}, {})", self.generate_expr(k), self.generate_expr(v))).collect();
+                format!("BTreeMap::from([{}])", entries.join(", "))
+            },
+            // Negatif ya da sınır dışı indeks konumlu Gojo hatasıdır (usize'a sarıp panic etmez)
+            // Map'te olmayan anahtar hata değil None'dır
+            ExprKind::Index(arr, idx) => {
+                let target = self.place(arr);
+                match self.types.get(arr) {
+                    Some(TypeRef::Map(..)) => format!("{}.g_get({})", target, self.generate_expr(idx)),
+                    _ => format!("{}.index(&{}, {})", self.site(expr.span), target, self.generate_expr(idx)),
+                }
+            },
+            // Dilim sınırları da indeks gibi konumlu hatadır
+            ExprKind::Slice(arr, from, to) => {
+                let target = self.place(arr);
+                format!("{}.slice(&{}, {}, {})", self.site(expr.span), target, self.generate_expr(from), self.generate_expr(to))
+            },
+            // Fonksiyon ödünç verilir (&dyn Fn), dizi kopyalanmaz
+            ExprKind::MethodCall(target, method, args) if HIGHER_ORDER_METHODS.contains(&method.as_str()) => {
+                let target = self.place(target);
+                let args: Vec<String> = args.iter().enumerate()
+                    .map(|(i, a)| if i + 1 == args.len() { format!("&*({})", self.generate_expr(a)) } else { self.generate_expr(a) })
+                    .collect();
+                format!("{}.g_{}({})", target, method, args.join(", "))
+            },
+            ExprKind::MethodCall(target, method, args) => {
+                let args: Vec<String> = args.iter().map(|a| self.generate_expr(a)).collect();
+                // Değiştiren metotlar kopyada değil değişkenin kendisinde çalışır
+                let target = match &target.kind {
+                    ExprKind::Identifier(name) if MUTATING_METHODS.contains(&method.as_str()) => name.clone(),
+                    _ => self.generate_expr(target),
+                };
+                format!("{}.g_{}({})", target, method, args.join(", "))
+            },
+            // Kaçış dizileri çözülmüş metin Rust literal'i olarak yeniden kaçışlanır ({:?}); süslü parantezler ikilenir
+            ExprKind::Interpolated(parts) => {
+                let mut template = String::new();
+                let mut args = Vec::new();
+                for part in parts {
+                    match part {
+                        StrPart::Text(s) => template.push_str(&s.replace('{', "{{").replace('}', "}}")),
+                        StrPart::Expr(e) => { template.push_str("{}"); args.push(self.generate_expr(e)); }
+                    }
+                }
+                format!("format!({:?}, {})", template, args.join(", "))
+            },
+            ExprKind::Field(target, field) => match &target.kind {
+                // Sadece alan klonlanır, tüm struct değil
+                ExprKind::Identifier(name) => format!("{}.{}.clone()", name, field),
+                _ => format!("{}.{}.clone()", self.generate_expr(target), field),
+            },
+            ExprKind::StructLiteral(name, fields) => {
+                let fields: Vec<String> = fields.iter().map(|(f, v)| format!("{}: {}", f, self.generate_expr(v))).collect();
+                format!("{} {{ {} }}", name, fields.join(", "))
+            },
+            // AKILLI HİBRİT SİSTEM 
+            ExprKind::Binary(left, op, right) => {
+                // İki taraf da i64 ise Site üzerinden denetimli işlem (Native Hız, taşmada konumlu hata); u8 için `_u8` metotları
+                // f64 işlemleri Rust operatörleridir (taşma inf/NaN üretir, hata değildir)
+                // Değilse .g_add() / .g_mul() kullan (String birleştirme ve tekrar için; i64 ise aynı Site kuralı)
+                let (lt, rt) = (self.types.get(left), self.types.get(right));
+                let native = lt == Some(&TypeRef::Integer) && rt == Some(&TypeRef::Integer);
+                let byte = lt == Some(&TypeRef::Byte) || rt == Some(&TypeRef::Byte);
+                let float = lt == Some(&TypeRef::Float) || rt == Some(&TypeRef::Float);
+                let (l, r) = (self.generate_expr(left), self.generate_expr(right));
+                let site = self.site(expr.span);
+                match op {
+                    BinaryOp::Add | BinaryOp::Sub | BinaryOp::Mul | BinaryOp::Div | BinaryOp::Mod if float => format!("({} {} {})", l, op.symbol(), r),
+                    BinaryOp::Add if byte => format!("{}.add_u8({}, {})", site, l, r),
+                    BinaryOp::Sub if byte => format!("{}.sub_u8({}, {})", site, l, r),
+                    BinaryOp::Mul if byte => format!("{}.mul_u8({}, {})", site, l, r),
+                    BinaryOp::Div if byte => format!("{}.div_u8({}, {})", site, l, r),
+                    BinaryOp::Mod if byte => format!("{}.rem_u8({}, {})", site, l, r),
+                    BinaryOp::Add if native => format!("{}.add({}, {})", site, l, r),
+                    BinaryOp::Mul if native => format!("{}.mul({}, {})", site, l, r),
+                    BinaryOp::Add => format!("{}.g_add({}, {})", l, r, site),
+                    BinaryOp::Mul => format!("{}.g_mul({}, {})", l, r, site),
+                    BinaryOp::Sub => format!("{}.sub({}, {})", site, l, r),
+                    BinaryOp::Div => format!("{}.div({}, {})", site, l, r),
+                    BinaryOp::Mod => format!("{}.rem({}, {})", site, l, r),
+                    // && / || Rust'ta da kısa devre yapar; sağdaki .await sadece gerekirse çalışır
+                    _ => format!("({} {} {})", l, op.symbol(), r),
+                }
             },
-            Expr::Call(n, a) => {
-                let await_suffix = if self.pure_functions.contains(n) { "" } else { ".await" };
-                format!("{}({}){}", n.replace(".", "::"), a.iter().map(|x| self.generate_expr(x)).collect::<Vec<_>>().join(", "), await_suffix)
+            ExprKind::Unary(UnaryOp::Neg, e) if self.types.get(e) == Some(&TypeRef::Float) => format!("(-{})", self.generate_expr(e)),
+            ExprKind::Unary(UnaryOp::Neg, e) => format!("{}.neg({})", self.site(expr.span), self.generate_expr(e)),
+            ExprKind::Unary(UnaryOp::Not, e) => format!("(!{})", self.generate_expr(e)),
+            ExprKind::Call(n, a) if n == "len" && a.len() == 1 && !self.functions.contains(n) => format!("({}.len() as i64)", self.place(&a[0])),
+            // Fonksiyon değeri çağrısı: closure'lar senkrondur, beklenmez
+            ExprKind::Call(n, a) if !self.functions.contains(n) && builtins::lookup(n).is_none() => {
+                format!("{}({})", n, a.iter().map(|x| self.generate_expr(x)).collect::<Vec<_>>().join(", "))
             },
-            Expr::Spawn(e) => format!("tokio::spawn(async move {{ {} }})", self.generate_expr(e)),
-            Expr::Await(e) => format!("{}.await", self.generate_expr(e)),
+            ExprKind::Call(n, a) => {
+                let is_sync_builtin = builtins::lookup(n).map(|b| b.purity == Purity::Deterministic).unwrap_or(false);
+                // Builtin servisler runtime'daki tiplerin metotlarıdır (DB.log -> DB::log)
+                let callee = if builtins::lookup(n).is_some() && !self.functions.contains(n) { n.replace('.', "::") } else { Self::function_name(n) };
+                let call = format!("{}({})", callee, a.iter().map(|x| self.generate_expr(x)).collect::<Vec<_>>().join(", "));
+                if self.pure_functions.contains(n) || is_sync_builtin { call } else { self.awaited(call) }
+            },
+            ExprKind::Spawn(e) => {
+                // Task'a yakalanan değişkenlerin kopyası verilir; dıştaki değişkenler kullanılmaya devam eder
+                let mut captured = Vec::new();
+                Self::collect_identifiers(e, &mut captured);
+                let clones: String = captured.iter().map(|v| format!("let {} = {}.clone(); ", v, v)).collect();
+                format!("{{ {}gojo_scope.spawn(async move {{ {} }}) }}", clones, self.generate_expr(e))
+            },
+            ExprKind::Await(e) => format!("{}.await", self.generate_expr(e)),
+            // Closure yakaladığı değişkenlerin kopyasını taşır (spawn gibi): `move |x: i64| -> i64 { .. }`
+            ExprKind::Lambda(params, body) => {
+                let mut captured = Vec::new();
+                Self::collect_identifiers(body, &mut captured);
+                captured.retain(|v| !params.iter().any(|(p, _)| p == v));
+                let clones: String = captured.iter().map(|v| format!("let {} = {}.clone(); ", v, v)).collect();
+                self.closures += 1;
+                let body = self.generate_expr(body);
+                self.closures -= 1;
+                match self.types.get(expr) {
+                    Some(t @ TypeRef::Func(types, ret)) => {
+                        let params: Vec<String> = params.iter().zip(types).map(|((p, _), t)| format!("mut {}: {}", p, self.map_type(t))).collect();
+                        format!("{{ {}let gojo_fn: {} = Arc::new(move |{}| -> {} {{ {} }}); gojo_fn }}", clones, self.map_type(t), params.join(", "), self.map_type(ret), body)
+                    }
+                    _ => {
+                        let params: Vec<String> = params.iter().map(|(p, _)| format!("mut {}", p)).collect();
+                        format!("{{ {}Arc::new(move |{}| {{ {} }}) }}", clones, params.join(", "), body)
+                    }
+                }
+            },
+        }
+    }
+
+    // Async çağrı: closure gövdesinde (senkron) block_on ile beklenir.
+    // Kullanıcı fonksiyonuyla çakışmasın diye tam yoluyla yazılır.
+    fn awaited(&self, call: String) -> String {
+        if self.closures > 0 { format!("gojo_runtime::block_on({})", call) } else { format!("{}.await", call) }
+    }
+
+    // Sadece okunan değişken kopyalanmadan ödünç verilir (xs[i], len(xs))
+    fn place(&mut self, expr: &Expr) -> String {
+        match &expr.kind {
+            ExprKind::Identifier(name) => name.clone(),
+            _ => format!("({})", self.generate_expr(expr)),
+        }
+    }
+
+    fn collect_identifiers(expr: &Expr, out: &mut Vec<String>) {
+        let mut visit = |e: &Expr| Self::collect_identifiers(e, out);
+        match &expr.kind {
+            // Modül fonksiyonu değeri (lib.double) bir değişken değildir
+            ExprKind::Identifier(name) if name.contains('.') => {}
+            ExprKind::Identifier(name) => if !out.contains(name) { out.push(name.clone()) },
+            ExprKind::Literal(_) => {}
+            ExprKind::Binary(l, _, r) | ExprKind::Index(l, r) => { visit(l); visit(r); }
+            ExprKind::Slice(arr, from, to) => { visit(arr); visit(from); visit(to); }
+            ExprKind::Call(_, args) | ExprKind::ArrayLiteral(args) => args.iter().for_each(visit),
+            ExprKind::MethodCall(target, _, args) => { visit(target); args.iter().for_each(visit); }
+            ExprKind::Interpolated(parts) => parts.iter().filter_map(StrPart::expr).for_each(visit),
+            ExprKind::Infra(call) => { call.args.iter().for_each(&mut visit); call.config.fallback.iter().for_each(|f| visit(f)); }
+            ExprKind::Spawn(e) | ExprKind::Await(e) | ExprKind::JsonField(e, _) | ExprKind::Field(e, _) | ExprKind::Unary(_, e) => visit(e),
+            // Closure parametreleri dışarıdan yakalanmaz
+            ExprKind::Lambda(params, body) => {
+                let mut inner = Vec::new();
+                Self::collect_identifiers(body, &mut inner);
+                for name in inner {
+                    if !params.iter().any(|(p, _)| *p == name) && !out.contains(&name) { out.push(name); }
+                }
+            }
+            ExprKind::StructLiteral(_, fields) => fields.iter().for_each(|(_, v)| visit(v)),
+            ExprKind::MapLiteral(entries) => entries.iter().for_each(|(k, v)| { visit(k); visit(v); }),
+            ExprKind::Ctor(_, payload) => payload.iter().for_each(|p| visit(p)),
+            ExprKind::Match(scrutinee, arms) => {
+                visit(scrutinee);
+                // Desenin bağladığı isimler dışarıdan yakalanmaz
+                for arm in arms {
+                    let mut inner = Vec::new();
+                    match &arm.body {
+                        ArmBody::Expr(e) => Self::collect_identifiers(e, &mut inner),
+                        ArmBody::Block(b) => Self::collect_block_identifiers(b, &mut inner),
+                    }
+                    let binding = match &arm.pattern { Pattern::Ctor(_, b) => b.as_deref(), Pattern::Wildcard => None };
+                    for name in inner {
+                        if Some(name.as_str()) != binding && !out.contains(&name) { out.push(name); }
+                    }
+                }
+            }
+        }
+    }
+
+    // Blok içinde bağlanan isimler (let, döngü değişkenleri) bağlandıkları yerden sonra yakalanmaz
+    fn collect_block_identifiers(block: &Block, out: &mut Vec<String>) {
+        let mut bound: Vec<&str> = Vec::new();
+        for stmt in &block.statements {
+            let mut inner = Vec::new();
+            let mut visit = |e: &Expr| Self::collect_identifiers(e, &mut inner);
+            match &stmt.kind {
+                StmtKind::Let(l) => visit(&l.value),
+                StmtKind::Assign { name, value } => { visit(value); inner.push(name.clone()); }
+                StmtKind::ExprStmt(e) | StmtKind::Return(Some(e)) => visit(e),
+                StmtKind::If { condition, then_block, else_block } => {
+                    visit(condition);
+                    Self::collect_block_identifiers(then_block, &mut inner);
+                    if let Some(b) = else_block { Self::collect_block_identifiers(b, &mut inner); }
+                }
+                StmtKind::While { condition, body } => { visit(condition); Self::collect_block_identifiers(body, &mut inner); }
+                StmtKind::For { var, start, end, step, body } => {
+                    visit(start);
+                    visit(end);
+                    step.iter().for_each(&mut visit);
+                    let mut body_names = Vec::new();
+                    Self::collect_block_identifiers(body, &mut body_names);
+                    inner.extend(body_names.into_iter().filter(|n| n != var));
+                }
+                StmtKind::ForIn { binding, iterable, body } => {
+                    visit(iterable);
+                    let mut body_names = Vec::new();
+                    Self::collect_block_identifiers(body, &mut body_names);
+                    inner.extend(body_names.into_iter().filter(|n| !binding.names().contains(&n.as_str())));
+                }
+                StmtKind::ScopeBlock { body, .. } => Self::collect_block_identifiers(body, &mut inner),
+                StmtKind::ValidateBlock { target, on_fail, success_scope, .. } => {
+                    inner.push(target.clone());
+                    if let Some(b) = on_fail { Self::collect_block_identifiers(b, &mut inner); }
+                    Self::collect_block_identifiers(success_scope, &mut inner);
+                }
+                StmtKind::ValidateLet { target, on_fail, .. } => {
+                    inner.push(target.clone());
+                    Self::collect_block_identifiers(on_fail, &mut inner);
+                }
+                StmtKind::Return(None) | StmtKind::AwaitAll => {}
+            }
+            for name in inner {
+                if !bound.contains(&name.as_str()) && !out.contains(&name) { out.push(name); }
+            }
+            match &stmt.kind {
+                StmtKind::Let(l) => bound.push(&l.name),
+                StmtKind::ValidateLet { name, .. } => bound.push(name),
+                _ => {}
+            }
         }
     }
 
@@ -279,9 +595,82 @@ impl<'a> GojoMul<i64> for &'a str { type Output = String; fn g_mul(self, rhs: i6
         match t { 
             TypeRef::Void => "()".to_string(), 
             TypeRef::Integer => "i64".to_string(), 
+            TypeRef::Float => "f64".to_string(),
+            TypeRef::Byte => "u8".to_string(),
             TypeRef::String => "String".to_string(),
+            TypeRef::Bool => "bool".to_string(),
             TypeRef::Array(inner) => format!("Vec<{}>", self.map_type(inner)),
-            _ => "String".to_string() 
+            TypeRef::Result(ok, err) => format!("Result<{}, {}>", self.map_type(ok), self.map_type(err)),
+            TypeRef::Option(inner) => format!("Option<{}>", self.map_type(inner)),
+            TypeRef::Map(k, v) => format!("BTreeMap<{}, {}>", self.map_type(k), self.map_type(v)),
+            TypeRef::Task(inner) => format!("Task<{}>", self.map_type(inner)),
+            TypeRef::Func(params, ret) => {
+                let params: Vec<String> = params.iter().map(|p| self.map_type(p)).collect();
+                format!("Arc<dyn Fn({}) -> {} + Send + Sync>", params.join(", "), self.map_type(ret))
+            }
+            TypeRef::Custom(name) if self.records.contains(name) => name.clone(),
+            // Untrusted çalışma zamanında bir String'dir
+            TypeRef::Untrusted | TypeRef::Custom(_) => "String".to_string(),
         } 
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diagnostics::SourceFile;
+    use crate::driver;
+
+    fn generate(source: &str) -> String {
+        let program = driver::parse(source).expect("parse");
+        let types = driver::analyze(&program).expect("analyze");
+        driver::generate(&program, &types, &SourceMap::single(SourceFile::new("test.gj", source)))
+    }
+
+    #[test]
+    fn schemas_become_records_with_field_rules() {
+        let code = generate("schema Payment {\n    amount: i64 range(1, 10000),\n    currency: String len(3, 3) regex(\"^[A-Z]+$\")\n}\nnondeterministic fn main(raw: Untrusted) {\n    validate raw { schema: Payment, on_fail: { DB.log(\"bad\") }, success: { DB.log(\"ok\") } }\n    let p = validate raw { schema: Payment, on_fail: return }\n    DB.log(p.amount)\n}\n");
+        assert!(code.contains("pub struct Payment {\n    pub amount: i64,\n    pub currency: String,\n}"), "{}", code);
+        assert!(code.contains("FieldSpec { name: \"amount\", kind: FieldKind::Int, rules: &[FieldRule::Range(1, 10000)] }"), "{}", code);
+        assert!(code.contains("FieldSpec { name: \"currency\", kind: FieldKind::Str, rules: &[FieldRule::Len(3, 3), FieldRule::Regex(\"^[A-Z]+$\")] }"), "{}", code);
+        assert!(code.contains("match validate_schema::<Payment>(&raw, \"Payment\", SCHEMA_PAYMENT) {"), "{}", code);
+        assert!(code.contains("let mut p = match validate_schema::<Payment>(&raw, \"Payment\", SCHEMA_PAYMENT) {"), "{}", code);
+    }
+
+    #[test]
+    fn validate_without_schema_uses_the_plain_check() {
+        let code = generate("nondeterministic fn main(raw: Untrusted) {\n    validate raw { success: { DB.log(raw) } }\n}\n");
+        assert!(code.contains("match raw.validate() {"), "{}", code);
+        assert!(!code.contains("validate_schema"), "{}", code);
+        // on_fail yoksa hata raporlanır ve program devam eder
+        assert!(code.contains("[Validate] raw:"), "{}", code);
+    }
+
+    #[test]
+    fn tasks_clone_variables_used_in_block_arms() {
+        let code = generate("nondeterministic fn main(input: Untrusted) {\n    let label = \"n\"\n    let r: Result<i64, String> = Ok(2)\n    scope S {\n        let h = spawn match r {\n            Ok(n) => {\n                let doubled = n * 2\n                DB.log(label + doubled)\n            },\n            Err(e) => { DB.log(e) },\n        }\n        await h\n    }\n    DB.log(label)\n}\n");
+        assert!(code.contains("{ let r = r.clone(); let label = label.clone(); gojo_scope.spawn(async move {"), "{}", code);
+        // Kolun içinde bağlanan isimler dışarıdan kopyalanmaz
+        assert!(!code.contains("let doubled = doubled.clone()") && !code.contains("let n = n.clone()"), "{}", code);
+    }
+
+    #[test]
+    fn scopes_own_a_task_set_that_is_joined_on_exit() {
+        let code = generate("deterministic fn sq(n: i64) -> i64 { return n * n }\nnondeterministic fn main(input: Untrusted) {\n    scope Work {\n        let a = spawn sq(2)\n        await all\n        DB.log(await a)\n    }\n}\n");
+        assert!(code.contains("let mut gojo_scope = TaskScope::new(\"Work\");"), "{}", code);
+        assert!(code.contains("gojo_scope.spawn(async move {"), "{}", code);
+        // `await all` ve scope sonu aynı task setini bekler
+        assert_eq!(code.matches("gojo_scope.join_all().await;").count(), 2, "{}", code);
+        let code = generate("deterministic fn sq(n: i64) -> i64 { return n * n }\n");
+        assert!(!code.contains("TaskScope"), "{}", code);
+    }
+
+
+    #[test]
+    fn impure_closures_block_on_their_async_calls() {
+        let code = generate("nondeterministic fn main(input: Untrusted) {\n    let log = |x: i64| DB.log(x)\n    log(1)\n    let t = |x: i64| x + call Util.now() { timeout: 1s, fallback: 0 }\n    DB.log(t(1))\n}\n");
+        assert!(code.contains("gojo_runtime::block_on(DB::log(x.clone()))"), "{}", code);
+        assert!(code.contains("gojo_runtime::block_on(infra_call(\"Util.now\""), "{}", code);
+        // Closure dışındaki çağrılar beklenir
+        assert!(code.contains("DB::log(t(1)).await"), "{}", code);
+    }
+}
@@ -0,0 +1,116 @@
+use crate::ast::Span;
+
+// Derleyici hatası: hangi pass'ten geldiği, mesaj ve kaynak konumu
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub pass: &'static str,
+    pub message: String,
+    pub span: Span,
+    pub notes: Vec<String>,
+}
+
+impl Diagnostic {
+    pub fn error(pass: &'static str, message: impl Into<String>, span: Span) -> Self {
+        Self { pass, message: message.into(), span, notes: Vec::new() }
+    }
+
+    pub fn with_note(mut self, note: impl Into<String>) -> Self {
+        self.notes.push(note.into());
+        self
+    }
+
+    // rustc tarzı çıktı:
+    //   error[scope]: Undefined variable used: x
+    //     --> run.gj:5:13
+    //      |
+    //    5 |     let a = x + 1
+    //      |             ^
+    pub fn render(&self, filename: &str, source: &str) -> String {
+        let start = self.span.start.min(source.len());
+        let (line, col) = line_col(source, start);
+        let line_start = source[..start].rfind('\n').map(|i| i + 1).unwrap_or(0);
+        let line_end = source[line_start..].find('\n').map(|i| line_start + i).unwrap_or(source.len());
+        let line_text = source[line_start..line_end].trim_end_matches('\r');
+        let gutter = " ".repeat(line.to_string().len());
+
+        // Çok satırlı span'lerde sadece ilk satır işaretlenir
+        let caret_end = self.span.end.clamp(start, line_end);
+        let caret_len = source[start..caret_end].chars().count().max(1);
+        let caret_pad: String = line_text.chars().take(col - 1).map(|c| if c == '\t' { '\t' } else { ' ' }).collect();
+
+        let mut out = format!("error[{}]: {}\n", self.pass, self.message);
+        out.push_str(&format!("{}--> {}:{}:{}\n", gutter, filename, line, col));
+        out.push_str(&format!("{} |\n", gutter));
+        out.push_str(&format!("{} | {}\n", line, line_text));
+        out.push_str(&format!("{} | {}{}\n", gutter, caret_pad, "^".repeat(caret_len)));
+        for note in &self.notes {
+            out.push_str(&format!("{} = note: {}\n", gutter, note));
+        }
+        out
+    }
+}
+
+// Çalışma zamanı hatalarının konumu (derlenmiş kod ve yorumlayıcı): "run.gj:5:13"
+#[derive(Debug, Clone, Default)]
+pub struct SourceFile {
+    pub name: String,
+    pub text: String,
+}
+
+impl SourceFile {
+    pub fn new(name: impl Into<String>, text: impl Into<String>) -> Self { Self { name: name.into(), text: text.into() } }
+
+    pub fn locate(&self, span: Span) -> String {
+        let (line, col) = line_col(&self.text, span.start);
+        format!("{}:{}:{}", self.name, line, col)
+    }
+}
+
+// Çok dosyalı programın kaynakları: her dosya, parser'a verilen taban offset'inden başlar.
+// Span'ler tüm dosyalar boyunca tekildir; hangi dosyaya ait olduğu buradan bulunur.
+#[derive(Debug, Clone, Default)]
+pub struct SourceMap {
+    files: Vec<(usize, SourceFile)>,
+}
+
+impl SourceMap {
+    pub fn single(file: SourceFile) -> Self { Self { files: vec![(0, file)] } }
+
+    // Bir sonraki dosyanın tabanı: öncekinin sonundan bir fazlası (dosya sonu span'leri karışmasın)
+    pub fn next_base(&self) -> usize {
+        self.files.last().map_or(0, |(base, f)| base + f.text.len() + 1)
+    }
+
+    pub fn add(&mut self, file: SourceFile) -> usize {
+        let base = self.next_base();
+        self.files.push((base, file));
+        base
+    }
+
+    // Span'in ait olduğu dosya ve dosya içindeki span
+    pub fn lookup(&self, span: Span) -> Option<(&SourceFile, Span)> {
+        let (base, file) = self.files.iter().rev().find(|(base, _)| *base <= span.start)?;
+        Some((file, Span::new(span.start - base, span.end.saturating_sub(*base))))
+    }
+
+    pub fn locate(&self, span: Span) -> String {
+        self.lookup(span).map(|(file, span)| file.locate(span)).unwrap_or_default()
+    }
+
+    pub fn render(&self, diagnostic: &Diagnostic) -> String {
+        match self.lookup(diagnostic.span) {
+            Some((file, span)) => Diagnostic { span, ..diagnostic.clone() }.render(&file.name, &file.text),
+            None => diagnostic.render("<unknown>", ""),
+        }
+    }
+}
+
+// Byte offset -> (satır, sütun), ikisi de 1'den başlar
+pub fn line_col(source: &str, offset: usize) -> (usize, usize) {
+    let offset = offset.min(source.len());
+    let before = &source[..offset];
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+    let col = source[line_start..offset].chars().count() + 1;
+    (line, col)
+}
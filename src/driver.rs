@@ -0,0 +1,212 @@
+use crate::analysis::determinism::{DeterminismAnalyzer, SymbolTable};
+use crate::analysis::exhaustive::ExhaustivenessAnalyzer;
+use crate::analysis::scope::ScopeAnalyzer;
+use crate::analysis::taint::TaintAnalyzer;
+use crate::analysis::types::{TypeChecker, TypeTable};
+use crate::ast::{Expr, FunctionDef, Program, Snippet, TypeRef};
+use crate::codegen::Codegen;
+use crate::diagnostics::{Diagnostic, SourceMap};
+use crate::parser;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+// Derleyici ön yüzü: parse + analiz pass'leri, ardından bağımsız bir Cargo projesi.
+// Üretilen proje derleyicinin kendi kaynaklarına dokunmaz; her çıktı dizini ayrı derlenir.
+
+// Sözdizimi hataları toplanır: hepsi birden raporlanır
+pub fn parse(source: &str) -> Result<Program, Vec<Diagnostic>> {
+    let (program, errors) = parser::parse_program(source);
+    if errors.is_empty() { Ok(program) } else { Err(errors) }
+}
+
+pub fn parse_snippet(source: &str) -> Result<Snippet, Diagnostic> { parser::parse_snippet(source) }
+
+pub fn parse_expr(source: &str) -> Result<Expr, Diagnostic> { parser::parse_expression(source) }
+
+// Tüm güvenlik ve tip pass'leri; her tanımın ilk hatası toplanır (LSP hepsini yayınlar)
+pub fn check(program: &Program) -> (TypeTable, Vec<Diagnostic>) {
+    let symbols = SymbolTable::from_program(program);
+    let mut errors = Vec::new();
+    for schema in program.schemas() {
+        errors.extend(TaintAnalyzer::check_schema(schema).err());
+    }
+    for def in program.structs() {
+        errors.extend(TypeChecker::check_struct(def, &symbols).err());
+    }
+    let mut types = TypeTable::default();
+    for func in program.functions() {
+        errors.extend(check_function(func, &symbols, &mut types).err());
+    }
+    (types, errors)
+}
+
+fn check_function(func: &FunctionDef, symbols: &SymbolTable, types: &mut TypeTable) -> Result<(), Diagnostic> {
+    if func.name == "main" { TypeChecker::check_main(func)?; }
+    DeterminismAnalyzer::check(func, symbols)?;
+    TaintAnalyzer::check(func, symbols)?;
+    ScopeAnalyzer::new().analyze(func, symbols)?;
+    TypeChecker::check(func, symbols, types)?;
+    ExhaustivenessAnalyzer::check(func, types)
+}
+
+// Derleyici ilk hatada durur
+pub fn analyze(program: &Program) -> Result<TypeTable, Diagnostic> {
+    let (types, errors) = check(program);
+    match errors.into_iter().next() {
+        Some(err) => Err(err),
+        None => Ok(types),
+    }
+}
+
+// REPL: tanımlar analiz edildikten sonra girdi, sentetik bir fonksiyonun gövdesi olarak aynı
+// pass'lerden geçer. Son deyim bir ifadeyse tipi de döndürülür.
+pub fn analyze_snippet(program: &Program, snippet: &FunctionDef) -> Result<(TypeTable, Option<TypeRef>), Diagnostic> {
+    let mut types = analyze(program)?;
+    let symbols = SymbolTable::from_program(program);
+    DeterminismAnalyzer::check(snippet, &symbols)?;
+    TaintAnalyzer::check(snippet, &symbols)?;
+    ScopeAnalyzer::new().analyze(snippet, &symbols)?;
+    let ty = TypeChecker::check_snippet(snippet, &symbols, &mut types)?;
+    ExhaustivenessAnalyzer::check(snippet, &types)?;
+    Ok((types, ty))
+}
+
+pub fn generate(program: &Program, types: &TypeTable, source: &SourceMap) -> String {
+    Codegen::new().generate(program, types, source)
+}
+
+// Cargo paket ve binary adı: dosya adından türetilir (run.gj -> run)
+pub fn package_name(input: &Path) -> String {
+    let stem = input.file_stem().and_then(|s| s.to_str()).unwrap_or("app");
+    let name: String = stem.chars().map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '_' }).collect();
+    if name.starts_with(|c: char| c.is_ascii_alphabetic()) { name } else { format!("gojo_{}", name) }
+}
+
+// gojo-runtime kaynağı: GOJO_RUNTIME_PATH ile değiştirilebilir, yoksa derleyicinin kendi paketi
+fn runtime_path() -> PathBuf {
+    std::env::var_os("GOJO_RUNTIME_PATH").map(PathBuf::from).unwrap_or_else(|| PathBuf::from(env!("CARGO_MANIFEST_DIR")))
+}
+
+fn cargo_manifest(name: &str) -> String {
+    let runtime = runtime_path();
+    let version = env!("CARGO_PKG_VERSION");
+    format!(r#"# gojo tarafından üretildi; elle düzenlemeyin
+[package]
+name = "{name}"
+version = "0.1.0"
+edition = "2021"
+
+[[bin]]
+name = "{name}"
+path = "src/main.rs"
+
+# Üst dizindeki bir workspace'e dahil olmasın
+[workspace]
+
+# Runtime sürümü derleyiciyle aynı olmalı: üretilen kod bu API'ye göre yazılır
+[dependencies]
+gojo-runtime = {{ version = "={version}", path = '{runtime}' }}
+
+[profile.release]
+opt-level = 3
+lto = true
+codegen-units = 1
+panic = "abort"
+strip = true
+"#, runtime = runtime.display())
+}
+
+// <out_dir>/Cargo.toml ve <out_dir>/src/main.rs yazar, Rust dosyasının yolunu döndürür
+pub fn emit_project(out_dir: &Path, name: &str, rust_code: &str) -> std::io::Result<PathBuf> {
+    let src_dir = out_dir.join("src");
+    fs::create_dir_all(&src_dir)?;
+    fs::write(out_dir.join("Cargo.toml"), cargo_manifest(name))?;
+    let main_rs = src_dir.join("main.rs");
+    fs::write(&main_rs, rust_code)?;
+    Ok(main_rs)
+}
+
+// Binary <out_dir>/target/release/<name> altındadır (CARGO_TARGET_DIR ayarlıysa onun altında)
+pub fn binary_path(out_dir: &Path, name: &str) -> PathBuf {
+    let target = std::env::var_os("CARGO_TARGET_DIR").map(PathBuf::from).unwrap_or_else(|| out_dir.join("target"));
+    target.join("release").join(format!("{}{}", name, std::env::consts::EXE_SUFFIX))
+}
+
+pub fn build_project(out_dir: &Path, name: &str) -> Result<PathBuf, String> {
+    let status = Command::new("cargo")
+        .arg("build")
+        .arg("--release")
+        .arg("--quiet")
+        .arg("--manifest-path")
+        .arg(out_dir.join("Cargo.toml"))
+        .status()
+        .map_err(|e| format!("cargo çalıştırılamadı: {}", e))?;
+    if !status.success() {
+        return Err("Rust derlemesi başarısız oldu.".to_string());
+    }
+    Ok(binary_path(out_dir, name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn package_names_are_valid_cargo_names() {
+        assert_eq!(package_name(Path::new("examples/run.gj")), "run");
+        assert_eq!(package_name(Path::new("My-App.v2.gj")), "my_app_v2");
+        assert_eq!(package_name(Path::new("2fast.gj")), "gojo_2fast");
+        assert_eq!(package_name(Path::new("")), "app");
+    }
+
+    #[test]
+    fn emits_a_standalone_project() {
+        let dir = std::env::temp_dir().join(format!("gojo-driver-{}", std::process::id()));
+        let main_rs = emit_project(&dir, "demo", "fn main() {}\n").unwrap();
+        assert_eq!(main_rs, dir.join("src").join("main.rs"));
+        assert_eq!(fs::read_to_string(&main_rs).unwrap(), "fn main() {}\n");
+        let manifest = fs::read_to_string(dir.join("Cargo.toml")).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(manifest.contains("name = \"demo\""), "{}", manifest);
+        assert!(manifest.contains(&format!("gojo-runtime = {{ version = \"={}\"", env!("CARGO_PKG_VERSION"))), "{}", manifest);
+        // Üst dizindeki bir workspace'e karışmaz
+        assert!(manifest.contains("\n[workspace]\n"), "{}", manifest);
+    }
+
+    #[test]
+    fn analysis_stops_at_the_first_error() {
+        let program = parse("deterministic fn f() -> i64 { return 1 }\nnondeterministic fn main(x: Untrusted) { DB.log(f()) }\n").unwrap();
+        assert!(analyze(&program).is_ok());
+        let program = parse("deterministic fn f() -> i64 { DB.log(1)\n return 1 }\nnondeterministic fn main(x: Untrusted) { DB.log(x) }\n").unwrap();
+        let (_, errors) = check(&program);
+        assert_eq!(errors.len(), 2);
+        assert_eq!(analyze(&program).unwrap_err().message, errors[0].message);
+        assert!(parse("deterministic fn f( { }").is_err());
+    }
+
+
+    #[test]
+    fn main_takes_at_most_one_text_parameter() {
+        for source in ["nondeterministic fn main() { DB.log(1) }\n", "nondeterministic fn main(s: String) { DB.log(1) }\n", "deterministic fn main() -> i64 { return 1 }\n"] {
+            assert!(analyze(&parse(source).unwrap()).is_ok(), "{}", source);
+        }
+        let message = |source: &str| analyze(&parse(source).unwrap()).unwrap_err().message;
+        assert_eq!(message("nondeterministic fn main(a: Untrusted, b: Untrusted) { }\n"), "`main` takes at most one parameter, found 2");
+        assert_eq!(message("nondeterministic fn main(n: i64) { }\n"), "`main` parameter `n` must be Untrusted or String, found i64");
+        // Başka isimli fonksiyonlar serbest
+        assert!(analyze(&parse("deterministic fn f(a: i64, b: i64) -> i64 { return a + b }\n").unwrap()).is_ok());
+    }
+
+    #[test]
+    fn the_main_shim_matches_the_signature() {
+        let shim = |source: &str| {
+            let program = parse(source).unwrap();
+            let code = generate(&program, &analyze(&program).unwrap(), &SourceMap::default());
+            code.lines().find(|l| l.contains("async fn main()")).unwrap().to_string()
+        };
+        assert!(shim("nondeterministic fn main() { DB.log(1) }\n").contains("{ user_main().await; }"));
+        assert!(shim("nondeterministic fn main(input: Untrusted) { DB.log(1) }\n").contains("{ user_main(\"Internet\".to_string()).await; }"));
+        assert!(shim("deterministic fn main() -> i64 { return 1 }\n").contains("{ user_main(); }"));
+    }
+}
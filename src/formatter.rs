@@ -0,0 +1,807 @@
+use crate::ast::*;
+use crate::diagnostics::Diagnostic;
+use crate::driver;
+use crate::parser;
+
+// gojo fmt: AST'den kanonik Gojo kaynağı üretir.
+// 4 boşluk girinti, noktalı virgül yok, tanımlar arasında tek boş satır; deyimler arasındaki
+// boş satırlar (en fazla bir) korunur. Yorumlar AST'de olmadığı için kaynaktaki konumlarına
+// göre en yakın deyimin önüne ya da aynı satırın sonuna geri yerleştirilir.
+
+// Sözdizimi hatası olan dosya biçimlendirilmez (okunamayan kısımlar kaybolurdu)
+pub fn format_source(source: &str) -> Result<String, Vec<Diagnostic>> {
+    let program = driver::parse(source)?;
+    let mut f = Formatter { source, comments: parser::comments(source), next: 0, last_end: 0, fresh: true, condition: false, indent: 0, out: String::new() };
+    f.program(&program);
+    Ok(f.out.trim_start_matches('\n').to_string() + "\n")
+}
+
+// Operatör önceliği: spawn/await ve closure gövdesi tüm ifadeyi yutar, ikili operatörler BinaryOp::precedence, atomlar en sıkı bağlanır
+const PREC_PREFIX: u8 = 0;
+const PREC_UNARY: u8 = 6;
+const PREC_ATOM: u8 = 7;
+
+fn prec(expr: &Expr) -> u8 {
+    match &expr.kind {
+        ExprKind::Binary(_, op, _) => op.precedence(),
+        ExprKind::Unary(..) => PREC_UNARY,
+        ExprKind::Spawn(_) | ExprKind::Await(_) | ExprKind::Lambda(..) => PREC_PREFIX,
+        _ => PREC_ATOM,
+    }
+}
+
+// Parser'ın çözdüğü kaçışları geri yazar; `${` metin olarak kalmalıysa `\${` olur
+fn escape(text: &str) -> String {
+    let mut out = String::new();
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            '\r' => out.push_str("\\r"),
+            '\0' => out.push_str("\\0"),
+            '$' if chars.peek() == Some(&'{') => out.push_str("\\$"),
+            c if c.is_control() => out.push_str(&format!("\\u{{{:x}}}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+// 2000 -> 2s, 250 -> 250ms
+fn duration(ms: u64) -> String {
+    if ms > 0 && ms.is_multiple_of(1000) { format!("{}s", ms / 1000) } else { format!("{}ms", ms) }
+}
+
+// Parser'ın tek deyimden ürettiği bloklar (else if, on_fail: return ...) süslü parantez taşımaz
+fn inline_statement(block: &Block) -> Option<&Statement> {
+    match block.statements.as_slice() {
+        [stmt] if stmt.span == block.span => Some(stmt),
+        _ => None,
+    }
+}
+
+struct Formatter<'a> {
+    source: &'a str,
+    comments: Vec<Comment>,
+    // Henüz yazılmamış ilk yorum
+    next: usize,
+    // Kaynakta en son yazılan kodun/yorumun bittiği yer
+    last_end: usize,
+    // Blok ya da dosya başındayız: boş satır korunmaz
+    fresh: bool,
+    // if/while/for/match başındaki ifadenin baş kısmındayız: struct literal parantez ister
+    condition: bool,
+    indent: usize,
+    out: String,
+}
+
+impl Formatter<'_> {
+    fn write(&mut self, s: &str) { self.out.push_str(s); }
+
+    fn line(&mut self) {
+        self.out.push('\n');
+        for _ in 0..self.indent { self.out.push_str("    "); }
+    }
+
+    // Kaynakta araya boş satır girmişse bir boş satır
+    fn gap(&mut self, at: usize) {
+        if !self.fresh && at > self.last_end && self.source[self.last_end..at].matches('\n').count() >= 2 {
+            self.out.push('\n');
+        }
+    }
+
+    // `before` konumundan önceki yorumlar: önceki kodla aynı satırdaysa satırın sonuna, değilse kendi satırına
+    fn comments_before(&mut self, before: usize) {
+        while let Some(comment) = self.comments.get(self.next).filter(|c| c.span.start < before).cloned() {
+            self.next += 1;
+            let same_line = comment.span.start >= self.last_end && !self.source[self.last_end..comment.span.start].contains('\n');
+            if same_line && !self.out.is_empty() {
+                self.write(" ");
+            } else {
+                self.gap(comment.span.start);
+                self.line();
+            }
+            self.write(&comment.text);
+            self.fresh = false;
+            self.last_end = self.last_end.max(comment.span.end);
+        }
+    }
+
+    // Son yazılan kodla aynı satırdaki yorumlar: ardından gelen boş satırdan önce yazılmalı
+    fn rest_of_line(&mut self) {
+        let line_end = self.source[self.last_end..].find('\n').map_or(self.source.len(), |i| self.last_end + i);
+        self.comments_before(line_end);
+    }
+
+    fn has_comments_in(&self, span: Span) -> bool {
+        self.comments.get(self.next).is_some_and(|c| c.span.start > span.start && c.span.start < span.end)
+    }
+
+    fn program(&mut self, program: &Program) {
+        // import/use satırları alt alta, tanımlardan bir boş satırla ayrılır
+        for import in &program.imports {
+            self.comments_before(import.span.start);
+            self.gap(import.span.start);
+            self.line();
+            self.fresh = false;
+            match &import.target {
+                ImportTarget::File(path) => self.write(&format!("import \"{}\"", escape(path))),
+                ImportTarget::Module(path) => self.write(&format!("use {}", path.join("."))),
+            }
+            self.last_end = import.span.end;
+            self.rest_of_line();
+        }
+        for (i, item) in program.items.iter().enumerate() {
+            if i > 0 || !program.imports.is_empty() {
+                self.out.push('\n');
+                self.fresh = true;
+            }
+            let span = item.span();
+            self.comments_before(span.start);
+            self.gap(span.start);
+            self.line();
+            self.fresh = false;
+            match item {
+                Item::Function(f) => self.function(f),
+                Item::Schema(s) => self.record("schema", &s.name, s.span, s.fields.iter().map(|f| (f.span, format!("{}: {}{}", f.name, f.field_type, constraints(&f.constraints))))),
+                Item::Struct(s) => self.record("struct", &s.name, s.span, s.fields.iter().map(|f| (f.span, format!("{}: {}", f.name, f.field_type)))),
+            }
+            self.last_end = span.end;
+            self.rest_of_line();
+        }
+        self.comments_before(self.source.len());
+    }
+
+    fn function(&mut self, func: &FunctionDef) {
+        let purity = match func.purity { Purity::Deterministic => "deterministic", Purity::Nondeterministic => "nondeterministic" };
+        let params: Vec<String> = func.params.iter().map(|p| format!("{}: {}", p.name, p.param_type)).collect();
+        let wrapping = if func.overflow == Overflow::Wrapping { " wrapping" } else { "" };
+        self.write(&format!("{}{} fn {}({})", purity, wrapping, func.name, params.join(", ")));
+        if func.return_type != TypeRef::Void {
+            self.write(&format!(" -> {}", func.return_type));
+        }
+        self.write(" ");
+        self.block(&func.body);
+    }
+
+    // struct/schema: her alan kendi satırında, virgülle ayrılır
+    fn record(&mut self, keyword: &str, name: &str, span: Span, fields: impl Iterator<Item = (Span, String)>) {
+        self.write(&format!("{} {} {{", keyword, name));
+        self.last_end = self.source[span.start..span.end].find('{').map_or(span.start, |i| span.start + i + 1);
+        self.indent += 1;
+        self.fresh = true;
+        let fields: Vec<(Span, String)> = fields.collect();
+        for (i, (field_span, text)) in fields.iter().enumerate() {
+            self.comments_before(field_span.start);
+            self.gap(field_span.start);
+            self.line();
+            self.fresh = false;
+            self.write(text);
+            if i + 1 < fields.len() { self.write(","); }
+            self.last_end = field_span.end;
+        }
+        self.comments_before(span.end);
+        self.indent -= 1;
+        self.line();
+        self.write("}");
+    }
+
+    fn block(&mut self, block: &Block) {
+        let has_comments = self.comments.get(self.next).is_some_and(|c| c.span.start < block.span.end);
+        if block.statements.is_empty() && !has_comments {
+            self.write("{}");
+            self.last_end = block.span.end;
+            return;
+        }
+        self.write("{");
+        self.last_end = block.span.start + 1;
+        self.indent += 1;
+        self.fresh = true;
+        for stmt in &block.statements {
+            self.statement(stmt);
+        }
+        self.comments_before(block.span.end);
+        self.indent -= 1;
+        self.line();
+        self.write("}");
+        self.last_end = block.span.end;
+    }
+
+    fn statement(&mut self, stmt: &Statement) {
+        self.comments_before(stmt.span.start);
+        self.gap(stmt.span.start);
+        self.line();
+        self.fresh = false;
+        self.stmt(stmt);
+        self.last_end = self.last_end.max(stmt.span.end);
+        // Tek satıra yazılan çok satırlı ifadelerin içindeki yorumlar deyimin arkasına taşınır
+        self.comments_before(stmt.span.end);
+    }
+
+    fn stmt(&mut self, stmt: &Statement) {
+        match &stmt.kind {
+            StmtKind::Let(l) => {
+                self.write(&format!("let {}", l.name));
+                if let Some(ty) = &l.ty { self.write(&format!(": {}", ty)); }
+                self.write(" = ");
+                self.expr(&l.value);
+            }
+            StmtKind::Assign { name, value } => {
+                self.write(&format!("{} = ", name));
+                self.expr(value);
+            }
+            StmtKind::If { condition, then_block, else_block } => {
+                self.write("if ");
+                self.condition(condition);
+                self.write(" ");
+                self.block(then_block);
+                if let Some(else_block) = else_block {
+                    // `} // yorum` satırında kalır; else bir alt satırdan devam eder
+                    let next = self.next;
+                    self.comments_before(else_block.span.start);
+                    if self.next > next {
+                        self.line();
+                        self.write("else ");
+                    } else {
+                        self.write(" else ");
+                    }
+                    match inline_statement(else_block) {
+                        Some(elif) => self.stmt(elif),
+                        None => self.block(else_block),
+                    }
+                }
+            }
+            StmtKind::While { condition, body } => {
+                self.write("while ");
+                self.condition(condition);
+                self.write(" ");
+                self.block(body);
+            }
+            StmtKind::For { var, start, end, step, body } => {
+                self.write(&format!("for {} in ", var));
+                self.condition(start);
+                self.write("..");
+                self.condition(end);
+                if let Some(step) = step {
+                    self.write(" by ");
+                    self.condition(step);
+                }
+                self.write(" ");
+                self.block(body);
+            }
+            StmtKind::ForIn { binding, iterable, body } => {
+                match binding {
+                    ForBinding::Item(x) => self.write(&format!("for {} in ", x)),
+                    ForBinding::Indexed(i, x) => self.write(&format!("for {}, {} in ", i, x)),
+                    ForBinding::Entry(k, v) => self.write(&format!("for ({}, {}) in ", k, v)),
+                }
+                self.condition(iterable);
+                self.write(" ");
+                self.block(body);
+            }
+            StmtKind::ScopeBlock { name, body } => {
+                self.write(&format!("scope {} ", name));
+                self.block(body);
+            }
+            StmtKind::AwaitAll => self.write("await all"),
+            StmtKind::ValidateBlock { target, schema, on_fail, success_scope } => {
+                self.write(&format!("validate {} {{", target));
+                self.validate_entries(schema.as_deref(), on_fail.as_deref(), Some(success_scope));
+            }
+            StmtKind::ValidateLet { name, target, schema, on_fail } => {
+                self.write(&format!("let {} = validate {} {{", name, target));
+                self.validate_entries(schema.as_deref(), Some(on_fail), None);
+            }
+            StmtKind::ExprStmt(e) => self.expr(e),
+            StmtKind::Return(None) => self.write("return"),
+            StmtKind::Return(Some(e)) => {
+                self.write("return ");
+                self.expr(e);
+            }
+        }
+    }
+
+    // validate x { schema: S, on_fail: ..., success: { ... } }: her giriş kendi satırında
+    fn validate_entries(&mut self, schema: Option<&str>, on_fail: Option<&Block>, success: Option<&Block>) {
+        let count = schema.is_some() as usize + on_fail.is_some() as usize + success.is_some() as usize;
+        let mut written = 0;
+        let mut separator = |f: &mut Self| {
+            written += 1;
+            if written < count { f.write(","); }
+        };
+        self.indent += 1;
+        if let Some(schema) = schema {
+            self.line();
+            self.write(&format!("schema: {}", schema));
+            separator(self);
+        }
+        if let Some(on_fail) = on_fail {
+            self.comments_before(on_fail.span.start);
+            self.line();
+            self.write("on_fail: ");
+            match inline_statement(on_fail) {
+                Some(stmt) => {
+                    self.stmt(stmt);
+                    self.last_end = self.last_end.max(stmt.span.end);
+                }
+                None => self.block(on_fail),
+            }
+            separator(self);
+        }
+        if let Some(success) = success {
+            self.comments_before(success.span.start);
+            self.line();
+            self.write("success: ");
+            self.block(success);
+            separator(self);
+        }
+        self.indent -= 1;
+        self.line();
+        self.write("}");
+    }
+
+    // Yorum içeren dizi/map literal'i ya da argüman listesi: her eleman kendi satırında,
+    // yorumlar yazıldıkları satırda kalır. `span` açılış parantezinden başlar
+    fn lines<T>(&mut self, span: Span, items: &[T], item_span: impl Fn(&T) -> Span, mut item: impl FnMut(&mut Self, &T)) {
+        self.last_end = span.start + 1;
+        self.indent += 1;
+        self.fresh = true;
+        for (i, it) in items.iter().enumerate() {
+            let at = item_span(it);
+            self.comments_before(at.start);
+            self.gap(at.start);
+            self.line();
+            self.fresh = false;
+            item(self, it);
+            if i + 1 < items.len() { self.write(","); }
+            self.last_end = at.end;
+        }
+        self.comments_before(span.end);
+        self.indent -= 1;
+        self.line();
+    }
+
+    fn operand(&mut self, expr: &Expr, parens: bool) {
+        if parens {
+            self.condition = false;
+            self.write("(");
+        }
+        self.expr(expr);
+        if parens { self.write(")"); }
+    }
+
+    fn exprs(&mut self, exprs: &[Expr]) {
+        for (i, e) in exprs.iter().enumerate() {
+            if i > 0 { self.write(", "); }
+            self.expr(e);
+        }
+    }
+
+    // `(a, b)`: argümanlar arasında yorum varsa çağrı çok satırlı kalır
+    fn args(&mut self, after: usize, end: usize, args: &[Expr]) {
+        let open = after + self.source[after..end].find('(').unwrap_or(0);
+        let span = Span::new(open, end);
+        self.write("(");
+        if self.has_comments_in(span) {
+            self.lines(span, args, |e| e.span, |f, e| f.expr(e));
+        } else {
+            self.exprs(args);
+        }
+        self.write(")");
+    }
+
+    // `if n < (Limit { n: 1 }).n { }`: parser koşulda struct literal'i ancak parantez içinde alır
+    fn condition(&mut self, expr: &Expr) {
+        self.condition = true;
+        self.expr(expr);
+        self.condition = false;
+    }
+
+    fn expr(&mut self, expr: &Expr) {
+        // Baş kısım operatörlerin operandları ve alıcılardan geçer; parantezli liste içleri serbesttir
+        let head = std::mem::take(&mut self.condition);
+        if head && matches!(expr.kind, ExprKind::StructLiteral(..)) {
+            self.operand(expr, true);
+            return;
+        }
+        match &expr.kind {
+            ExprKind::Identifier(name) => self.write(name),
+            ExprKind::Literal(Literal::Int(n)) => self.write(&n.to_string()),
+            ExprKind::Literal(Literal::Float(f)) => self.write(&format!("{:?}", f)),
+            ExprKind::Literal(Literal::Str(s)) => self.write(&format!("\"{}\"", escape(s))),
+            ExprKind::Literal(Literal::Bool(b)) => self.write(&b.to_string()),
+            // Sol birleşimli: sağdaki aynı öncelikli işlem parantez ister (a - (b - c))
+            // Karşılaştırmalar zincirlenemediği için soldaki de ister ((a < b) == c)
+            ExprKind::Binary(l, op, r) => {
+                let p = op.precedence();
+                self.condition = head;
+                self.operand(l, prec(l) < p || (op.is_comparison() && prec(l) == p));
+                self.write(&format!(" {} ", op.symbol()));
+                self.condition = head;
+                self.operand(r, prec(r) <= p);
+            }
+            // -(5) parantezsiz yazılırsa -5 literali olarak okunur
+            ExprKind::Unary(op, e) => {
+                self.write(op.symbol());
+                self.condition = head;
+                self.operand(e, prec(e) < PREC_UNARY || matches!(e.kind, ExprKind::Literal(Literal::Int(_) | Literal::Float(_))));
+            }
+            ExprKind::Call(name, args) => {
+                // `call` öneki AST'de tutulmaz; yazıldığı gibi korunur
+                let text = &self.source[expr.span.start..expr.span.end];
+                if text.strip_prefix("call").is_some_and(|rest| rest.starts_with(char::is_whitespace)) {
+                    self.write("call ");
+                }
+                self.write(name);
+                self.args(expr.span.start, expr.span.end, args);
+            }
+            ExprKind::Lambda(params, body) => {
+                let params: Vec<String> = params.iter().map(|(name, ty)| match ty {
+                    Some(t) => format!("{}: {}", name, t),
+                    None => name.clone(),
+                }).collect();
+                self.write(&format!("|{}| ", params.join(", ")));
+                self.condition = head;
+                self.expr(body);
+            }
+            ExprKind::Spawn(e) => {
+                self.write("spawn ");
+                self.condition = head;
+                self.expr(e);
+            }
+            ExprKind::Await(e) => {
+                self.write("await ");
+                self.condition = head;
+                self.expr(e);
+            }
+            ExprKind::Infra(call) => {
+                self.write(&format!("call {}.{}(", call.service, call.method));
+                self.exprs(&call.args);
+                self.write(") ");
+                self.infra_config(&call.config);
+            }
+            ExprKind::JsonField(e, key) => {
+                self.write("json(");
+                self.expr(e);
+                self.write(&format!(", \"{}\")", escape(key)));
+            }
+            ExprKind::ArrayLiteral(items) if self.has_comments_in(expr.span) => {
+                self.write("[");
+                self.lines(expr.span, items, |e| e.span, |f, e| f.expr(e));
+                self.write("]");
+            }
+            ExprKind::ArrayLiteral(items) => {
+                self.write("[");
+                self.exprs(items);
+                self.write("]");
+            }
+            ExprKind::Index(e, idx) => {
+                self.condition = head;
+                self.operand(e, prec(e) < PREC_ATOM);
+                self.write("[");
+                self.expr(idx);
+                self.write("]");
+            }
+            ExprKind::Slice(e, from, to) => {
+                self.condition = head;
+                self.operand(e, prec(e) < PREC_ATOM);
+                self.write("[");
+                self.expr(from);
+                self.write("..");
+                self.expr(to);
+                self.write("]");
+            }
+            ExprKind::MethodCall(e, name, args) => {
+                self.condition = head;
+                self.operand(e, prec(e) < PREC_ATOM);
+                self.write(&format!(".{}", name));
+                self.args(e.span.end, expr.span.end, args);
+            }
+            ExprKind::Interpolated(parts) => {
+                self.write("\"");
+                for part in parts {
+                    match part {
+                        StrPart::Text(text) => self.write(&escape(text)),
+                        StrPart::Expr(e) => {
+                            self.write("${");
+                            self.expr(e);
+                            self.write("}");
+                        }
+                    }
+                }
+                self.write("\"");
+            }
+            ExprKind::Field(e, name) => {
+                self.condition = head;
+                self.operand(e, prec(e) < PREC_ATOM);
+                self.write(&format!(".{}", name));
+            }
+            ExprKind::StructLiteral(name, fields) if fields.is_empty() => self.write(&format!("{} {{}}", name)),
+            ExprKind::StructLiteral(name, fields) => {
+                self.write(&format!("{} {{ ", name));
+                for (i, (field, value)) in fields.iter().enumerate() {
+                    if i > 0 { self.write(", "); }
+                    self.write(&format!("{}: ", field));
+                    self.expr(value);
+                }
+                self.write(" }");
+            }
+            ExprKind::MapLiteral(entries) if self.has_comments_in(expr.span) => {
+                self.write("{");
+                self.lines(expr.span, entries, |(k, v)| Span::new(k.span.start, v.span.end), |f, (k, v)| {
+                    f.expr(k);
+                    f.write(": ");
+                    f.expr(v);
+                });
+                self.write("}");
+            }
+            ExprKind::MapLiteral(entries) if entries.is_empty() => self.write("{}"),
+            ExprKind::MapLiteral(entries) => {
+                self.write("{ ");
+                for (i, (key, value)) in entries.iter().enumerate() {
+                    if i > 0 { self.write(", "); }
+                    self.expr(key);
+                    self.write(": ");
+                    self.expr(value);
+                }
+                self.write(" }");
+            }
+            ExprKind::Ctor(c, None) => self.write(c.name()),
+            ExprKind::Ctor(c, Some(e)) => {
+                self.write(&format!("{}(", c.name()));
+                self.expr(e);
+                self.write(")");
+            }
+            ExprKind::Match(scrutinee, arms) => {
+                self.write("match ");
+                self.condition(scrutinee);
+                self.write(" {");
+                self.indent += 1;
+                for arm in arms {
+                    self.comments_before(arm.span.start);
+                    self.line();
+                    self.write(&format!("{} => ", pattern(&arm.pattern)));
+                    match &arm.body {
+                        ArmBody::Expr(e) => {
+                            self.expr(e);
+                            self.write(",");
+                        }
+                        ArmBody::Block(b) => self.block(b),
+                    }
+                    self.last_end = self.last_end.max(arm.span.end);
+                }
+                self.indent -= 1;
+                self.line();
+                self.write("}");
+            }
+        }
+    }
+
+    // Varsayılan değerler (retry: 0, backoff: fixed(100ms)) yazılmaz
+    fn infra_config(&mut self, config: &InfraConfig) {
+        let mut entries = Vec::new();
+        if let Some(ms) = config.timeout_ms { entries.push(format!("timeout: {}", duration(ms))); }
+        if config.retry != 0 { entries.push(format!("retry: {}", config.retry)); }
+        match config.backoff {
+            Backoff::Fixed(InfraConfig::DEFAULT_BACKOFF_MS) => {}
+            Backoff::Fixed(ms) => entries.push(format!("backoff: fixed({})", duration(ms))),
+            Backoff::Exponential(InfraConfig::DEFAULT_BACKOFF_MS) => entries.push("backoff: exponential".to_string()),
+            Backoff::Exponential(ms) => entries.push(format!("backoff: exponential({})", duration(ms))),
+        }
+        let Some(fallback) = &config.fallback else {
+            // Tek giriş aynı satırda kalır
+            match entries.as_slice() {
+                [] => self.write("{}"),
+                [entry] => self.write(&format!("{{ {} }}", entry)),
+                _ => self.config_lines(&entries, None),
+            }
+            return;
+        };
+        if entries.is_empty() {
+            self.write("{ fallback: ");
+            self.expr(fallback);
+            self.write(" }");
+        } else {
+            self.config_lines(&entries, Some(fallback));
+        }
+    }
+
+    fn config_lines(&mut self, entries: &[String], fallback: Option<&Expr>) {
+        self.write("{");
+        self.indent += 1;
+        for (i, entry) in entries.iter().enumerate() {
+            self.line();
+            self.write(entry);
+            if i + 1 < entries.len() || fallback.is_some() { self.write(","); }
+        }
+        if let Some(fallback) = fallback {
+            self.line();
+            self.write("fallback: ");
+            self.expr(fallback);
+        }
+        self.indent -= 1;
+        self.line();
+        self.write("}");
+    }
+}
+
+fn constraints(constraints: &[Constraint]) -> String {
+    constraints.iter().map(|c| format!(" {}", c)).collect()
+}
+
+fn pattern(pattern: &Pattern) -> String {
+    match pattern {
+        Pattern::Wildcard => "_".to_string(),
+        Pattern::Ctor(c, _) if !c.has_payload() => c.name().to_string(),
+        Pattern::Ctor(c, binding) => format!("{}({})", c.name(), binding.as_deref().unwrap_or("_")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use regex::Regex;
+
+    // Ifade kimlikleri ve span'ler hariç AST yapısı
+    fn shape(source: &str) -> String {
+        let program = driver::parse(source).expect("parse");
+        let debug = format!("{:?}", program.items);
+        let ids = Regex::new(r"id: \d+, ").unwrap();
+        let spans = Regex::new(r"(name_)?span: Span \{ start: \d+, end: \d+ \}").unwrap();
+        spans.replace_all(&ids.replace_all(&debug, ""), "").into_owned()
+    }
+
+    fn assert_round_trip(source: &str) -> String {
+        let formatted = format_source(source).expect("format");
+        assert_eq!(shape(&formatted), shape(source), "AST değişti:\n{}", formatted);
+        assert_eq!(format_source(&formatted).expect("format"), formatted, "idempotent değil");
+        assert_eq!(parser::comments(&formatted).len(), parser::comments(source).len(), "yorum kayboldu:\n{}", formatted);
+        formatted
+    }
+
+    const SAMPLES: &[&str] = &[
+        include_str!("../run.gj"),
+        r#"
+use payments.fees
+import "lib/util.gj" // yardımcılar
+
+// Kayıtlar
+struct User { id: i64, name: String }
+schema Payment {
+    amount: i64 range(1, 10000), // tutar
+    currency: String len(3, 3) regex("^[A-Z]+$")
+}
+
+deterministic fn calc(a: i64, b: i64) -> i64 {
+    let x = (a - (b - 1)) * (a + b) / 2
+    let y = a - b - 1
+    if x > y { return x } else if x == y { return 0 } else {
+        return y
+    }
+}
+
+deterministic wrapping fn mix(h: i64) -> i64 { return h * 31 + 7 }
+
+deterministic fn scale(r: u8, f: f64, on: bool) -> f64 { if on && r > 128 { return f * -(0.5) } return 1.5e-3 }
+
+nondeterministic fn main(raw: Untrusted) -> Void {
+    // doğrulama
+    let p = validate raw { schema: Payment, on_fail: return }
+    validate raw {
+        schema: Payment,
+        on_fail: {
+            DB.log("gecersiz");
+            return
+        },
+        success: { DB.log("ok: " + p.amount) }
+    }
+    let r: Result<i64, String> = Ok(1)
+    let v = match r {
+        Ok(n) => n * 2, // çift
+        Err(_) => { return }
+    }
+    let arr = [1, 2, 3]
+    for i in 0..3 by 1 { DB.log(arr[i] + v) }
+    let first = arr.get(0)
+    let ages: Map<String, i64> = { "ali": 30, "veli": arr[1] }
+    ages.insert("ayse", 25)
+    for (name, age) in ages { DB.log("${name}: ${age}") }
+    arr.push(len(arr))
+    for n in arr[1..len(arr) - 1] { DB.log(n) }
+    for i, n in arr { if arr.contains(n) { arr.sort() } }
+    let twice: fn(i64) -> i64 = |x| x * 2
+    let big = arr.map(twice).filter(|x: i64| x > v && x != 3)
+    let sum = big.fold(0, |acc, x| acc + calc(x, 1))
+    let same = (|x: i64| x) == calc
+    let none = match ages["can"] {
+        Some(_) => {},
+        None => { "can": 0 },
+    }
+    DB.log("calc(${v}) = ${calc(v, 1)}\t\"x\" \${raw} \\ \u{7}")
+    while v < 10 { v = v + 1 }
+    let body = call HTTP.get("http://x") { timeout: 2s, retry: 3, backoff: exponential, fallback: "{}" }
+    let t = call Util.now() { timeout: 250ms }
+    let k = json(body, "k")
+    let u = User { id: calc(1, 2), name: "gojo" }
+    scope S {
+        let h = spawn calc(1, 2)
+        DB.log((await h) + 1)
+        await all
+    }
+} // son
+"#,
+    ];
+
+    #[test]
+    fn round_trip_preserves_ast_and_comments() {
+        for source in SAMPLES {
+            assert_round_trip(source);
+        }
+    }
+
+    #[test]
+    fn canonical_layout() {
+        let source = "deterministic fn f(n: i64) -> i64 {   \n  let a = n+1   // artır\n\n\n\n  return a*2\n}\nnondeterministic fn main(x: Untrusted) -> Void { DB.log(f(1)); }";
+        let expected = "deterministic fn f(n: i64) -> i64 {\n    let a = n + 1 // artır\n\n    return a * 2\n}\n\nnondeterministic fn main(x: Untrusted) {\n    DB.log(f(1))\n}\n";
+        assert_eq!(assert_round_trip(source), expected);
+    }
+
+    #[test]
+    fn keeps_required_parentheses() {
+        let formatted = assert_round_trip("deterministic fn f(a: i64, b: i64) -> i64 { return (a - (b - 1)) * (a + b) }");
+        assert!(formatted.contains("return (a - (b - 1)) * (a + b)"), "{}", formatted);
+        let formatted = assert_round_trip("deterministic fn g(a: i64) -> i64 { if (a<1)==(a>2) || !(a==0 && a%2!=1) { return -(1) - -a } return 0 }");
+        assert!(formatted.contains("if (a < 1) == (a > 2) || !(a == 0 && a % 2 != 1) {"), "{}", formatted);
+        assert!(formatted.contains("return -(1) - -a"), "{}", formatted);
+    }
+
+    #[test]
+    fn rejects_unparsed_input() {
+        assert!(format_source("deterministic fn f() { }\nfn oops {").is_err());
+    }
+
+
+    #[test]
+    fn trailing_comments_stay_on_their_line() {
+        let source = "use a.b // modül\ndeterministic fn f(n: i64) -> i64 {\n    return n\n} // fn end\nstruct User { id: i64 } // x\n\
+                      deterministic fn g(n: i64) -> i64 {\n    if n > 1 { return n } // c\n    else { return 0 }\n}\n\
+                      deterministic fn h() -> i64 {\n    let a = [\n        1, // bir\n        2\n    ]\n    let m: Map<String, i64> = { // boş\n    }\n    return a[0]\n}\n";
+        let expected = "use a.b // modül\n\n\
+                        deterministic fn f(n: i64) -> i64 {\n    return n\n} // fn end\n\n\
+                        struct User {\n    id: i64\n} // x\n\n\
+                        deterministic fn g(n: i64) -> i64 {\n    if n > 1 {\n        return n\n    } // c\n    else {\n        return 0\n    }\n}\n\n\
+                        deterministic fn h() -> i64 {\n    let a = [\n        1, // bir\n        2\n    ]\n    let m: Map<String, i64> = { // boş\n    }\n    return a[0]\n}\n";
+        assert_eq!(assert_round_trip(source), expected);
+    }
+
+    #[test]
+    fn comments_inside_literals_keep_the_literal_multiline() {
+        let formatted = assert_round_trip("deterministic fn f() -> i64 {\n    let m: Map<String, i64> = {\n        // yaşlar\n        \"ali\": 30, \"veli\": 40, // iki\n    }\n    DB.log(1)\n    return 0\n}\nstruct S { a: i64 }\n");
+        assert!(formatted.contains("= {\n        // yaşlar\n        \"ali\": 30,\n        \"veli\": 40 // iki\n    }\n"), "{}", formatted);
+        // Yorumsuz literal tek satırda kalır
+        let formatted = assert_round_trip("deterministic fn f() -> i64 {\n    let a = [\n        1,\n        2\n    ]\n    return a[0]\n}\n");
+        assert!(formatted.contains("let a = [1, 2]\n"), "{}", formatted);
+    }
+
+
+    #[test]
+    fn comments_inside_arguments_keep_the_call_multiline() {
+        let source = "deterministic fn f2(n: i64) -> i64 { return n }\ndeterministic fn f(n: i64, xs: Array<i64>) -> i64 {\n    let a = f2(\n        // arg comment\n        n\n    )\n    let b = xs.get(\n        0 // ilk\n    )\n    return a + f2(n)\n}\n";
+        let formatted = assert_round_trip(source);
+        assert!(formatted.contains("let a = f2(\n        // arg comment\n        n\n    )\n"), "{}", formatted);
+        assert!(formatted.contains("let b = xs.get(\n        0 // ilk\n    )\n"), "{}", formatted);
+        assert!(formatted.contains("return a + f2(n)\n"), "{}", formatted);
+    }
+
+
+    #[test]
+    fn struct_literals_in_conditions_keep_their_parentheses() {
+        let formatted = assert_round_trip("struct Limit { n: i64 }\ndeterministic fn f(n: i64) -> i64 {\n    if n < (Limit { n: 3 }).n { return 1 }\n    while (Limit { n: 1 }) == (Limit { n: n }) { return 2 }\n    return len([Limit { n: n }])\n}\n");
+        assert!(formatted.contains("if n < (Limit { n: 3 }).n {"), "{}", formatted);
+        assert!(formatted.contains("while (Limit { n: 1 }) == (Limit { n: n }) {"), "{}", formatted);
+        assert!(formatted.contains("return len([Limit { n: n }])"), "{}", formatted);
+    }
+}
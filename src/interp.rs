@@ -0,0 +1,1339 @@
+use crate::analysis::determinism::SymbolTable;
+use crate::analysis::types::TypeTable;
+use crate::ast::*;
+use crate::builtins;
+use crate::diagnostics::SourceMap;
+use gojo_runtime::color::{RED, RESET};
+use gojo_runtime::{array, ops};
+use gojo_runtime::{block_on, infra_call, json_field, runtime_error, validate_schema, Console, FieldKind, FieldRule, FieldSpec, GojoAdd, Site, Task, TaskScope, Util, Validate, DB, HTTP};
+use std::collections::HashSet;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+// Ağaç yürüten yorumlayıcı (gojo run --interp)
+// Analizden geçmiş programı rustc'ye gitmeden çalıştırır. Servisler, schema doğrulama,
+// altyapı çağrıları ve task'lar derlenmiş programın kullandığı gojo-runtime fonksiyonlarına
+// gider; böylece iki mod aynı çıktıyı üretir. Deterministic fonksiyonlar derlenmiş halde
+// olduğu gibi senkron çalışır, async yol yalnızca spawn/await/altyapı çağrısı içeren
+// ifadelerde kullanılır.
+
+#[derive(Clone)]
+pub enum Value {
+    Void,
+    Int(i64),
+    Float(f64),
+    Byte(u8),
+    Str(String),
+    Bool(bool),
+    Array(Vec<Value>),
+    // Girdiler anahtara göre sıralı tutulur (derlenmiş BTreeMap gibi)
+    Map(Vec<(Value, Value)>),
+    // Alanlar tanım sırasında tutulur (JSON çıktısı derlenmiş struct'la aynı olsun)
+    Record(String, Vec<(String, Value)>),
+    Ctor(Ctor, Option<Box<Value>>),
+    Task(Task<Value>),
+    Func(Arc<Func>),
+}
+
+// Fonksiyon değeri: değer olarak geçilen fonksiyon ya da yakaladığı değişkenlerin kopyasıyla bir closure
+pub enum Func {
+    Named(String),
+    Closure { params: Vec<String>, body: Expr, captured: Vec<(String, Value)>, overflow: ops::Overflow },
+}
+
+impl Value {
+    // Derlenmiş struct'ların serde çıktısıyla aynı biçim
+    fn to_json(&self) -> String {
+        match self {
+            Value::Int(n) => n.to_string(),
+            Value::Float(f) => serde_json::to_string(f).unwrap_or_default(),
+            Value::Byte(n) => n.to_string(),
+            Value::Str(s) => serde_json::to_string(s).unwrap_or_default(),
+            Value::Bool(b) => b.to_string(),
+            Value::Array(items) => format!("[{}]", items.iter().map(Value::to_json).collect::<Vec<_>>().join(",")),
+            // serde_json map anahtarlarını metne çevirir
+            Value::Map(entries) => {
+                let entries: Vec<String> = entries.iter().map(|(k, v)| match k {
+                    Value::Str(s) => format!("{}:{}", serde_json::to_string(s).unwrap_or_default(), v.to_json()),
+                    k => format!("\"{}\":{}", k, v.to_json()),
+                }).collect();
+                format!("{{{}}}", entries.join(","))
+            }
+            Value::Record(_, fields) => {
+                let fields: Vec<String> = fields.iter().map(|(k, v)| format!("{}:{}", serde_json::to_string(k).unwrap_or_default(), v.to_json())).collect();
+                format!("{{{}}}", fields.join(","))
+            }
+            Value::Ctor(Ctor::Some, Some(v)) => v.to_json(),
+            Value::Ctor(c, Some(v)) => format!("{{\"{}\":{}}}", c.name(), v.to_json()),
+            Value::Void | Value::Ctor(_, None) | Value::Task(_) | Value::Func(_) => "null".to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Void => write!(f, "()"),
+            Value::Int(n) => write!(f, "{}", n),
+            Value::Float(n) => write!(f, "{}", n),
+            Value::Byte(n) => write!(f, "{}", n),
+            Value::Str(s) => write!(f, "{}", s),
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::Array(items) => write!(f, "[{}]", items.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(", ")),
+            Value::Map(entries) => write!(f, "{{{}}}", entries.iter().map(|(k, v)| format!("{}: {}", k, v)).collect::<Vec<_>>().join(", ")),
+            Value::Record(..) => write!(f, "{}", self.to_json()),
+            Value::Ctor(c, Some(v)) => write!(f, "{}({})", c.name(), v),
+            Value::Ctor(c, None) => write!(f, "{}", c.name()),
+            Value::Task(_) => write!(f, "<task>"),
+            Value::Func(_) => write!(f, "<fn>"),
+        }
+    }
+}
+
+// REPL gösterimi: metinler tırnaklı
+impl fmt::Debug for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Str(s) => write!(f, "{:?}", s),
+            Value::Array(items) => f.debug_list().entries(items).finish(),
+            Value::Map(entries) => f.debug_map().entries(entries.iter().map(|(k, v)| (k, v))).finish(),
+            Value::Record(name, fields) => {
+                let mut s = f.debug_struct(name);
+                for (k, v) in fields { s.field(k, v); }
+                s.finish()
+            }
+            Value::Ctor(c, Some(v)) => write!(f, "{}({:?})", c.name(), v),
+            other => write!(f, "{}", other),
+        }
+    }
+}
+
+impl PartialEq for Value {
+    fn eq(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Value::Void, Value::Void) => true,
+            (Value::Int(a), Value::Int(b)) => a == b,
+            (Value::Float(a), Value::Float(b)) => a == b,
+            (Value::Byte(a), Value::Byte(b)) => a == b,
+            (Value::Str(a), Value::Str(b)) => a == b,
+            (Value::Bool(a), Value::Bool(b)) => a == b,
+            (Value::Array(a), Value::Array(b)) => a == b,
+            (Value::Map(a), Value::Map(b)) => a == b,
+            (Value::Record(n1, f1), Value::Record(n2, f2)) => n1 == n2 && f1 == f2,
+            (Value::Ctor(c1, p1), Value::Ctor(c2, p2)) => c1 == c2 && p1 == p2,
+            _ => false,
+        }
+    }
+}
+
+// Normal akışın dışına çıkış: return ya da çalışma zamanı hatası
+pub enum Unwind {
+    Return(Value),
+    Error(String),
+}
+
+type Exec<T> = Result<T, Unwind>;
+type Eval<'a, T> = Pin<Box<dyn Future<Output = Exec<T>> + Send + 'a>>;
+
+fn fail<T>(msg: impl Into<String>) -> Exec<T> { Err(Unwind::Error(msg.into())) }
+
+// Değişken yığını: her blok girişinde işaret konur, çıkışta o noktaya kadar kesilir.
+// Aynı isim tekrar tanımlanırsa (gölgeleme) en son tanım bulunur.
+#[derive(Default)]
+struct Env {
+    vars: Vec<(String, Value)>,
+    marks: Vec<usize>,
+    // İç içe `scope` blokları (en içteki sonda)
+    scopes: Vec<TaskScope>,
+    // Çalışan fonksiyonun i64 taşma davranışı (`wrapping fn`)
+    overflow: ops::Overflow,
+}
+
+impl Env {
+    fn with(vars: Vec<(String, Value)>, overflow: ops::Overflow) -> Self { Self { vars, marks: Vec::new(), scopes: Vec::new(), overflow } }
+
+    fn enter(&mut self) { self.marks.push(self.vars.len()); }
+
+    fn leave(&mut self) {
+        if let Some(mark) = self.marks.pop() { self.vars.truncate(mark); }
+    }
+
+    fn get(&self, name: &str) -> Exec<Value> {
+        match self.vars.iter().rev().find(|(n, _)| n == name) {
+            Some((_, v)) => Ok(v.clone()),
+            None => fail(format!("undefined variable `{}`", name)),
+        }
+    }
+
+    fn define(&mut self, name: &str, value: Value) { self.vars.push((name.to_string(), value)); }
+
+    fn assign(&mut self, name: &str, value: Value) -> Exec<()> {
+        match self.vars.iter_mut().rev().find(|(n, _)| n == name) {
+            Some((_, slot)) => { *slot = value; Ok(()) }
+            None => fail(format!("undefined variable `{}`", name)),
+        }
+    }
+
+    // spawn'a verilen kopya: görünen tüm değişkenler
+    fn snapshot(&self) -> Vec<(String, Value)> { self.vars.clone() }
+
+    // Closure'a verilen kopya: sadece gövdede geçen isimlerin görünen (en son) tanımları
+    fn capture(&self, names: &HashSet<String>) -> Vec<(String, Value)> {
+        let mut seen = HashSet::new();
+        let mut vars: Vec<(String, Value)> = self.vars.iter().rev().filter(|(n, _)| names.contains(n) && seen.insert(n.as_str())).cloned().collect();
+        vars.reverse();
+        vars
+    }
+}
+
+#[derive(Clone)]
+pub struct Interpreter {
+    symbols: Arc<SymbolTable>,
+    // spawn/await/altyapı çağrısı/nondeterministic çağrı içeren ifadeler
+    async_exprs: Arc<HashSet<ExprId>>,
+    // Aritmetik hatalarına eklenen konum (REPL'de girdiler farklı kaynaklardan geldiği için yok)
+    source: Option<Arc<SourceMap>>,
+    // Tip denetimine göre u8 olan ifadeler: bunlar arasındaki tamsayı literal'leri byte olarak değerlendirilir
+    bytes: Arc<HashSet<ExprId>>,
+}
+
+// Programı çok thread'li tokio runtime'ında çalıştırır (derlenmiş #[tokio::main] gibi)
+pub fn run(program: &Program, types: &TypeTable, source: &SourceMap) -> Result<(), String> {
+    let runtime = tokio::runtime::Runtime::new().map_err(|e| format!("tokio runtime: {}", e))?;
+    let mut interp = Interpreter::new(program, types);
+    interp.source = Some(Arc::new(source.clone()));
+    runtime.block_on(interp.run_main())
+}
+
+// REPL oturumu: değişkenler girdiler arasında korunur, tanımlar her girdide yeniden yüklenir
+pub struct Session {
+    env: Env,
+    runtime: tokio::runtime::Runtime,
+}
+
+impl Session {
+    pub fn new() -> Result<Self, String> {
+        let runtime = tokio::runtime::Runtime::new().map_err(|e| format!("tokio runtime: {}", e))?;
+        Ok(Self { env: Env::default(), runtime })
+    }
+
+    pub fn get(&self, name: &str) -> Option<Value> { self.env.get(name).ok() }
+
+    // Deyimi çalıştırır; bir ifadeyse değerini döndürür
+    pub fn exec(&mut self, program: &Program, types: &TypeTable, stmt: &Statement) -> Result<Option<Value>, String> {
+        let interp = Interpreter::build(program, types, Some(stmt));
+        let env = &mut self.env;
+        let result = self.runtime.block_on(async {
+            match &stmt.kind {
+                StmtKind::ExprStmt(e) => interp.eval(env, e).await.map(Some),
+                _ => interp.exec(env, stmt).await.map(|_| None),
+            }
+        });
+        match result {
+            Ok(value) => Ok(value),
+            Err(Unwind::Return(_)) => Err("`return` outside of a function".to_string()),
+            Err(Unwind::Error(msg)) => Err(msg),
+        }
+    }
+}
+
+impl Interpreter {
+    pub fn new(program: &Program, types: &TypeTable) -> Self { Self::build(program, types, None) }
+
+    // `extra`: programa ait olmayan (REPL) deyimi de async işaretlemesine dahil edilir
+    fn build(program: &Program, types: &TypeTable, extra: Option<&Statement>) -> Self {
+        let symbols = SymbolTable::from_program(program);
+        let mut async_exprs = HashSet::new();
+        for func in program.functions() {
+            mark_block(&func.body, &symbols, &mut async_exprs);
+        }
+        if let Some(stmt) = extra { mark_stmt(stmt, &symbols, &mut async_exprs); }
+        Self { symbols: Arc::new(symbols), async_exprs: Arc::new(async_exprs), source: None, bytes: Arc::new(types.with_type(&TypeRef::Byte).collect()) }
+    }
+
+    // main derlenmiş shim'deki gibi "Internet" argümanıyla çağrılır
+    pub async fn run_main(&self) -> Result<(), String> {
+        let Some(main) = self.symbols.functions.get("main") else { return Err("`main` fonksiyonu bulunamadı".to_string()) };
+        let args = main.params.iter().map(|_| Value::Str("Internet".to_string())).collect();
+        match self.call("main", args).await {
+            Ok(_) | Err(Unwind::Return(_)) => Ok(()),
+            Err(Unwind::Error(msg)) => Err(msg),
+        }
+    }
+
+    fn bind_params(func: &FunctionDef, args: Vec<Value>) -> Exec<Env> {
+        if func.params.len() != args.len() {
+            return fail(format!("`{}` expects {} arguments, got {}", func.name, func.params.len(), args.len()));
+        }
+        let overflow = match func.overflow { Overflow::Checked => ops::Overflow::Checked, Overflow::Wrapping => ops::Overflow::Wrapping };
+        Ok(Env::with(func.params.iter().map(|p| p.name.clone()).zip(args).collect(), overflow))
+    }
+
+    // Derlenmiş koddaki Site gibi: aritmetik hatası işlemin kaynak konumunu söyler
+    fn located<T>(&self, span: Span, result: Exec<T>) -> Exec<T> {
+        match (result, &self.source) {
+            (Err(Unwind::Error(msg)), Some(source)) => fail(format!("{} at {}", msg, source.locate(span))),
+            (result, _) => result,
+        }
+    }
+
+    async fn call(&self, name: &str, args: Vec<Value>) -> Exec<Value> {
+        if let Some(func) = self.symbols.functions.get(name) {
+            return match func.purity {
+                Purity::Deterministic => self.call_function_sync(func, args),
+                Purity::Nondeterministic => self.call_function(func, args).await,
+            };
+        }
+        let Some(builtin) = builtins::lookup(name) else { return fail(format!("unknown function `{}`", name)) };
+        if builtin.purity == Purity::Deterministic { return call_pure_builtin(name, args); }
+        let out = call_service(name, args).await;
+        if builtin.fallible { Ok(wrap_result(out)) } else { out.map_err(Unwind::Error) }
+    }
+
+    async fn call_function(&self, func: &FunctionDef, args: Vec<Value>) -> Exec<Value> {
+        let mut env = Self::bind_params(func, args)?;
+        match self.exec_block(&mut env, &func.body).await {
+            Ok(()) => Ok(Value::Void),
+            Err(Unwind::Return(v)) => Ok(v),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn call_function_sync(&self, func: &FunctionDef, args: Vec<Value>) -> Exec<Value> {
+        let mut env = Self::bind_params(func, args)?;
+        match self.exec_block_sync(&mut env, &func.body) {
+            Ok(()) => Ok(Value::Void),
+            Err(Unwind::Return(v)) => Ok(v),
+            Err(e) => Err(e),
+        }
+    }
+
+    // Kullanıcı fonksiyonu ya da builtin değilse `f(x)` bir fonksiyon değeri çağrısıdır
+    fn is_value_call(&self, name: &str) -> bool {
+        !self.symbols.functions.contains_key(name) && builtins::lookup(name).is_none()
+    }
+
+    // Fonksiyon değerleri her zaman senkron çalışır (derlenmiş Arc<dyn Fn> gibi)
+    fn apply(&self, f: &Value, args: Vec<Value>) -> Exec<Value> {
+        let Value::Func(f) = f else { return fail(format!("cannot call {}", f)) };
+        match &**f {
+            Func::Named(name) => self.call_sync(name, args),
+            Func::Closure { params, body, captured, overflow } => {
+                if params.len() != args.len() {
+                    return fail(format!("closure expects {} arguments, got {}", params.len(), args.len()));
+                }
+                let mut vars = captured.clone();
+                vars.extend(params.iter().cloned().zip(args));
+                self.eval_sync(&mut Env::with(vars, *overflow), body)
+            }
+        }
+    }
+
+    // map/filter/fold: fonksiyon elemanlara sırayla uygulanır (derlenmiş GojoArray gibi)
+    fn combinator(&self, target: Value, name: &str, mut args: Vec<Value>) -> Exec<Value> {
+        let (Value::Array(items), Some(f)) = (target, args.pop()) else { return fail(format!("`{}` expects an array and a function", name)) };
+        match (name, args.pop()) {
+            ("map", None) => items.into_iter().map(|x| self.apply(&f, vec![x])).collect::<Exec<Vec<_>>>().map(Value::Array),
+            ("filter", None) => {
+                let mut kept = Vec::new();
+                for x in items {
+                    if truthy(self.apply(&f, vec![x.clone()])?)? { kept.push(x); }
+                }
+                Ok(Value::Array(kept))
+            }
+            ("fold", Some(init)) => items.into_iter().try_fold(init, |acc, x| self.apply(&f, vec![acc, x])),
+            (name, _) => fail(format!("no method `{}` on arrays", name)),
+        }
+    }
+
+    fn call_sync(&self, name: &str, args: Vec<Value>) -> Exec<Value> {
+        match self.symbols.functions.get(name) {
+            Some(func) if func.purity == Purity::Deterministic => self.call_function_sync(func, args),
+            // Saf olmayan closure'ın çağrısı: async çağrı yerinde beklenir
+            Some(_) => block_on(self.call(name, args)),
+            None if builtins::lookup(name).is_some_and(|b| b.purity != Purity::Deterministic) => block_on(self.call(name, args)),
+            None => call_pure_builtin(name, args),
+        }
+    }
+
+    // ---- senkron yol (deterministic kod) ----
+
+    fn exec_block_sync(&self, env: &mut Env, block: &Block) -> Exec<()> {
+        env.enter();
+        let result = block.statements.iter().try_for_each(|s| self.exec_sync(env, s));
+        env.leave();
+        result
+    }
+
+    fn exec_sync(&self, env: &mut Env, stmt: &Statement) -> Exec<()> {
+        match &stmt.kind {
+            StmtKind::Let(l) => {
+                let value = self.eval_sync(env, &l.value)?;
+                env.define(&l.name, value);
+            }
+            StmtKind::Assign { name, value } => {
+                let value = self.eval_sync(env, value)?;
+                env.assign(name, value)?;
+            }
+            StmtKind::ExprStmt(e) => { self.eval_sync(env, e)?; }
+            StmtKind::If { condition, then_block, else_block } => {
+                if truthy(self.eval_sync(env, condition)?)? {
+                    self.exec_block_sync(env, then_block)?;
+                } else if let Some(else_b) = else_block {
+                    self.exec_block_sync(env, else_b)?;
+                }
+            }
+            StmtKind::While { condition, body } => {
+                while truthy(self.eval_sync(env, condition)?)? {
+                    self.exec_block_sync(env, body)?;
+                }
+            }
+            StmtKind::For { var, start, end, step, body } => {
+                let start = int(self.eval_sync(env, start)?)?;
+                let end = int(self.eval_sync(env, end)?)?;
+                let step = match step { Some(s) => int(self.eval_sync(env, s)?)?, None => 1 };
+                env.enter();
+                env.define(var, Value::Int(start));
+                let result = (|| {
+                    while for_continues(int(env.get(var)?)?, end, step) {
+                        self.exec_block_sync(env, body)?;
+                        let next = self.located(stmt.span, arith(ops::add(int(env.get(var)?)?, step, env.overflow)))?;
+                        env.assign(var, next)?;
+                    }
+                    Ok(())
+                })();
+                env.leave();
+                result?;
+            }
+            // Döngü, başladığı andaki elemanlar üzerinde döner (map'te anahtar sırasıyla)
+            StmtKind::ForIn { binding, iterable, body } => {
+                for values in entries(self.eval_sync(env, iterable)?)? {
+                    env.enter();
+                    bind(env, binding, values);
+                    let result = self.exec_block_sync(env, body);
+                    env.leave();
+                    result?;
+                }
+            }
+            StmtKind::ScopeBlock { .. } | StmtKind::AwaitAll => return fail("`scope` in a deterministic function"),
+            StmtKind::ValidateBlock { target, schema, on_fail, success_scope } => match self.validate(env, target, schema)? {
+                Ok(value) => {
+                    env.enter();
+                    env.define(target, value);
+                    let result = self.exec_block_sync(env, success_scope);
+                    env.leave();
+                    result?;
+                }
+                Err(e) => match on_fail {
+                    Some(b) => self.exec_block_sync(env, b)?,
+                    None => report_validate_error(target, &e),
+                },
+            },
+            StmtKind::ValidateLet { name, target, schema, on_fail } => match self.validate(env, target, schema)? {
+                Ok(value) => env.define(name, value),
+                Err(_) => {
+                    self.exec_block_sync(env, on_fail)?;
+                    return fail("`on_fail` must leave the function");
+                }
+            },
+            StmtKind::Return(value) => {
+                let value = match value { Some(e) => self.eval_sync(env, e)?, None => Value::Void };
+                return Err(Unwind::Return(value));
+            }
+        }
+        Ok(())
+    }
+
+    fn eval_sync(&self, env: &mut Env, expr: &Expr) -> Exec<Value> {
+        match &expr.kind {
+            // Değişken değilse değer olarak kullanılan fonksiyondur
+            ExprKind::Identifier(name) => match env.get(name) {
+                Err(_) if self.symbols.functions.contains_key(name) => Ok(Value::Func(Arc::new(Func::Named(name.clone())))),
+                result => result,
+            },
+            ExprKind::Literal(Literal::Int(n)) if !self.bytes.is_empty() && self.bytes.contains(&expr.id) => Ok(Value::Byte(*n as u8)),
+            ExprKind::Literal(Literal::Int(n)) => Ok(Value::Int(*n)),
+            ExprKind::Literal(Literal::Float(f)) => Ok(Value::Float(*f)),
+            ExprKind::Literal(Literal::Str(s)) => Ok(Value::Str(s.clone())),
+            ExprKind::Literal(Literal::Bool(b)) => Ok(Value::Bool(*b)),
+            ExprKind::Binary(l, op @ (BinaryOp::And | BinaryOp::Or), r) => {
+                let l = truthy(self.eval_sync(env, l)?)?;
+                if short_circuits(op, l) { return Ok(Value::Bool(l)); }
+                Ok(Value::Bool(truthy(self.eval_sync(env, r)?)?))
+            }
+            ExprKind::Binary(l, op, r) => {
+                let l = self.eval_sync(env, l)?;
+                let r = self.eval_sync(env, r)?;
+                self.located(expr.span, binary(op, l, r, env.overflow))
+            }
+            ExprKind::Unary(op, e) => {
+                let v = self.eval_sync(env, e)?;
+                self.located(expr.span, unary(*op, v, env.overflow))
+            }
+            ExprKind::Call(name, args) => {
+                let args = args.iter().map(|a| self.eval_sync(env, a)).collect::<Exec<Vec<_>>>()?;
+                if self.is_value_call(name) { return self.apply(&env.get(name)?, args); }
+                self.call_sync(name, args)
+            }
+            ExprKind::JsonField(source, key) => json(self.eval_sync(env, source)?, key),
+            ExprKind::ArrayLiteral(items) => Ok(Value::Array(items.iter().map(|e| self.eval_sync(env, e)).collect::<Exec<Vec<_>>>()?)),
+            ExprKind::MapLiteral(entries) => {
+                let mut map = Vec::with_capacity(entries.len());
+                for (k, v) in entries {
+                    let k = self.eval_sync(env, k)?;
+                    let v = self.eval_sync(env, v)?;
+                    insert(&mut map, k, v);
+                }
+                Ok(Value::Map(map))
+            }
+            ExprKind::Index(arr, idx) => {
+                let arr = self.eval_sync(env, arr)?;
+                let idx = self.eval_sync(env, idx)?;
+                self.located(expr.span, index(arr, idx))
+            }
+            ExprKind::Slice(arr, from, to) => {
+                let arr = self.eval_sync(env, arr)?;
+                let from = self.eval_sync(env, from)?;
+                let to = self.eval_sync(env, to)?;
+                self.located(expr.span, slice(arr, from, to))
+            }
+            ExprKind::MethodCall(target, name, args) if MUTATING_METHODS.contains(&name.as_str()) => {
+                let args = args.iter().map(|a| self.eval_sync(env, a)).collect::<Exec<Vec<_>>>()?;
+                mutate(env, target, name, args)
+            }
+            ExprKind::MethodCall(target, name, args) => {
+                let target = self.eval_sync(env, target)?;
+                let args = args.iter().map(|a| self.eval_sync(env, a)).collect::<Exec<Vec<_>>>()?;
+                if HIGHER_ORDER_METHODS.contains(&name.as_str()) { return self.combinator(target, name, args); }
+                method(target, name, args)
+            }
+            ExprKind::Interpolated(parts) => {
+                let mut out = String::new();
+                for part in parts {
+                    match part {
+                        StrPart::Text(s) => out.push_str(s),
+                        StrPart::Expr(e) => out.push_str(&self.eval_sync(env, e)?.to_string()),
+                    }
+                }
+                Ok(Value::Str(out))
+            }
+            ExprKind::Field(target, name) => field(self.eval_sync(env, target)?, name),
+            ExprKind::StructLiteral(name, fields) => {
+                let values = fields.iter().map(|(f, v)| Ok((f.clone(), self.eval_sync(env, v)?))).collect::<Exec<Vec<_>>>()?;
+                self.record(name, values)
+            }
+            ExprKind::Ctor(c, payload) => {
+                let payload = payload.as_ref().map(|p| self.eval_sync(env, p)).transpose()?;
+                Ok(Value::Ctor(*c, payload.map(Box::new)))
+            }
+            ExprKind::Match(scrutinee, arms) => {
+                let value = self.eval_sync(env, scrutinee)?;
+                let (arm, binding) = select_arm(arms, value)?;
+                env.enter();
+                if let Some((name, value)) = binding { env.define(&name, value); }
+                let result = match &arm.body {
+                    ArmBody::Expr(e) => self.eval_sync(env, e),
+                    ArmBody::Block(b) => self.exec_block_sync(env, b).map(|_| Value::Void),
+                };
+                env.leave();
+                result
+            }
+            ExprKind::Lambda(params, body) => {
+                let mut names = HashSet::new();
+                names_in_expr(body, &mut names);
+                Ok(Value::Func(Arc::new(Func::Closure {
+                    params: params.iter().map(|(p, _)| p.clone()).collect(),
+                    body: (**body).clone(),
+                    captured: env.capture(&names),
+                    overflow: env.overflow,
+                })))
+            }
+            ExprKind::Infra(call) => block_on(self.infra(env, call)),
+            ExprKind::Spawn(_) | ExprKind::Await(_) => fail("async expression in a synchronous context"),
+        }
+    }
+
+    // ---- async yol ----
+
+    fn exec_block<'a>(&'a self, env: &'a mut Env, block: &'a Block) -> Eval<'a, ()> {
+        Box::pin(async move {
+            env.enter();
+            let mut result = Ok(());
+            for stmt in &block.statements {
+                result = self.exec(env, stmt).await;
+                if result.is_err() { break; }
+            }
+            env.leave();
+            result
+        })
+    }
+
+    async fn exec(&self, env: &mut Env, stmt: &Statement) -> Exec<()> {
+        match &stmt.kind {
+            StmtKind::Let(l) => {
+                let value = self.eval(env, &l.value).await?;
+                env.define(&l.name, value);
+            }
+            StmtKind::Assign { name, value } => {
+                let value = self.eval(env, value).await?;
+                env.assign(name, value)?;
+            }
+            StmtKind::ExprStmt(e) => { self.eval(env, e).await?; }
+            StmtKind::If { condition, then_block, else_block } => {
+                if truthy(self.eval(env, condition).await?)? {
+                    self.exec_block(env, then_block).await?;
+                } else if let Some(else_b) = else_block {
+                    self.exec_block(env, else_b).await?;
+                }
+            }
+            StmtKind::While { condition, body } => {
+                while truthy(self.eval(env, condition).await?)? {
+                    self.exec_block(env, body).await?;
+                }
+            }
+            StmtKind::For { var, start, end, step, body } => {
+                let start = int(self.eval(env, start).await?)?;
+                let end = int(self.eval(env, end).await?)?;
+                let step = match step { Some(s) => int(self.eval(env, s).await?)?, None => 1 };
+                env.enter();
+                env.define(var, Value::Int(start));
+                let result = self.for_loop(env, stmt.span, var, end, step, body).await;
+                env.leave();
+                result?;
+            }
+            StmtKind::ForIn { binding, iterable, body } => {
+                for values in entries(self.eval(env, iterable).await?)? {
+                    env.enter();
+                    bind(env, binding, values);
+                    let result = self.exec_block(env, body).await;
+                    env.leave();
+                    result?;
+                }
+            }
+            // Normal çıkışta task'lar beklenir; return/hata ile çıkışta TaskScope düşer ve iptal eder
+            StmtKind::ScopeBlock { name, body } => {
+                env.scopes.push(TaskScope::new(name));
+                let result = self.exec_block(env, body).await;
+                let mut scope = env.scopes.pop().expect("scope stack");
+                result?;
+                scope.join_all().await;
+            }
+            StmtKind::AwaitAll => match env.scopes.last_mut() {
+                Some(scope) => scope.join_all().await,
+                None => return fail("`await all` outside of a `scope` block"),
+            },
+            StmtKind::ValidateBlock { target, schema, on_fail, success_scope } => match self.validate(env, target, schema)? {
+                Ok(value) => {
+                    env.enter();
+                    env.define(target, value);
+                    let result = self.exec_block(env, success_scope).await;
+                    env.leave();
+                    result?;
+                }
+                Err(e) => match on_fail {
+                    Some(b) => self.exec_block(env, b).await?,
+                    None => report_validate_error(target, &e),
+                },
+            },
+            StmtKind::ValidateLet { name, target, schema, on_fail } => match self.validate(env, target, schema)? {
+                Ok(value) => env.define(name, value),
+                Err(_) => {
+                    self.exec_block(env, on_fail).await?;
+                    return fail("`on_fail` must leave the function");
+                }
+            },
+            StmtKind::Return(value) => {
+                let value = match value { Some(e) => self.eval(env, e).await?, None => Value::Void };
+                return Err(Unwind::Return(value));
+            }
+        }
+        Ok(())
+    }
+
+    async fn for_loop(&self, env: &mut Env, span: Span, var: &str, end: i64, step: i64, body: &Block) -> Exec<()> {
+        while for_continues(int(env.get(var)?)?, end, step) {
+            self.exec_block(env, body).await?;
+            let next = self.located(span, arith(ops::add(int(env.get(var)?)?, step, env.overflow)))?;
+            env.assign(var, next)?;
+        }
+        Ok(())
+    }
+
+    fn eval<'a>(&'a self, env: &'a mut Env, expr: &'a Expr) -> Eval<'a, Value> {
+        if !self.async_exprs.contains(&expr.id) {
+            return Box::pin(std::future::ready(self.eval_sync(env, expr)));
+        }
+        Box::pin(async move {
+            match &expr.kind {
+                ExprKind::Binary(l, op @ (BinaryOp::And | BinaryOp::Or), r) => {
+                    let l = truthy(self.eval(env, l).await?)?;
+                    if short_circuits(op, l) { return Ok(Value::Bool(l)); }
+                    Ok(Value::Bool(truthy(self.eval(env, r).await?)?))
+                }
+                ExprKind::Binary(l, op, r) => {
+                    let l = self.eval(env, l).await?;
+                    let r = self.eval(env, r).await?;
+                    self.located(expr.span, binary(op, l, r, env.overflow))
+                }
+                ExprKind::Unary(op, e) => {
+                    let v = self.eval(env, e).await?;
+                    self.located(expr.span, unary(*op, v, env.overflow))
+                }
+                ExprKind::Call(name, args) => {
+                    let mut values = Vec::with_capacity(args.len());
+                    for a in args { values.push(self.eval(env, a).await?); }
+                    if self.is_value_call(name) { return self.apply(&env.get(name)?, values); }
+                    self.call(name, values).await
+                }
+                ExprKind::JsonField(source, key) => json(self.eval(env, source).await?, key),
+                ExprKind::ArrayLiteral(items) => {
+                    let mut values = Vec::with_capacity(items.len());
+                    for e in items { values.push(self.eval(env, e).await?); }
+                    Ok(Value::Array(values))
+                }
+                ExprKind::MapLiteral(entries) => {
+                    let mut map = Vec::with_capacity(entries.len());
+                    for (k, v) in entries {
+                        let k = self.eval(env, k).await?;
+                        let v = self.eval(env, v).await?;
+                        insert(&mut map, k, v);
+                    }
+                    Ok(Value::Map(map))
+                }
+                ExprKind::Index(arr, idx) => {
+                    let arr = self.eval(env, arr).await?;
+                    let idx = self.eval(env, idx).await?;
+                    self.located(expr.span, index(arr, idx))
+                }
+                ExprKind::Slice(arr, from, to) => {
+                    let arr = self.eval(env, arr).await?;
+                    let from = self.eval(env, from).await?;
+                    let to = self.eval(env, to).await?;
+                    self.located(expr.span, slice(arr, from, to))
+                }
+                ExprKind::MethodCall(target, name, args) if MUTATING_METHODS.contains(&name.as_str()) => {
+                    let mut values = Vec::with_capacity(args.len());
+                    for a in args { values.push(self.eval(env, a).await?); }
+                    mutate(env, target, name, values)
+                }
+                ExprKind::MethodCall(target, name, args) => {
+                    let target = self.eval(env, target).await?;
+                    let mut values = Vec::with_capacity(args.len());
+                    for a in args { values.push(self.eval(env, a).await?); }
+                    if HIGHER_ORDER_METHODS.contains(&name.as_str()) { return self.combinator(target, name, values); }
+                    method(target, name, values)
+                }
+                ExprKind::Interpolated(parts) => {
+                    let mut out = String::new();
+                    for part in parts {
+                        match part {
+                            StrPart::Text(s) => out.push_str(s),
+                            StrPart::Expr(e) => out.push_str(&self.eval(env, e).await?.to_string()),
+                        }
+                    }
+                    Ok(Value::Str(out))
+                }
+                ExprKind::Field(target, name) => field(self.eval(env, target).await?, name),
+                ExprKind::StructLiteral(name, fields) => {
+                    let mut values = Vec::with_capacity(fields.len());
+                    for (f, v) in fields { values.push((f.clone(), self.eval(env, v).await?)); }
+                    self.record(name, values)
+                }
+                ExprKind::Ctor(c, Some(payload)) => Ok(Value::Ctor(*c, Some(Box::new(self.eval(env, payload).await?)))),
+                ExprKind::Match(scrutinee, arms) => {
+                    let value = self.eval(env, scrutinee).await?;
+                    let (arm, binding) = select_arm(arms, value)?;
+                    env.enter();
+                    if let Some((name, value)) = binding { env.define(&name, value); }
+                    let result = match &arm.body {
+                        ArmBody::Expr(e) => self.eval(env, e).await,
+                        ArmBody::Block(b) => self.exec_block(env, b).await.map(|_| Value::Void),
+                    };
+                    env.leave();
+                    result
+                }
+                ExprKind::Spawn(e) => self.spawn(env, e),
+                ExprKind::Await(e) => match self.eval(env, e).await? {
+                    Value::Task(task) => Ok(task.await),
+                    other => fail(format!("cannot await {}", other)),
+                },
+                ExprKind::Infra(call) => self.infra(env, call).await,
+                ExprKind::Identifier(_) | ExprKind::Literal(_) | ExprKind::Ctor(_, None) | ExprKind::Lambda(..) => self.eval_sync(env, expr),
+            }
+        })
+    }
+
+    // Task yakaladığı değişkenlerin kopyasıyla en içteki scope'ta çalışır
+    fn spawn(&self, env: &mut Env, expr: &Expr) -> Exec<Value> {
+        let interp = self.clone();
+        let mut task_env = Env::with(env.snapshot(), env.overflow);
+        let expr = expr.clone();
+        let Some(scope) = env.scopes.last_mut() else { return fail("`spawn` outside of a `scope` block") };
+        Ok(Value::Task(scope.spawn(async move {
+            match interp.eval(&mut task_env, &expr).await {
+                Ok(v) | Err(Unwind::Return(v)) => v,
+                // Derlenmiş programda task içindeki hata süreci sonlandırır
+                Err(Unwind::Error(msg)) => runtime_error(msg),
+            }
+        })))
+    }
+
+    async fn infra(&self, env: &mut Env, call: &InfraCall) -> Exec<Value> {
+        let name = format!("{}.{}", call.service, call.method);
+        if builtins::lookup(&name).is_none() { return fail(format!("unknown service `{}`", name)); }
+        // Argümanlar bir kez hesaplanır ve metne çevrilir, her denemede kopyası verilir
+        let mut args = Vec::with_capacity(call.args.len());
+        for a in &call.args { args.push(Value::Str(self.eval(env, a).await?.to_string())); }
+        let backoff = match call.config.backoff {
+            Backoff::Fixed(ms) => gojo_runtime::Backoff::Fixed(ms),
+            Backoff::Exponential(ms) => gojo_runtime::Backoff::Exponential(ms),
+        };
+        let result = infra_call(&name, call.config.timeout_ms.unwrap_or_default(), call.config.retry, backoff, || call_service(&name, args.clone())).await;
+        match (result, &call.config.fallback) {
+            (Ok(v), Some(_)) => Ok(v),
+            (Err(_), Some(fallback)) => self.eval(env, fallback).await,
+            (out, None) => Ok(wrap_result(out)),
+        }
+    }
+
+    // ---- kayıtlar ve schema doğrulama ----
+
+    fn record_def(&self, name: &str) -> Option<Vec<(&str, &TypeRef, &[Constraint])>> {
+        if let Some(s) = self.symbols.structs.get(name) {
+            return Some(s.fields.iter().map(|f| (f.name.as_str(), &f.field_type, &[][..])).collect());
+        }
+        self.symbols.schemas.get(name).map(|s| s.fields.iter().map(|f| (f.name.as_str(), &f.field_type, &f.constraints[..])).collect())
+    }
+
+    // Alanlar tanım sırasına dizilir
+    fn record(&self, name: &str, mut values: Vec<(String, Value)>) -> Exec<Value> {
+        let Some(def) = self.record_def(name) else { return fail(format!("unknown type `{}`", name)) };
+        let mut fields = Vec::with_capacity(def.len());
+        for (field, _, _) in def {
+            let Some(pos) = values.iter().position(|(f, _)| f == field) else { return fail(format!("missing field `{}` in `{}`", field, name)) };
+            fields.push(values.swap_remove(pos));
+        }
+        Ok(Value::Record(name.to_string(), fields))
+    }
+
+    // Derlenmiş koddaki validate_schema::<S> / .validate() çağrısının karşılığı
+    fn validate(&self, env: &Env, target: &str, schema: &Option<String>) -> Exec<Result<Value, String>> {
+        let raw = match env.get(target)? {
+            Value::Str(s) => s,
+            other => return fail(format!("cannot validate {}", other)),
+        };
+        let Some(schema) = schema else { return Ok(raw.validate().map(Value::Str)) };
+        let Some(def) = self.record_def(schema) else { return fail(format!("unknown schema `{}`", schema)) };
+        let rules: Vec<Vec<FieldRule>> = def.iter().map(|(_, _, constraints)| constraints.iter().map(|c| match c {
+            Constraint::Len(min, max) => FieldRule::Len(*min, *max),
+            Constraint::Range(min, max) => FieldRule::Range(*min, *max),
+            Constraint::Regex(re) => FieldRule::Regex(re),
+        }).collect()).collect();
+        let specs: Vec<FieldSpec> = def.iter().zip(&rules).map(|((name, t, _), rules)| {
+            let kind = match t { TypeRef::Integer => FieldKind::Int, TypeRef::String => FieldKind::Str, _ => FieldKind::Any };
+            FieldSpec { name, kind, rules }
+        }).collect();
+        Ok(validate_schema::<serde_json::Value>(&raw, schema, &specs)
+            .and_then(|json| self.decode_json(&TypeRef::Custom(schema.clone()), &json).map_err(|e| format!("{}: {}", schema, e))))
+    }
+
+    fn decode_json(&self, t: &TypeRef, json: &serde_json::Value) -> Result<Value, String> {
+        use serde_json::Value as Json;
+        match (t, json) {
+            (TypeRef::Integer, Json::Number(n)) if n.is_i64() => Ok(Value::Int(n.as_i64().unwrap_or_default())),
+            (TypeRef::Float, Json::Number(n)) => Ok(Value::Float(n.as_f64().unwrap_or_default())),
+            (TypeRef::Byte, Json::Number(n)) if n.as_u64().is_some_and(|n| n <= 255) => Ok(Value::Byte(n.as_u64().unwrap_or_default() as u8)),
+            (TypeRef::String | TypeRef::Untrusted, Json::String(s)) => Ok(Value::Str(s.clone())),
+            (TypeRef::Bool, Json::Bool(b)) => Ok(Value::Bool(*b)),
+            (TypeRef::Array(inner), Json::Array(items)) => items.iter().map(|i| self.decode_json(inner, i)).collect::<Result<_, _>>().map(Value::Array),
+            (TypeRef::Map(key, inner), Json::Object(obj)) => {
+                let mut entries = Vec::with_capacity(obj.len());
+                for (k, v) in obj {
+                    let k = match (&**key, k.as_str()) {
+                        (TypeRef::String, k) => Value::Str(k.to_string()),
+                        (TypeRef::Integer, k) => k.parse().map(Value::Int).map_err(|_| format!("invalid map key `{}`, expected {}", k, key))?,
+                        (TypeRef::Byte, k) => k.parse().map(Value::Byte).map_err(|_| format!("invalid map key `{}`, expected {}", k, key))?,
+                        (TypeRef::Bool, k) => k.parse().map(Value::Bool).map_err(|_| format!("invalid map key `{}`, expected {}", k, key))?,
+                        (key, k) => return Err(format!("invalid map key `{}`, expected {}", k, key)),
+                    };
+                    insert(&mut entries, k, self.decode_json(inner, v)?);
+                }
+                Ok(Value::Map(entries))
+            }
+            (TypeRef::Option(_), Json::Null) => Ok(Value::Ctor(Ctor::None, None)),
+            (TypeRef::Option(inner), j) => Ok(Value::Ctor(Ctor::Some, Some(Box::new(self.decode_json(inner, j)?)))),
+            (TypeRef::Custom(name), Json::Object(obj)) if self.record_def(name).is_some() => {
+                let def = self.record_def(name).unwrap_or_default();
+                let mut fields = Vec::with_capacity(def.len());
+                for (field, field_type, _) in def {
+                    let value = match (obj.get(field), field_type) {
+                        (Some(v), _) => self.decode_json(field_type, v)?,
+                        (None, TypeRef::Option(_)) => Value::Ctor(Ctor::None, None),
+                        (None, _) => return Err(format!("missing field `{}`", field)),
+                    };
+                    fields.push((field.to_string(), value));
+                }
+                Ok(Value::Record(name.clone(), fields))
+            }
+            (t, j) => Err(format!("invalid type: {}, expected {}", j, t)),
+        }
+    }
+}
+
+// ---- builtin servisler: derlenmiş kodla aynı runtime fonksiyonları ----
+
+async fn call_service(name: &str, args: Vec<Value>) -> Result<Value, String> {
+    let arg = |i: usize| args.get(i).map(|v| v.to_string()).unwrap_or_default();
+    match name {
+        "DB.log" => { DB::log(arg(0)).await; Ok(Value::Void) }
+        "Console.read" => Ok(Value::Str(Console::read(arg(0)).await)),
+        "Util.now" => Ok(Value::Int(Util::now().await)),
+        "Util.to_int" => Ok(Value::Int(Util::to_int(arg(0)))),
+        "HTTP.get" => HTTP::get(arg(0)).await.map(Value::Str),
+        _ => Err(format!("unknown builtin `{}`", name)),
+    }
+}
+
+fn call_pure_builtin(name: &str, args: Vec<Value>) -> Exec<Value> {
+    match (name, args.first()) {
+        ("Util.to_int", Some(s)) => Ok(Value::Int(Util::to_int(s.to_string()))),
+        ("len", Some(Value::Array(items))) => Ok(Value::Int(items.len() as i64)),
+        ("len", Some(Value::Map(entries))) => Ok(Value::Int(entries.len() as i64)),
+        _ => fail(format!("`{}` cannot be called from a deterministic context", name)),
+    }
+}
+
+fn wrap_result(out: Result<Value, String>) -> Value {
+    match out {
+        Ok(v) => Value::Ctor(Ctor::Ok, Some(Box::new(v))),
+        Err(e) => Value::Ctor(Ctor::Err, Some(Box::new(Value::Str(e)))),
+    }
+}
+
+fn report_validate_error(target: &str, err: &str) {
+    eprintln!("  {}[Validate] {}: {}{}", RED, target, err, RESET);
+}
+
+// ---- değer işlemleri ----
+
+fn truthy(v: Value) -> Exec<bool> {
+    match v { Value::Bool(b) => Ok(b), other => fail(format!("expected bool, found {}", other)) }
+}
+
+fn int(v: Value) -> Exec<i64> {
+    match v { Value::Int(n) => Ok(n), other => fail(format!("expected i64, found {}", other)) }
+}
+
+fn for_continues(i: i64, end: i64, step: i64) -> bool {
+    (step > 0 && i < end) || (step < 0 && i > end)
+}
+
+fn arith(result: Result<i64, &'static str>) -> Exec<Value> {
+    result.map(Value::Int).or_else(fail)
+}
+
+fn byte(result: Result<u8, &'static str>) -> Exec<Value> {
+    result.map(Value::Byte).or_else(fail)
+}
+
+// Metin birleştirme taşmaz: trait'e verilen Site'ın konumu kullanılmaz
+const TEXT: Site = Site::checked("");
+
+// i64 ve u8 işlemleri derlenmiş koddaki Site ile aynı kuralı izler (runtime'ın ops fonksiyonları);
+// f64 işlemleri Rust operatörleridir; String işlemleri runtime'ın trait'leridir
+fn binary(op: &BinaryOp, l: Value, r: Value, overflow: ops::Overflow) -> Exec<Value> {
+    Ok(match (op, l, r) {
+        (BinaryOp::Add, Value::Int(a), Value::Int(b)) => return arith(ops::add(a, b, overflow)),
+        (BinaryOp::Sub, Value::Int(a), Value::Int(b)) => return arith(ops::sub(a, b, overflow)),
+        (BinaryOp::Mul, Value::Int(a), Value::Int(b)) => return arith(ops::mul(a, b, overflow)),
+        (BinaryOp::Div, Value::Int(a), Value::Int(b)) => return arith(ops::div(a, b, overflow)),
+        (BinaryOp::Mod, Value::Int(a), Value::Int(b)) => return arith(ops::rem(a, b, overflow)),
+        (BinaryOp::Add, Value::Byte(a), Value::Byte(b)) => return byte(ops::Int::add(a, b, overflow)),
+        (BinaryOp::Sub, Value::Byte(a), Value::Byte(b)) => return byte(ops::Int::sub(a, b, overflow)),
+        (BinaryOp::Mul, Value::Byte(a), Value::Byte(b)) => return byte(ops::Int::mul(a, b, overflow)),
+        (BinaryOp::Div, Value::Byte(a), Value::Byte(b)) => return byte(ops::Int::div(a, b, overflow)),
+        (BinaryOp::Mod, Value::Byte(a), Value::Byte(b)) => return byte(ops::Int::rem(a, b, overflow)),
+        (BinaryOp::Add, Value::Float(a), Value::Float(b)) => Value::Float(a + b),
+        (BinaryOp::Sub, Value::Float(a), Value::Float(b)) => Value::Float(a - b),
+        (BinaryOp::Mul, Value::Float(a), Value::Float(b)) => Value::Float(a * b),
+        (BinaryOp::Div, Value::Float(a), Value::Float(b)) => Value::Float(a / b),
+        (BinaryOp::Mod, Value::Float(a), Value::Float(b)) => Value::Float(a % b),
+        (BinaryOp::Add, Value::Str(a), Value::Int(b)) => Value::Str(a.g_add(b, TEXT)),
+        (BinaryOp::Add, Value::Str(a), Value::Str(b)) => Value::Str(a.g_add(b, TEXT)),
+        (BinaryOp::Add, Value::Str(a), r @ Value::Record(..)) => Value::Str(format!("{}{}", a, r)),
+        (BinaryOp::Mul, Value::Str(a), Value::Int(b)) => return ops::repeat(&a, b).map(Value::Str).or_else(fail),
+        (BinaryOp::Eq, l, r) => Value::Bool(l == r),
+        (BinaryOp::Neq, l, r) => Value::Bool(l != r),
+        // NaN hiçbir şeyle sıralanamaz: tüm karşılaştırmalar false
+        (op @ (BinaryOp::Gt | BinaryOp::Lt | BinaryOp::Gte | BinaryOp::Lte), Value::Float(a), Value::Float(b)) => Value::Bool(match op {
+            BinaryOp::Gt => a > b,
+            BinaryOp::Lt => a < b,
+            BinaryOp::Gte => a >= b,
+            _ => a <= b,
+        }),
+        (op @ (BinaryOp::Gt | BinaryOp::Lt | BinaryOp::Gte | BinaryOp::Lte), l, r) => {
+            let ord = match (&l, &r) {
+                (Value::Int(a), Value::Int(b)) => a.cmp(b),
+                (Value::Byte(a), Value::Byte(b)) => a.cmp(b),
+                (Value::Str(a), Value::Str(b)) => a.cmp(b),
+                (Value::Bool(a), Value::Bool(b)) => a.cmp(b),
+                _ => return fail(format!("cannot compare {} and {}", l, r)),
+            };
+            Value::Bool(match op {
+                BinaryOp::Gt => ord.is_gt(),
+                BinaryOp::Lt => ord.is_lt(),
+                BinaryOp::Gte => ord.is_ge(),
+                _ => ord.is_le(),
+            })
+        }
+        (op, l, r) => return fail(format!("unsupported operands for `{}`: {} and {}", op.symbol(), l, r)),
+    })
+}
+
+// false && .. ve true || .. sağ tarafı hiç değerlendirmez
+fn short_circuits(op: &BinaryOp, left: bool) -> bool {
+    matches!((op, left), (BinaryOp::And, false) | (BinaryOp::Or, true))
+}
+
+fn unary(op: UnaryOp, v: Value, overflow: ops::Overflow) -> Exec<Value> {
+    match (op, v) {
+        (UnaryOp::Not, Value::Bool(b)) => Ok(Value::Bool(!b)),
+        (UnaryOp::Neg, Value::Int(n)) => arith(ops::neg(n, overflow)),
+        (UnaryOp::Neg, Value::Float(f)) => Ok(Value::Float(-f)),
+        (op, v) => fail(format!("unsupported operand for `{}`: {}", op.symbol(), v)),
+    }
+}
+
+// Derlenmiş kodla aynı kural: negatif ya da sınır dışı indeks hatadır
+fn index(arr: Value, idx: Value) -> Exec<Value> {
+    match (arr, idx) {
+        (Value::Array(items), Value::Int(i)) => array::index(&items, i).cloned().or_else(fail),
+        (Value::Map(entries), key) => Ok(option(lookup(&entries, &key).map(|i| entries[i].1.clone()))),
+        (arr, _) => fail(format!("cannot index into {}", arr)),
+    }
+}
+
+fn slice(arr: Value, from: Value, to: Value) -> Exec<Value> {
+    match (arr, from, to) {
+        (Value::Array(items), Value::Int(from), Value::Int(to)) => array::slice(&items, from, to).map(|s| Value::Array(s.to_vec())).or_else(fail),
+        (arr, _, _) => fail(format!("cannot slice {}", arr)),
+    }
+}
+
+fn option(value: Option<Value>) -> Value {
+    match value {
+        Some(v) => Value::Ctor(Ctor::Some, Some(Box::new(v))),
+        None => Value::Ctor(Ctor::None, None),
+    }
+}
+
+// Derlenmiş kodda `GojoArray` ve `GojoMap` trait'lerinin `g_` metotları
+fn method(target: Value, name: &str, args: Vec<Value>) -> Exec<Value> {
+    match (target, name, args.as_slice()) {
+        (Value::Array(items), "get", [Value::Int(i)]) => Ok(option(array::index(&items, *i).ok().cloned())),
+        (Value::Array(items), "contains", [item]) => Ok(Value::Bool(items.contains(item))),
+        (Value::Map(entries), "get", [key]) => Ok(option(lookup(&entries, key).map(|i| entries[i].1.clone()))),
+        (target, name, _) => fail(format!("no method `{}` on {}", name, target)),
+    }
+}
+
+// Hedefini değiştiren metotlar (insert, push, sort...): değişken okunur, değiştirilir ve geri yazılır
+fn mutate(env: &mut Env, target: &Expr, name: &str, args: Vec<Value>) -> Exec<Value> {
+    let ExprKind::Identifier(var) = &target.kind else { return fail(format!("`{}` modifies its target, which must be a variable", name)) };
+    let (value, out) = match (env.get(var)?, name, args.as_slice()) {
+        (Value::Map(mut entries), "insert", [key, value]) => {
+            let old = insert(&mut entries, key.clone(), value.clone());
+            (Value::Map(entries), option(old))
+        }
+        (Value::Map(mut entries), "remove", [key]) => {
+            let old = lookup(&entries, key).map(|i| entries.remove(i).1);
+            (Value::Map(entries), option(old))
+        }
+        (Value::Array(mut items), "push", [item]) => {
+            items.push(item.clone());
+            (Value::Array(items), Value::Void)
+        }
+        (Value::Array(mut items), "pop", []) => {
+            let last = items.pop();
+            (Value::Array(items), option(last))
+        }
+        (Value::Array(mut items), "sort", []) => {
+            items.sort_by(key_cmp);
+            (Value::Array(items), Value::Void)
+        }
+        (Value::Array(mut items), "reverse", []) => {
+            items.reverse();
+            (Value::Array(items), Value::Void)
+        }
+        (target, name, _) => return fail(format!("no method `{}` on {}", name, target)),
+    };
+    env.assign(var, value)?;
+    Ok(out)
+}
+
+// Map anahtarlarının ve sort'un sırası: derlenmiş koddaki Ord ile aynı (false < true, metinler bayt sırasıyla)
+fn key_cmp(a: &Value, b: &Value) -> std::cmp::Ordering {
+    match (a, b) {
+        (Value::Int(a), Value::Int(b)) => a.cmp(b),
+        (Value::Byte(a), Value::Byte(b)) => a.cmp(b),
+        (Value::Str(a), Value::Str(b)) => a.cmp(b),
+        (Value::Bool(a), Value::Bool(b)) => a.cmp(b),
+        _ => std::cmp::Ordering::Equal,
+    }
+}
+
+fn lookup(entries: &[(Value, Value)], key: &Value) -> Option<usize> {
+    entries.binary_search_by(|(k, _)| key_cmp(k, key)).ok()
+}
+
+// Sırayı koruyarak ekler; anahtar zaten varsa eski değeri döndürür
+fn insert(entries: &mut Vec<(Value, Value)>, key: Value, value: Value) -> Option<Value> {
+    match entries.binary_search_by(|(k, _)| key_cmp(k, &key)) {
+        Ok(i) => Some(std::mem::replace(&mut entries[i].1, value)),
+        Err(i) => { entries.insert(i, (key, value)); None }
+    }
+}
+
+// Dizide (indeks, eleman), map'te (anahtar, değer) çiftleri
+fn entries(v: Value) -> Exec<Vec<(Value, Value)>> {
+    match v {
+        Value::Array(items) => Ok((0..).map(Value::Int).zip(items).collect()),
+        Value::Map(entries) => Ok(entries),
+        other => fail(format!("cannot iterate over {}", other)),
+    }
+}
+
+fn bind(env: &mut Env, binding: &ForBinding, (key, value): (Value, Value)) {
+    match binding {
+        ForBinding::Item(x) => env.define(x, value),
+        ForBinding::Indexed(k, v) | ForBinding::Entry(k, v) => {
+            env.define(k, key);
+            env.define(v, value);
+        }
+    }
+}
+
+fn field(target: Value, name: &str) -> Exec<Value> {
+    match target {
+        Value::Record(record, fields) => match fields.into_iter().find(|(f, _)| f == name) {
+            Some((_, v)) => Ok(v),
+            None => fail(format!("`{}` has no field `{}`", record, name)),
+        },
+        other => fail(format!("cannot read field `{}` of {}", name, other)),
+    }
+}
+
+fn json(source: Value, key: &str) -> Exec<Value> {
+    match source {
+        Value::Str(raw) => Ok(wrap_result(json_field(&raw, key).map(Value::Str))),
+        other => fail(format!("json() expects a String, found {}", other)),
+    }
+}
+
+// İlk uyan kol ve (varsa) desenin bağladığı değer
+fn select_arm(arms: &[MatchArm], value: Value) -> Exec<(&MatchArm, Option<(String, Value)>)> {
+    let Value::Ctor(ctor, payload) = value else { return fail(format!("cannot match on {}", value)) };
+    for arm in arms {
+        match &arm.pattern {
+            Pattern::Wildcard => return Ok((arm, None)),
+            Pattern::Ctor(c, binding) if *c == ctor => return Ok((arm, binding.clone().zip(payload.map(|p| *p)))),
+            Pattern::Ctor(..) => {}
+        }
+    }
+    fail("no `match` arm matched")
+}
+
+// ---- async ifadelerin işaretlenmesi ----
+
+// Fonksiyon değeri çağrıları (builtin olmayan bilinmeyen isimler) da saftır
+fn is_pure_call(symbols: &SymbolTable, name: &str) -> bool {
+    match symbols.functions.get(name) {
+        Some(f) => f.purity == Purity::Deterministic,
+        None => !builtins::lookup(name).is_some_and(|b| b.purity != Purity::Deterministic),
+    }
+}
+
+// İfade (ya da alt ifadelerinden biri) await gerektiriyorsa işaretler
+fn mark_expr(expr: &Expr, symbols: &SymbolTable, out: &mut HashSet<ExprId>) -> bool {
+    let is_async = match &expr.kind {
+        // Closure gövdesi her zaman senkron değerlendirilir (servis çağrıları block_on ile beklenir)
+        ExprKind::Identifier(_) | ExprKind::Literal(_) | ExprKind::Ctor(_, None) | ExprKind::Lambda(..) => false,
+        ExprKind::Binary(l, _, r) | ExprKind::Index(l, r) => mark_expr(l, symbols, out) | mark_expr(r, symbols, out),
+        ExprKind::Slice(arr, from, to) => mark_expr(arr, symbols, out) | mark_expr(from, symbols, out) | mark_expr(to, symbols, out),
+        ExprKind::Call(name, args) => args.iter().fold(!is_pure_call(symbols, name), |acc, a| mark_expr(a, symbols, out) | acc),
+        ExprKind::ArrayLiteral(items) => items.iter().fold(false, |acc, e| mark_expr(e, symbols, out) | acc),
+        ExprKind::MapLiteral(entries) => entries.iter().fold(false, |acc, (k, v)| mark_expr(k, symbols, out) | mark_expr(v, symbols, out) | acc),
+        ExprKind::MethodCall(target, _, args) => args.iter().fold(mark_expr(target, symbols, out), |acc, a| mark_expr(a, symbols, out) | acc),
+        ExprKind::Interpolated(parts) => parts.iter().filter_map(StrPart::expr).fold(false, |acc, e| mark_expr(e, symbols, out) | acc),
+        ExprKind::StructLiteral(_, fields) => fields.iter().fold(false, |acc, (_, v)| mark_expr(v, symbols, out) | acc),
+        ExprKind::JsonField(e, _) | ExprKind::Field(e, _) | ExprKind::Ctor(_, Some(e)) | ExprKind::Unary(_, e) => mark_expr(e, symbols, out),
+        ExprKind::Spawn(e) | ExprKind::Await(e) => { mark_expr(e, symbols, out); true }
+        ExprKind::Infra(call) => {
+            call.args.iter().for_each(|a| { mark_expr(a, symbols, out); });
+            if let Some(f) = &call.config.fallback { mark_expr(f, symbols, out); }
+            true
+        }
+        ExprKind::Match(scrutinee, arms) => arms.iter().fold(mark_expr(scrutinee, symbols, out), |acc, arm| match &arm.body {
+            ArmBody::Expr(e) => mark_expr(e, symbols, out) | acc,
+            ArmBody::Block(b) => mark_block(b, symbols, out) | acc,
+        }),
+    };
+    if is_async { out.insert(expr.id); }
+    is_async
+}
+
+fn mark_block(block: &Block, symbols: &SymbolTable, out: &mut HashSet<ExprId>) -> bool {
+    block.statements.iter().fold(false, |acc, s| mark_stmt(s, symbols, out) | acc)
+}
+
+fn mark_stmt(stmt: &Statement, symbols: &SymbolTable, out: &mut HashSet<ExprId>) -> bool {
+    match &stmt.kind {
+        StmtKind::Let(LetStmt { value, .. }) | StmtKind::Assign { value, .. } | StmtKind::ExprStmt(value) | StmtKind::Return(Some(value)) => mark_expr(value, symbols, out),
+        StmtKind::Return(None) => false,
+        StmtKind::If { condition, then_block, else_block } => {
+            mark_expr(condition, symbols, out) | mark_block(then_block, symbols, out) | else_block.as_ref().is_some_and(|b| mark_block(b, symbols, out))
+        }
+        StmtKind::While { condition, body } => mark_expr(condition, symbols, out) | mark_block(body, symbols, out),
+        StmtKind::For { start, end, step, body, .. } => {
+            mark_expr(start, symbols, out) | mark_expr(end, symbols, out) | step.as_ref().is_some_and(|s| mark_expr(s, symbols, out)) | mark_block(body, symbols, out)
+        }
+        StmtKind::ForIn { iterable, body, .. } => mark_expr(iterable, symbols, out) | mark_block(body, symbols, out),
+        StmtKind::ScopeBlock { body, .. } => { mark_block(body, symbols, out); true }
+        StmtKind::AwaitAll => true,
+        StmtKind::ValidateBlock { on_fail, success_scope, .. } => on_fail.as_ref().is_some_and(|b| mark_block(b, symbols, out)) | mark_block(success_scope, symbols, out),
+        StmtKind::ValidateLet { on_fail, .. } => mark_block(on_fail, symbols, out),
+    }
+}
+
+// ---- closure'ların yakaladığı isimler ----
+
+// Gövdede geçen tüm isimler: değişkenler, değer çağrıları ve atama hedefleri.
+// Fazlası zararsızdır (closure'ın kendi tanımları da yakalanabilir, gölgelenir).
+fn names_in_expr(expr: &Expr, out: &mut HashSet<String>) {
+    let mut visit = |e: &Expr| names_in_expr(e, out);
+    match &expr.kind {
+        ExprKind::Identifier(name) => { out.insert(name.clone()); }
+        ExprKind::Literal(_) | ExprKind::Ctor(_, None) => {}
+        ExprKind::Call(name, args) => {
+            out.insert(name.clone());
+            args.iter().for_each(|a| names_in_expr(a, out));
+        }
+        ExprKind::Binary(l, _, r) | ExprKind::Index(l, r) => { visit(l); visit(r); }
+        ExprKind::Slice(arr, from, to) => { visit(arr); visit(from); visit(to); }
+        ExprKind::ArrayLiteral(items) => items.iter().for_each(visit),
+        ExprKind::MapLiteral(entries) => entries.iter().for_each(|(k, v)| { visit(k); visit(v); }),
+        ExprKind::MethodCall(target, _, args) => { visit(target); args.iter().for_each(visit); }
+        ExprKind::Interpolated(parts) => parts.iter().filter_map(StrPart::expr).for_each(visit),
+        ExprKind::StructLiteral(_, fields) => fields.iter().for_each(|(_, v)| visit(v)),
+        ExprKind::Infra(call) => { call.args.iter().for_each(&mut visit); call.config.fallback.iter().for_each(|f| visit(f)); }
+        ExprKind::Spawn(e) | ExprKind::Await(e) | ExprKind::JsonField(e, _) | ExprKind::Field(e, _) | ExprKind::Ctor(_, Some(e)) | ExprKind::Unary(_, e) | ExprKind::Lambda(_, e) => visit(e),
+        ExprKind::Match(scrutinee, arms) => {
+            visit(scrutinee);
+            for arm in arms {
+                match &arm.body {
+                    ArmBody::Expr(e) => names_in_expr(e, out),
+                    ArmBody::Block(b) => names_in_block(b, out),
+                }
+            }
+        }
+    }
+}
+
+fn names_in_block(block: &Block, out: &mut HashSet<String>) {
+    for stmt in &block.statements {
+        match &stmt.kind {
+            StmtKind::Let(LetStmt { value, .. }) | StmtKind::ExprStmt(value) | StmtKind::Return(Some(value)) => names_in_expr(value, out),
+            StmtKind::Assign { name, value } => { out.insert(name.clone()); names_in_expr(value, out); }
+            StmtKind::If { condition, then_block, else_block } => {
+                names_in_expr(condition, out);
+                names_in_block(then_block, out);
+                if let Some(b) = else_block { names_in_block(b, out); }
+            }
+            StmtKind::While { condition, body } => { names_in_expr(condition, out); names_in_block(body, out); }
+            StmtKind::For { start, end, step, body, .. } => {
+                names_in_expr(start, out);
+                names_in_expr(end, out);
+                if let Some(s) = step { names_in_expr(s, out); }
+                names_in_block(body, out);
+            }
+            StmtKind::ForIn { iterable, body, .. } => { names_in_expr(iterable, out); names_in_block(body, out); }
+            StmtKind::ScopeBlock { body, .. } => names_in_block(body, out),
+            StmtKind::ValidateBlock { target, on_fail, success_scope, .. } => {
+                out.insert(target.clone());
+                if let Some(b) = on_fail { names_in_block(b, out); }
+                names_in_block(success_scope, out);
+            }
+            StmtKind::ValidateLet { target, on_fail, .. } => { out.insert(target.clone()); names_in_block(on_fail, out); }
+            StmtKind::Return(None) | StmtKind::AwaitAll => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diagnostics::SourceFile;
+    use crate::{driver, parser};
+
+    fn run_source(source: &str) -> Result<(), String> {
+        let (program, errors) = parser::parse_program(source);
+        assert!(errors.is_empty(), "{:?}", errors);
+        let types = driver::analyze(&program).unwrap_or_else(|e| panic!("{}", e.message));
+        run(&program, &types, &SourceMap::single(SourceFile::new("test.gj", source)))
+    }
+
+    #[test]
+    fn runs_analyzed_programs() {
+        let source = "deterministic wrapping fn mix(h: i64) -> i64 { return h * 31 }\n\
+                      nondeterministic fn main(input: Untrusted) -> Void {\n\
+                          let arr = [1, 2, 3]\n\
+                          DB.log(arr.map(|x: i64| mix(x)).fold(0, |acc, x| acc + x))\n\
+                          DB.log(mix(9223372036854775807))\n\
+                      }";
+        assert_eq!(run_source(source), Ok(()));
+    }
+
+    #[test]
+    fn impure_closures_wait_for_their_services() {
+        let source = "nondeterministic fn stamp(n: i64) -> i64 { return n + 1 }
+                      nondeterministic fn main(input: Untrusted) -> Void {
+                          let log = |x: i64| DB.log(x)
+                          let arr = [1, 2]
+                          let logged = arr.map(|x: i64| log(stamp(x)))
+                          let t = |x: i64| x + call Util.now() { timeout: 1s, fallback: 0 }
+                          DB.log(t(0) > 0)
+                          let f = stamp
+                          DB.log(f(len(logged)))
+                      }";
+        assert_eq!(run_source(source), Ok(()));
+        // Closure içindeki servis hatası da yerinde raporlanır
+        let failing = "nondeterministic fn main(input: Untrusted) -> Void {
+    let div = |x: i64| DB.log(10 / x)
+    div(0)
+}";
+        assert_eq!(run_source(failing), Err("attempt to divide by zero at test.gj:2:31".to_string()));
+    }
+
+    #[test]
+    fn reports_runtime_errors_with_their_location() {
+        let overflow = "deterministic fn inc(n: i64) -> i64 {\n    return n + 1\n}\n\
+                        nondeterministic fn main(input: Untrusted) -> Void { DB.log(inc(9223372036854775807)) }";
+        assert_eq!(run_source(overflow), Err("attempt to add with overflow at test.gj:2:12".to_string()));
+
+        let divide = "nondeterministic fn main(input: Untrusted) -> Void {\n    let zero = 0\n    DB.log(10 / zero)\n}";
+        assert_eq!(run_source(divide), Err("attempt to divide by zero at test.gj:3:12".to_string()));
+
+        let repeat = "nondeterministic fn main(input: Untrusted) -> Void {\n    DB.log(\"ab\" * 9223372036854775807)\n}";
+        assert_eq!(run_source(repeat), Err("capacity overflow at test.gj:2:12".to_string()));
+
+        let index = "nondeterministic fn main(input: Untrusted) -> Void {\n    let arr = [1, 2]\n    DB.log(arr[2])\n}";
+        let err = run_source(index).unwrap_err();
+        assert!(err.ends_with(" at test.gj:3:12"), "{}", err);
+
+        // Closure içindeki hata da closure gövdesindeki işlemin konumunu söyler
+        let closure = "nondeterministic fn main(input: Untrusted) -> Void {\n    let arr = [0, 1]\n    DB.log(arr.map(|x: i64| 1 % x).fold(0, |a, b| a + b))\n}";
+        assert_eq!(run_source(closure), Err("attempt to calculate the remainder with a divisor of zero at test.gj:3:29".to_string()));
+    }
+}
@@ -0,0 +1,299 @@
+use crate::analysis::determinism::SymbolTable;
+use crate::analysis::types::TypeTable;
+use crate::ast::*;
+use crate::builtins::{self, Builtin};
+use crate::diagnostics::{Diagnostic, SourceMap};
+use serde_json::{json, Value};
+
+// LSP pozisyonları satır + UTF-16 sütunudur; analiz byte offset'leriyle çalışır
+
+pub fn position(text: &str, offset: usize) -> Value {
+    let offset = offset.min(text.len());
+    let line_start = text[..offset].rfind('\n').map(|i| i + 1).unwrap_or(0);
+    let line = text[..offset].matches('\n').count();
+    json!({ "line": line, "character": text[line_start..offset].encode_utf16().count() })
+}
+
+pub fn range(text: &str, span: Span) -> Value {
+    json!({ "start": position(text, span.start), "end": position(text, span.end.max(span.start)) })
+}
+
+pub fn offset(text: &str, position: &Value) -> usize {
+    let line = position["line"].as_u64().unwrap_or(0) as usize;
+    let character = position["character"].as_u64().unwrap_or(0) as usize;
+    let mut line_start = 0;
+    for _ in 0..line {
+        match text[line_start..].find('\n') {
+            Some(i) => line_start += i + 1,
+            None => return text.len(),
+        }
+    }
+    let mut units = 0;
+    for (i, c) in text[line_start..].char_indices() {
+        if units >= character || c == '\n' { return line_start + i; }
+        units += c.len_utf16();
+    }
+    text.len()
+}
+
+pub fn diagnostic(text: &str, d: &Diagnostic) -> Value {
+    let mut message = d.message.clone();
+    for note in &d.notes {
+        message.push_str(&format!("\nnote: {}", note));
+    }
+    // 1: Error
+    json!({ "range": range(text, d.span), "severity": 1, "source": format!("gojo/{}", d.pass), "message": message })
+}
+
+// deterministic fn fib(n: i64) -> i64
+pub fn signature(func: &FunctionDef) -> String {
+    let purity = match func.purity { Purity::Deterministic => "deterministic", Purity::Nondeterministic => "nondeterministic" };
+    let params: Vec<String> = func.params.iter().map(|p| format!("{}: {}", p.name, p.param_type)).collect();
+    let ret = if func.return_type == TypeRef::Void { String::new() } else { format!(" -> {}", func.return_type) };
+    let wrapping = if func.overflow == Overflow::Wrapping { " wrapping" } else { "" };
+    format!("{}{} fn {}({}){}", purity, wrapping, func.name, params.join(", "), ret)
+}
+
+// nondeterministic HTTP.get(String) -> Result<String, String>
+fn builtin_signature(b: &Builtin) -> String {
+    let purity = match b.purity { Purity::Deterministic => "deterministic", Purity::Nondeterministic => "nondeterministic" };
+    let params: Vec<String> = b.params.iter().map(|p| p.as_ref().map(|t| t.to_string()).unwrap_or_else(|| "_".to_string())).collect();
+    let ret = b.return_type();
+    let ret = if ret == TypeRef::Void { String::new() } else { format!(" -> {}", ret) };
+    format!("{} {}({}){}", purity, b.name, params.join(", "), ret)
+}
+
+fn code(text: &str) -> Value {
+    json!({ "kind": "markdown", "value": format!("```gojo\n{}\n```", text) })
+}
+
+// Kaynak içindeki tüm ifadeler (iç içe olanlar dahil)
+fn collect_block<'a>(block: &'a Block, out: &mut Vec<&'a Expr>) {
+    for stmt in &block.statements {
+        collect_stmt(stmt, out);
+    }
+}
+
+fn collect_stmt<'a>(stmt: &'a Statement, out: &mut Vec<&'a Expr>) {
+    match &stmt.kind {
+        StmtKind::Let(l) => collect_expr(&l.value, out),
+        StmtKind::Assign { value, .. } | StmtKind::ExprStmt(value) | StmtKind::Return(Some(value)) => collect_expr(value, out),
+        StmtKind::If { condition, then_block, else_block } => {
+            collect_expr(condition, out);
+            collect_block(then_block, out);
+            if let Some(b) = else_block { collect_block(b, out); }
+        }
+        StmtKind::While { condition, body } => {
+            collect_expr(condition, out);
+            collect_block(body, out);
+        }
+        StmtKind::For { start, end, step, body, .. } => {
+            collect_expr(start, out);
+            collect_expr(end, out);
+            if let Some(s) = step { collect_expr(s, out); }
+            collect_block(body, out);
+        }
+        StmtKind::ForIn { iterable, body, .. } => {
+            collect_expr(iterable, out);
+            collect_block(body, out);
+        }
+        StmtKind::ScopeBlock { body, .. } => collect_block(body, out),
+        StmtKind::ValidateBlock { on_fail, success_scope, .. } => {
+            if let Some(b) = on_fail { collect_block(b, out); }
+            collect_block(success_scope, out);
+        }
+        StmtKind::ValidateLet { on_fail, .. } => collect_block(on_fail, out),
+        StmtKind::AwaitAll | StmtKind::Return(None) => {}
+    }
+}
+
+fn collect_expr<'a>(expr: &'a Expr, out: &mut Vec<&'a Expr>) {
+    out.push(expr);
+    match &expr.kind {
+        ExprKind::Identifier(_) | ExprKind::Literal(_) | ExprKind::Ctor(_, None) => {}
+        ExprKind::Binary(l, _, r) | ExprKind::Index(l, r) => {
+            collect_expr(l, out);
+            collect_expr(r, out);
+        }
+        ExprKind::Slice(arr, from, to) => {
+            collect_expr(arr, out);
+            collect_expr(from, out);
+            collect_expr(to, out);
+        }
+        ExprKind::Call(_, args) | ExprKind::ArrayLiteral(args) => args.iter().for_each(|a| collect_expr(a, out)),
+        ExprKind::MethodCall(target, _, args) => {
+            collect_expr(target, out);
+            args.iter().for_each(|a| collect_expr(a, out));
+        }
+        ExprKind::Interpolated(parts) => parts.iter().filter_map(StrPart::expr).for_each(|e| collect_expr(e, out)),
+        ExprKind::Spawn(e) | ExprKind::Await(e) | ExprKind::JsonField(e, _) | ExprKind::Field(e, _) | ExprKind::Ctor(_, Some(e)) | ExprKind::Unary(_, e) | ExprKind::Lambda(_, e) => collect_expr(e, out),
+        ExprKind::Infra(call) => {
+            call.args.iter().for_each(|a| collect_expr(a, out));
+            if let Some(f) = &call.config.fallback { collect_expr(f, out); }
+        }
+        ExprKind::StructLiteral(_, fields) => fields.iter().for_each(|(_, e)| collect_expr(e, out)),
+        ExprKind::MapLiteral(entries) => entries.iter().for_each(|(k, v)| {
+            collect_expr(k, out);
+            collect_expr(v, out);
+        }),
+        ExprKind::Match(scrutinee, arms) => {
+            collect_expr(scrutinee, out);
+            for arm in arms {
+                match &arm.body {
+                    ArmBody::Expr(e) => collect_expr(e, out),
+                    ArmBody::Block(b) => collect_block(b, out),
+                }
+            }
+        }
+    }
+}
+
+// İmlecin üzerindeki en içteki ifade
+fn expr_at(program: &Program, at: usize) -> Option<&Expr> {
+    let mut exprs = Vec::new();
+    for func in program.functions() {
+        collect_block(&func.body, &mut exprs);
+    }
+    exprs.into_iter()
+        .filter(|e| e.span.start <= at && at < e.span.end)
+        .min_by_key(|e| e.span.end - e.span.start)
+}
+
+// Çağrı ifadesinde fonksiyon adının span'i (`call` öneki atlanır). Modül fonksiyonu
+// kaynakta namespace ile yazılır (`fees.compute`): sadece son parça işaretlenir.
+fn callee_span(text: &str, expr: &Expr, name: &str) -> Option<Span> {
+    let name = if builtins::lookup(name).is_some() { name } else { name.rsplit('.').next()? };
+    let start = expr.span.start + text.get(expr.span.start..expr.span.end)?.find(name)?;
+    Some(Span::new(start, start + name.len()))
+}
+
+fn callee_at<'a>(text: &str, program: &'a Program, at: usize) -> Option<(&'a str, Span)> {
+    let expr = expr_at(program, at)?;
+    let ExprKind::Call(name, _) = &expr.kind else { return None };
+    let span = callee_span(text, expr, name)?;
+    (span.start <= at && at < span.end).then_some((name.as_str(), span))
+}
+
+pub fn hover(text: &str, program: &Program, types: &TypeTable, at: usize) -> Value {
+    let symbols = SymbolTable::from_program(program);
+    if let Some(func) = program.functions().find(|f| f.name_span.start <= at && at < f.name_span.end) {
+        return json!({ "contents": code(&signature(func)), "range": range(text, func.name_span) });
+    }
+    if let Some((name, span)) = callee_at(text, program, at) {
+        let contents = match (symbols.functions.get(name), builtins::lookup(name)) {
+            (Some(func), _) => code(&signature(func)),
+            (None, Some(b)) if b.taint_source => code(&format!("{}\n// builtin: sonuç Untrusted, validate edilmeli", builtin_signature(b))),
+            (None, Some(b)) => code(&format!("{}\n// builtin", builtin_signature(b))),
+            (None, None) => return Value::Null,
+        };
+        return json!({ "contents": contents, "range": range(text, span) });
+    }
+    let Some(expr) = expr_at(program, at) else { return Value::Null };
+    let Some(ty) = types.get(expr) else { return Value::Null };
+    let contents = match &expr.kind {
+        ExprKind::Identifier(name) => format!("{}: {}", name, ty),
+        _ => ty.to_string(),
+    };
+    json!({ "contents": code(&contents), "range": range(text, expr.span) })
+}
+
+// Kullanıcı fonksiyonuna yapılan çağrıdan tanımına (import edilen modülde olabilir)
+pub fn definition(text: &str, uri: &str, sources: &SourceMap, program: &Program, at: usize) -> Value {
+    let symbols = SymbolTable::from_program(program);
+    let Some(func) = callee_at(text, program, at).and_then(|(name, _)| symbols.functions.get(name)) else { return Value::Null };
+    if local(text, func.span) {
+        return json!({ "uri": uri, "range": range(text, func.name_span) });
+    }
+    match sources.lookup(func.name_span) {
+        Some((file, span)) => {
+            let path = std::fs::canonicalize(&file.name).map_or_else(|_| file.name.clone(), |p| p.display().to_string());
+            json!({ "uri": format!("file://{}", path), "range": range(&file.text, span) })
+        }
+        None => Value::Null,
+    }
+}
+
+// Tanım bu belgede mi? Import edilen modüllerin span'leri belgenin sonundan sonra başlar.
+fn local(text: &str, span: Span) -> bool { span.end <= text.len() }
+
+// SymbolKind: 8 Field, 12 Function, 23 Struct
+pub fn document_symbols(text: &str, program: &Program) -> Value {
+    let field = |name: &str, ty: &TypeRef, span: Span| json!({
+        "name": name, "detail": ty.to_string(), "kind": 8, "range": range(text, span), "selectionRange": range(text, span),
+    });
+    let symbols: Vec<Value> = program.items.iter().filter(|item| local(text, item.span())).map(|item| match item {
+        Item::Function(f) => json!({
+            "name": f.name, "detail": signature(f), "kind": 12,
+            "range": range(text, f.span), "selectionRange": range(text, f.name_span),
+        }),
+        Item::Struct(s) => json!({
+            "name": s.name, "detail": "struct", "kind": 23,
+            "range": range(text, s.span), "selectionRange": range(text, s.name_span),
+            "children": s.fields.iter().map(|f| field(&f.name, &f.field_type, f.span)).collect::<Vec<_>>(),
+        }),
+        Item::Schema(s) => json!({
+            "name": s.name, "detail": "schema", "kind": 23,
+            "range": range(text, s.span), "selectionRange": range(text, s.name_span),
+            "children": s.fields.iter().map(|f| field(&f.name, &f.field_type, f.span)).collect::<Vec<_>>(),
+        }),
+    }).collect();
+    Value::Array(symbols)
+}
+
+// `DB.` yazıldıysa servisin metotları, değilse tüm builtin'ler ve dosyadaki fonksiyonlar
+// CompletionItemKind: 2 Method, 3 Function
+pub fn completion(text: &str, program: Option<&Program>, at: usize) -> Value {
+    let line_start = text[..at].rfind('\n').map(|i| i + 1).unwrap_or(0);
+    let word = text[line_start..at].rsplit(|c: char| !(c.is_alphanumeric() || c == '_' || c == '.')).next().unwrap_or("");
+    if let Some((service, _)) = word.rsplit_once('.') {
+        let items: Vec<Value> = builtins::BUILTINS.iter()
+            .filter_map(|b| b.name.strip_prefix(service).and_then(|m| m.strip_prefix('.')).map(|method| (b, method)))
+            .map(|(b, method)| json!({ "label": method, "kind": 2, "detail": builtin_signature(b) }))
+            .collect();
+        return Value::Array(items);
+    }
+    let mut items: Vec<Value> = builtins::BUILTINS.iter()
+        .map(|b| json!({ "label": b.name, "kind": 3, "detail": builtin_signature(b) }))
+        .collect();
+    if let Some(program) = program {
+        items.extend(program.functions().filter(|f| local(text, f.span)).map(|f| json!({ "label": f.name, "kind": 3, "detail": signature(f) })));
+    }
+    Value::Array(items)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn positions_count_utf16_units() {
+        // "ş" 2 byte ama tek UTF-16 birimi; "😀" 4 byte, iki birim
+        let text = "let a = 1\nlet ş = \"😀\" + b\n";
+        let b = text.find('b').unwrap();
+        assert_eq!(position(text, b), json!({ "line": 1, "character": 15 }));
+        assert_eq!(offset(text, &json!({ "line": 1, "character": 15 })), b);
+        assert_eq!(position(text, 0), json!({ "line": 0, "character": 0 }));
+    }
+
+    #[test]
+    fn offsets_clamp_to_the_document() {
+        let text = "ab\ncd";
+        // Satır sonunu aşan sütun satırın sonunda, olmayan satır metnin sonunda durur
+        assert_eq!(offset(text, &json!({ "line": 0, "character": 99 })), 2);
+        assert_eq!(offset(text, &json!({ "line": 5, "character": 0 })), text.len());
+        assert_eq!(position(text, 99), json!({ "line": 1, "character": 2 }));
+    }
+
+    #[test]
+    fn diagnostics_carry_range_pass_and_notes() {
+        let text = "fn f() {\n  x\n}";
+        let start = text.find('x').unwrap();
+        let d = Diagnostic::error("scope", "Undefined variable `x`", Span { start, end: start + 1 }).with_note("declare it with `let`");
+        assert_eq!(diagnostic(text, &d), json!({
+            "range": { "start": { "line": 1, "character": 2 }, "end": { "line": 1, "character": 3 } },
+            "severity": 1,
+            "source": "gojo/scope",
+            "message": "Undefined variable `x`\nnote: declare it with `let`",
+        }));
+    }
+}
@@ -0,0 +1,53 @@
+// gojo-lsp: editörler için Language Server (stdio üzerinden LSP).
+// Derleyicinin parser ve analiz modüllerini paylaşır; hepsini kullanmadığı için dead_code susturulur.
+#![allow(dead_code)]
+
+#[path = "../analysis/mod.rs"]
+mod analysis;
+#[path = "../ast.rs"]
+mod ast;
+#[path = "../builtins.rs"]
+mod builtins;
+#[path = "../codegen.rs"]
+mod codegen;
+#[path = "../diagnostics.rs"]
+mod diagnostics;
+#[path = "../driver.rs"]
+mod driver;
+#[path = "../modules.rs"]
+mod modules;
+#[path = "../parser.rs"]
+mod parser;
+
+mod features;
+mod rpc;
+mod server;
+
+use std::io;
+use std::process::exit;
+
+fn main() {
+    let stdin = io::stdin();
+    let mut input = stdin.lock();
+    let stdout = io::stdout();
+    let mut output = stdout.lock();
+    let mut server = server::Server::default();
+    loop {
+        let message = match rpc::read_message(&mut input) {
+            Ok(Some(message)) => message,
+            // İstemci `exit` göndermeden bağlantıyı kapattı
+            Ok(None) => exit(1),
+            Err(e) => {
+                eprintln!("gojo-lsp: {}", e);
+                exit(1);
+            }
+        };
+        for reply in server.handle(message) {
+            if let Err(e) = rpc::write_message(&mut output, &reply) {
+                eprintln!("gojo-lsp: {}", e);
+                exit(1);
+            }
+        }
+        if let Some(code) = server.exit_code { exit(code); }
+    }
+}
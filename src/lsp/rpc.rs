@@ -0,0 +1,44 @@
+use serde_json::{json, Value};
+use std::io::{self, BufRead, Write};
+
+// LSP taşıma katmanı: `Content-Length` başlıklı JSON-RPC 2.0 mesajları
+
+// Girdi kapandıysa None
+pub fn read_message(input: &mut impl BufRead) -> io::Result<Option<Value>> {
+    let mut length = None;
+    loop {
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 { return Ok(None); }
+        let line = line.trim_end();
+        if line.is_empty() { break; }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("Content-Length") { length = value.trim().parse::<usize>().ok(); }
+        }
+    }
+    let length = length.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Content-Length başlığı eksik"))?;
+    let mut body = vec![0; length];
+    input.read_exact(&mut body)?;
+    serde_json::from_slice(&body).map(Some).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+pub fn write_message(output: &mut impl Write, message: &Value) -> io::Result<()> {
+    let body = message.to_string();
+    write!(output, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+    output.flush()
+}
+
+pub fn response(id: Value, result: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "result": result })
+}
+
+pub fn error_response(id: Value, code: i64, message: &str) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
+
+pub fn notification(method: &str, params: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "method": method, "params": params })
+}
+
+// JSON-RPC hata kodları
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_REQUEST: i64 = -32600;
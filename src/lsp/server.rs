@@ -0,0 +1,182 @@
+use crate::analysis::types::TypeTable;
+use crate::ast::{Program, Span};
+use crate::diagnostics::{Diagnostic, SourceFile, SourceMap};
+use crate::driver;
+use crate::features;
+use crate::modules;
+use crate::parser;
+use crate::rpc;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::Path;
+
+// Açık bir belge: her değişiklikte yeniden parse edilip analiz edilir
+struct Document {
+    text: String,
+    // Sözdizimi hatasında okunabilen tanımlar (sembol listesi ve tanıma gitme için).
+    // Import edilen modüllerin tanımları da buradadır; span'leri `sources` ile dosyalarına çözülür.
+    program: Option<Program>,
+    types: TypeTable,
+    sources: SourceMap,
+}
+
+impl Document {
+    fn new(uri: &str, text: String) -> (Self, Vec<Diagnostic>) {
+        if text.trim().is_empty() {
+            return (Self { text, program: None, types: TypeTable::default(), sources: SourceMap::default() }, Vec::new());
+        }
+        // Diskteki belge: import'lar dosyanın dizinine göre çözülür (editördeki metin kaydedilmemiş olabilir)
+        let (program, sources, errors) = match uri.strip_prefix("file://") {
+            Some(path) => modules::load(Path::new(path), text.clone()),
+            None => {
+                let (program, errors) = parser::parse_program(&text);
+                (program, SourceMap::single(SourceFile::new(uri, text.clone())), errors)
+            }
+        };
+        // Yarım program analiz edilmez: atlanan tanımlar eksik olduğu için sahte hatalar üretir
+        let (types, errors) = if errors.is_empty() { driver::check(&program) } else { (TypeTable::default(), errors) };
+        let errors = errors.into_iter().map(|d| Self::local(&text, &program, &sources, d)).collect();
+        (Self { text, program: Some(program), types, sources }, errors)
+    }
+
+    // Başka dosyadaki hata, o modülü getiren ilk import satırında gösterilir
+    fn local(text: &str, program: &Program, sources: &SourceMap, d: Diagnostic) -> Diagnostic {
+        if d.span.start <= text.len() { return d; }
+        let span = program.imports.first().map_or(Span::default(), |i| i.span);
+        Diagnostic { message: format!("{}: {}", sources.locate(d.span), d.message), span, ..d }
+    }
+}
+
+#[derive(Default)]
+pub struct Server {
+    documents: HashMap<String, Document>,
+    shutdown: bool,
+    // `exit` bildirimi geldiğinde süreç bu kodla çıkar
+    pub exit_code: Option<i32>,
+}
+
+impl Server {
+    // Gelen mesaja karşılık gönderilecek mesajlar (yanıt ve/veya bildirimler)
+    pub fn handle(&mut self, message: Value) -> Vec<Value> {
+        let method = message["method"].as_str().unwrap_or("");
+        let params = &message["params"];
+        let Some(id) = message.get("id").cloned() else { return self.notify(method, params) };
+        if method.is_empty() { return Vec::new(); } // istemciden gelen yanıt: beklemediğimiz için yok sayılır
+        if self.shutdown {
+            return vec![rpc::error_response(id, rpc::INVALID_REQUEST, "sunucu kapatılıyor")];
+        }
+        let result = match method {
+            "initialize" => json!({
+                "capabilities": {
+                    // 1: Full (her değişiklikte tüm metin gönderilir)
+                    "textDocumentSync": 1,
+                    "hoverProvider": true,
+                    "definitionProvider": true,
+                    "documentSymbolProvider": true,
+                    "completionProvider": { "triggerCharacters": ["."] },
+                },
+                "serverInfo": { "name": "gojo-lsp", "version": env!("CARGO_PKG_VERSION") },
+            }),
+            "shutdown" => {
+                self.shutdown = true;
+                Value::Null
+            }
+            "textDocument/hover" => self.at(params, |doc, program, at| features::hover(&doc.text, program, &doc.types, at)),
+            "textDocument/definition" => {
+                let uri = params["textDocument"]["uri"].as_str().unwrap_or("");
+                self.at(params, |doc, program, at| features::definition(&doc.text, uri, &doc.sources, program, at))
+            }
+            "textDocument/documentSymbol" => match self.document(params) {
+                Some(Document { text, program: Some(program), .. }) => features::document_symbols(text, program),
+                _ => json!([]),
+            },
+            "textDocument/completion" => match self.document(params) {
+                Some(doc) => features::completion(&doc.text, doc.program.as_ref(), features::offset(&doc.text, &params["position"])),
+                None => json!([]),
+            },
+            _ => return vec![rpc::error_response(id, rpc::METHOD_NOT_FOUND, &format!("desteklenmeyen istek: {}", method))],
+        };
+        vec![rpc::response(id, result)]
+    }
+
+    fn notify(&mut self, method: &str, params: &Value) -> Vec<Value> {
+        let uri = params["textDocument"]["uri"].as_str().unwrap_or("").to_string();
+        match method {
+            "textDocument/didOpen" => {
+                let text = params["textDocument"]["text"].as_str().unwrap_or("").to_string();
+                self.update(uri, text)
+            }
+            "textDocument/didChange" => {
+                // Full senkronizasyon: son değişiklik belgenin tamamıdır
+                let Some(text) = params["contentChanges"].as_array().and_then(|c| c.last()).and_then(|c| c["text"].as_str()) else { return Vec::new() };
+                self.update(uri, text.to_string())
+            }
+            "textDocument/didClose" => {
+                self.documents.remove(&uri);
+                vec![rpc::notification("textDocument/publishDiagnostics", json!({ "uri": uri, "diagnostics": [] }))]
+            }
+            "exit" => {
+                self.exit_code = Some(if self.shutdown { 0 } else { 1 });
+                Vec::new()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    fn update(&mut self, uri: String, text: String) -> Vec<Value> {
+        let (doc, errors) = Document::new(&uri, text);
+        let diagnostics: Vec<Value> = errors.iter().map(|d| features::diagnostic(&doc.text, d)).collect();
+        self.documents.insert(uri.clone(), doc);
+        vec![rpc::notification("textDocument/publishDiagnostics", json!({ "uri": uri, "diagnostics": diagnostics }))]
+    }
+
+    fn document(&self, params: &Value) -> Option<&Document> {
+        self.documents.get(params["textDocument"]["uri"].as_str()?)
+    }
+
+    // Pozisyon alan istekler: belge ve program yoksa sonuç null
+    fn at(&self, params: &Value, f: impl FnOnce(&Document, &Program, usize) -> Value) -> Value {
+        match self.document(params) {
+            Some(doc) => match &doc.program {
+                Some(program) => f(doc, program, features::offset(&doc.text, &params["position"])),
+                None => Value::Null,
+            },
+            None => Value::Null,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn open(server: &mut Server, text: &str) -> Vec<Value> {
+        let params = json!({ "textDocument": { "uri": "untitled:test.gj", "text": text } });
+        let out = server.handle(json!({ "jsonrpc": "2.0", "method": "textDocument/didOpen", "params": params }));
+        assert_eq!(out[0]["method"], "textDocument/publishDiagnostics");
+        out[0]["params"]["diagnostics"].as_array().unwrap().clone()
+    }
+
+    #[test]
+    fn publishes_analysis_errors_at_their_range() {
+        let mut server = Server::default();
+        let diagnostics = open(&mut server, "deterministic fn f() -> i64 {\n    DB.log(\"ö\")\n    return 1\n}");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0]["source"], "gojo/determinism");
+        assert_eq!(diagnostics[0]["range"]["start"], json!({ "line": 1, "character": 4 }));
+
+        // Sözdizimi hataları da yayınlanır
+        let diagnostics = open(&mut server, "deterministic fn f( {");
+        assert!(!diagnostics.is_empty());
+        assert_eq!(diagnostics[0]["source"], "gojo/syntax");
+    }
+
+    #[test]
+    fn clean_and_closed_documents_have_no_diagnostics() {
+        let mut server = Server::default();
+        assert_eq!(open(&mut server, "deterministic fn f() -> i64 { return 1 }"), Vec::<Value>::new());
+        let out = server.handle(json!({ "jsonrpc": "2.0", "method": "textDocument/didClose", "params": { "textDocument": { "uri": "untitled:test.gj" } } }));
+        assert_eq!(out[0]["params"]["diagnostics"], json!([]));
+        assert!(server.documents.is_empty());
+    }
+}
@@ -2,80 +2,224 @@ mod ast;
 mod parser;
 mod codegen;
 mod analysis;
+mod builtins;
+mod diagnostics;
+mod driver;
+mod formatter;
+mod interp;
+mod modules;
+mod repl;
 
+use ast::{Item, Overflow};
 use std::env;
 use std::fs;
-use std::process::Command;
-use std::collections::HashMap;
-use crate::analysis::determinism::{DeterminismAnalyzer, SymbolTable};
-use crate::analysis::taint::TaintAnalyzer;
-use crate::analysis::scope::ScopeAnalyzer;
+use std::path::{Path, PathBuf};
+use std::process::{exit, Command};
 
-fn main() {
+const USAGE: &str = "Kullanim: gojo <build|run|emit-rust> <dosya.gj> [--out-dir <dizin>] [--interp] [--wrapping]\n\
+    \x20      gojo repl\n\
+    \x20      gojo fmt [--check] <dosya.gj>...\n\
+    \x20 build      Cargo projesini üretir ve derler (binary: <out-dir>/target/release/<isim>)\n\
+    \x20 run        derler ve çalıştırır (--interp: derlemeden yorumlayıcıyla çalıştırır)\n\
+    \x20 emit-rust  sadece Cargo projesini üretir\n\
+    \x20 repl       etkileşimli kabuk (:type, :ast, :load)\n\
+    \x20 fmt        dosyaları kanonik biçime getirir (--check: sadece kontrol eder, farklıysa 1 ile çıkar)\n\
+    \x20 --out-dir  çıktı dizini (varsayılan: gojo-out/<isim>)\n\
+    \x20 --wrapping i64 taşması hata yerine sarar (tüm fonksiyonlar `wrapping fn` gibi)";
 
-    let args: Vec<String> = env::args().collect();
-    if args.len() < 2 {
-        println!("Kullanim: gojo <dosya.gojo>");
-        return;
+fn usage() -> ! {
+    println!("{}", USAGE);
+    exit(2)
+}
+
+// gojo fmt: dosyaları yerinde biçimlendirir; --check ile sadece farklı olanları listeler
+// Bayraklar dosyalardan önce ayrılır: `--help` dosya adı sanılmasın. Hatalı kullanımda None
+fn fmt_args(args: &[String]) -> Option<(bool, Vec<&String>)> {
+    let mut check = false;
+    let mut files = Vec::new();
+    for arg in args {
+        match arg.as_str() {
+            "--check" => check = true,
+            _ if arg.starts_with('-') => return None,
+            _ => files.push(arg),
+        }
+    }
+    if files.is_empty() { None } else { Some((check, files)) }
+}
+
+fn fmt(args: &[String]) -> ! {
+    let (check, files) = fmt_args(args).unwrap_or_else(|| usage());
+    let mut failed = false;
+    for filename in files {
+        let content = match fs::read_to_string(filename) {
+            Ok(c) => c,
+            Err(_) => { println!("Dosya okunamadi: {}", filename); failed = true; continue; }
+        };
+        let formatted = match formatter::format_source(&content) {
+            Ok(f) => f,
+            Err(errors) => {
+                for e in errors { print!("{}", e.render(filename, &content)); }
+                failed = true;
+                continue;
+            }
+        };
+        if formatted == content { continue; }
+        if check {
+            println!("Biçimlendirilmemiş: {}", filename);
+            failed = true;
+        } else if let Err(e) = fs::write(filename, formatted) {
+            println!("Dosya yazilamadi ({}): {}", filename, e);
+            failed = true;
+        } else {
+            println!("Biçimlendirildi: {}", filename);
+        }
     }
+    exit(if failed { 1 } else { 0 })
+}
+
+// build/run/emit-rust argümanları
+#[derive(Debug, Default, PartialEq)]
+struct Options {
+    filename: String,
+    out_dir: Option<PathBuf>,
+    use_interp: bool,
+    wrapping: bool,
+}
 
-    let filename = &args[1];
+// Hatalı kullanımda None: çağıran kullanım metnini basar
+fn parse_options(command: &str, args: &[String]) -> Option<Options> {
+    let mut options = Options::default();
+    let mut filename = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--out-dir" => options.out_dir = Some(PathBuf::from(args.next()?)),
+            "--interp" if command == "run" => options.use_interp = true,
+            "--wrapping" => options.wrapping = true,
+            _ if filename.is_none() && !arg.starts_with("--") => filename = Some(arg.clone()),
+            _ => return None,
+        }
+    }
+    options.filename = filename?;
+    Some(options)
+}
 
-    let content = match fs::read_to_string(filename) {
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let (command, rest) = match args.first().map(String::as_str) {
+        Some("build" | "run" | "emit-rust") => (args[0].as_str(), &args[1..]),
+        Some("repl") => {
+            if let Err(e) = repl::run() { println!("{}", e); exit(1); }
+            return;
+        }
+        Some("fmt") => fmt(&args[1..]),
+        Some("-h" | "--help") | None => usage(),
+        // Eski kullanım: `gojo dosya.gj` == `gojo run dosya.gj`
+        Some(_) => ("run", &args[..]),
+    };
+
+    let Options { filename, out_dir, use_interp, wrapping } = parse_options(command, rest).unwrap_or_else(|| usage());
+
+    let content = match fs::read_to_string(&filename) {
         Ok(c) => c,
         Err(_) => {
             println!("Dosya okunamadi!");
-            return;
+            exit(1);
         }
     };
 
-    // 1. PARSER (ARTIK parse_program ÇAĞIRIYORUZ)
-    let (_, functions) = match parser::parse_program(&content) {
-        Ok(res) => res,
-        Err(e) => {
-            println!("Syntax Hatası:\n{:?}", e);
-            return;
+    // 1. PARSER: giriş dosyası ve import ettiği modüller
+    // Çalışma zamanı hataları (taşma, sıfıra bölme) de konumlarını buradaki dosyalardan bulur
+    let (mut program, sources, errors) = modules::load(Path::new(&filename), content);
+    if !errors.is_empty() {
+        for e in &errors { print!("{}", sources.render(e)); }
+        println!("{} hata bulundu.", errors.len());
+        exit(1);
+    }
+    if wrapping {
+        for item in &mut program.items {
+            if let Item::Function(f) = item { f.overflow = Overflow::Wrapping; }
         }
+    }
+    println!("Parser: {} fonksiyon, {} schema, {} struct bulundu.", program.functions().count(), program.schemas().count(), program.structs().count());
+
+    // 2. GÜVENLİK (Tüm fonksiyonları tek tek tara)
+    let types = match driver::analyze(&program) {
+        Ok(t) => t,
+        Err(e) => { print!("{}", sources.render(&e)); exit(1); }
     };
-    println!("Parser: {} fonksiyon bulundu.", functions.len());
 
-    // Sembol tablosunu hazırla (Tüm fonksiyonları kaydet)
-    let mut func_map = HashMap::new();
-    for f in &functions {
-        func_map.insert(f.name.clone(), f.clone());
+    // 3a. YORUMLAYICI: rustc'ye gitmeden aynı runtime ile çalıştır
+    if use_interp {
+        println!("Çalıştırılıyor (yorumlayıcı)...");
+        match interp::run(&program, &types, &sources) {
+            Ok(()) => println!(),
+            Err(e) => { gojo_runtime::report_error(&e); println!("Çalışma zamanı hatası!"); exit(1); }
+        }
+        return;
     }
-    let symbols = SymbolTable { functions: func_map };
 
-    // 2. GÜVENLİK (Tüm fonksiyonları tek tek tara)
-    for func in &functions {
-        if let Err(e) = DeterminismAnalyzer::check(func, &symbols) { println!("DETERMINISM HATASI ({}): {}", func.name, e); return; }
-        if let Err(e) = TaintAnalyzer::check(func) { println!("TAINT HATASI ({}): {}", func.name, e); return; }
-        let mut scope_pass = ScopeAnalyzer::new();
-        if let Err(e) = scope_pass.analyze(func) { println!("SCOPE HATASI ({}): {}", func.name, e); return; }
+    // 3. CODEGEN -> bağımsız Cargo projesi
+    let name = driver::package_name(Path::new(&filename));
+    let out_dir = out_dir.unwrap_or_else(|| PathBuf::from("gojo-out").join(&name));
+    let rust_code = driver::generate(&program, &types, &sources);
+    let main_rs = match driver::emit_project(&out_dir, &name, &rust_code) {
+        Ok(p) => p,
+        Err(e) => { println!("Rust projesi yazilamadi ({}): {}", out_dir.display(), e); exit(1); }
+    };
+    if command == "emit-rust" {
+        println!("Rust kodu: {}", main_rs.display());
+        return;
     }
 
-    // 3. CODEGEN (Listeyi gönder)
-    let mut generator = codegen::Codegen::new();
-    let rust_code = generator.generate(&functions);
+    println!("Derleniyor...");
+    let binary = match driver::build_project(&out_dir, &name) {
+        Ok(b) => b,
+        Err(e) => { println!("{}", e); exit(1); }
+    };
+    if command == "build" {
+        println!("Binary: {}", binary.display());
+        return;
+    }
 
-    let output_path = "src/app.rs";
-    if let Err(_) = fs::write(output_path, rust_code) {
-         println!("Rust dosyasi yazilamadi.");
-         return;
+    println!("Çalıştırılıyor...");
+    match Command::new(&binary).status() {
+        Ok(s) if s.success() => println!(),
+        Ok(s) => { println!("Çalışma zamanı hatası!"); exit(s.code().unwrap_or(1)); }
+        Err(e) => { println!("{} çalıştırılamadı: {}", binary.display(), e); exit(1); }
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    println!("Derleniyor ve Çalıştırılıyor...");
-    
-    let status = Command::new("cargo")
-        .arg("run")
-        .arg("--release")
-        .arg("--quiet")
-        .arg("--bin") 
-        .arg("app")   
-        .status();
-
-    match status {
-        Ok(s) if s.success() => println!(""),
-        _ => println!("Çalışma zamanı hatası!"),
+    fn options(command: &str, args: &[&str]) -> Option<Options> {
+        parse_options(command, &args.iter().map(|a| a.to_string()).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn parses_build_and_run_options() {
+        assert_eq!(options("build", &["a.gj", "--out-dir", "out", "--wrapping"]), Some(Options { filename: "a.gj".into(), out_dir: Some(PathBuf::from("out")), use_interp: false, wrapping: true }));
+        assert_eq!(options("run", &["--interp", "a.gj"]), Some(Options { filename: "a.gj".into(), use_interp: true, ..Options::default() }));
+    }
+
+    #[test]
+    fn rejects_bad_usage() {
+        assert_eq!(options("run", &[]), None);
+        assert_eq!(options("run", &["a.gj", "b.gj"]), None);
+        assert_eq!(options("build", &["a.gj", "--out-dir"]), None);
+        // --interp sadece run ile anlamlıdır; bilinmeyen bayrak dosya adı sanılmaz
+        assert_eq!(options("build", &["--interp", "a.gj"]), None);
+        assert_eq!(options("run", &["--help"]), None);
+    }
+
+    #[test]
+    fn separates_fmt_flags_from_files() {
+        let args: Vec<String> = ["a.gj", "--check", "b.gj"].iter().map(|a| a.to_string()).collect();
+        assert_eq!(fmt_args(&args), Some((true, vec![&args[0], &args[2]])));
+        for bad in [&["--help"][..], &["-h", "a.gj"], &["--check"], &[]] {
+            assert_eq!(fmt_args(&bad.iter().map(|a| a.to_string()).collect::<Vec<_>>()), None, "{:?}", bad);
+        }
     }
-}
\ No newline at end of file
+}
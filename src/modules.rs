@@ -0,0 +1,401 @@
+use crate::ast::*;
+use crate::builtins;
+use crate::diagnostics::{Diagnostic, SourceFile, SourceMap};
+use crate::parser;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// Çok dosyalı programlar: giriş dosyasından başlayarak import/use satırları izlenir ve tüm
+// modüller tek bir Program'da birleştirilir. İçe aktarılan modülün fonksiyonları modül yoluyla
+// nitelenir (lib/payments.gj içindeki `f` -> `lib.payments.f`), böylece analiz pass'leri,
+// yorumlayıcı ve codegen tek sembol tablosuyla çalışır; ama her modül sadece kendi
+// fonksiyonlarını ve içe aktardığı modüllerinkini görür. struct ve schema isimleri ortaktır.
+
+// Yüklenmiş bir modül: fonksiyonlarının öneki ve isimleri
+#[derive(Clone)]
+struct Module {
+    prefix: String,
+    functions: HashSet<String>,
+}
+
+struct Loader {
+    // Giriş dosyasının dizini: `use a.b` buradan a/b.gj olarak çözülür.
+    // Kanonik hali modül yollarının (lib.payments) hesabında kullanılır.
+    root: PathBuf,
+    canonical_root: PathBuf,
+    sources: SourceMap,
+    modules: HashMap<PathBuf, Module>,
+    // Yüklenmekte olan dosyalar (kanonik yol, görünen ad): döngü tespiti için
+    stack: Vec<(PathBuf, String)>,
+    items: Vec<Item>,
+    // struct/schema adı -> tanımlandığı dosya
+    records: HashMap<String, String>,
+    errors: Vec<Diagnostic>,
+}
+
+// Giriş dosyası ve içe aktardığı modüller. Hatalar olsa bile okunabilen program döner;
+// span'ler SourceMap üzerinden dosyalarına çözülür.
+pub fn load(entry: &Path, text: String) -> (Program, SourceMap, Vec<Diagnostic>) {
+    let canonical = fs::canonicalize(entry).unwrap_or_else(|_| entry.to_path_buf());
+    let mut loader = Loader {
+        root: entry.parent().map(Path::to_path_buf).unwrap_or_default(),
+        canonical_root: canonical.parent().map(Path::to_path_buf).unwrap_or_default(),
+        sources: SourceMap::default(),
+        modules: HashMap::new(),
+        stack: Vec::new(),
+        items: Vec::new(),
+        records: HashMap::new(),
+        errors: Vec::new(),
+    };
+    let (_, imports) = loader.load_file(canonical, entry.display().to_string(), text, String::new());
+    let mut errors = loader.errors;
+    errors.sort_by_key(|d| d.span.start);
+    (Program { items: loader.items, imports }, loader.sources, errors)
+}
+
+fn qualify(prefix: &str, name: &str) -> String {
+    if prefix.is_empty() { name.to_string() } else { format!("{}.{}", prefix, name) }
+}
+
+fn is_identifier(s: &str) -> bool {
+    s.starts_with(|c: char| c.is_alphabetic() || c == '_') && s.chars().all(|c| c.is_alphanumeric() || c == '_')
+}
+
+impl Loader {
+    // Dosyayı parse eder, import'larını yükler ve tanımlarını nitelenmiş olarak ekler
+    fn load_file(&mut self, canonical: PathBuf, name: String, text: String, prefix: String) -> (HashSet<String>, Vec<Import>) {
+        let base = self.sources.next_base();
+        let (program, errors) = parser::parse_module(&text, base);
+        self.sources.add(SourceFile::new(name.clone(), text));
+        self.errors.extend(errors);
+
+        self.stack.push((canonical, name.clone()));
+        let mut namespaces: HashMap<String, Module> = HashMap::new();
+        for import in &program.imports {
+            let Some(module) = self.import(&name, import) else { continue };
+            let namespace = import.namespace().to_string();
+            if namespaces.insert(namespace.clone(), module).is_some() {
+                self.errors.push(Diagnostic::error("import", format!("`{}` is imported more than once", namespace), import.span));
+            }
+        }
+        self.stack.pop();
+
+        let functions: HashSet<String> = program.functions().map(|f| f.name.clone()).collect();
+        let mut resolver = Resolver { prefix: &prefix, functions: &functions, namespaces: &namespaces, locals: HashSet::new(), errors: Vec::new() };
+        let mut items = program.items;
+        for item in &mut items {
+            match item {
+                Item::Function(f) => {
+                    f.name = qualify(&prefix, &f.name);
+                    resolver.locals = f.params.iter().map(|p| p.name.clone()).collect();
+                    resolver.visit_block(&mut f.body);
+                }
+                Item::Struct(StructDef { name: record, name_span, .. }) | Item::Schema(SchemaDef { name: record, name_span, .. }) => {
+                    match self.records.get(record.as_str()) {
+                        Some(other) => self.errors.push(Diagnostic::error("import", format!("`{}` is already defined in {}", record, other), *name_span)),
+                        None => { self.records.insert(record.clone(), name.clone()); }
+                    }
+                }
+            }
+        }
+        self.errors.append(&mut resolver.errors);
+        self.items.append(&mut items);
+        (functions, program.imports)
+    }
+
+    // İçe aktarılan dosyayı bulur, gerekirse yükler; daha önce yüklendiyse tekrar okunmaz
+    fn import(&mut self, importer: &str, import: &Import) -> Option<Module> {
+        let path = match &import.target {
+            ImportTarget::File(rel) => Path::new(importer).parent().unwrap_or(Path::new("")).join(rel),
+            ImportTarget::Module(segments) => {
+                let mut path = self.root.clone();
+                path.extend(segments);
+                path.set_extension("gj");
+                path
+            }
+        };
+        let error = |message: String| Diagnostic::error("import", message, import.span);
+        let Ok(canonical) = fs::canonicalize(&path) else {
+            self.errors.push(error(format!("cannot find module `{}`", path.display())));
+            return None;
+        };
+        if let Some(at) = self.stack.iter().position(|(p, _)| *p == canonical) {
+            let cycle: Vec<&str> = self.stack[at..].iter().map(|(_, n)| n.as_str()).chain([self.stack[at].1.as_str()]).collect();
+            self.errors.push(error(format!("import cycle: {}", cycle.join(" -> "))));
+            return None;
+        }
+        if let Some(module) = self.modules.get(&canonical) {
+            return Some(module.clone());
+        }
+
+        // Modül yolu giriş dizinine göredir: lib/payments.gj -> lib.payments
+        let Ok(relative) = canonical.strip_prefix(&self.canonical_root).map(Path::to_path_buf) else {
+            self.errors.push(error(format!("module `{}` is outside the project directory", path.display()))
+                .with_note(format!("imports are resolved inside {}", self.canonical_root.display())));
+            return None;
+        };
+        // Hata mesajlarındaki ad, hangi yoldan import edilirse edilsin aynıdır
+        let name = self.root.join(&relative).display().to_string();
+        let segments: Vec<String> = relative.with_extension("").components().map(|c| c.as_os_str().to_string_lossy().into_owned()).collect();
+        if let Some(bad) = segments.iter().find(|s| !is_identifier(s)) {
+            self.errors.push(error(format!("`{}` is not a valid module name", bad)));
+            return None;
+        }
+        let text = match fs::read_to_string(&canonical) {
+            Ok(text) => text,
+            Err(e) => {
+                self.errors.push(error(format!("cannot read module `{}`: {}", name, e)));
+                return None;
+            }
+        };
+        let prefix = segments.join(".");
+        let (functions, _) = self.load_file(canonical.clone(), name, text, prefix.clone());
+        let module = Module { prefix, functions };
+        self.modules.insert(canonical, module.clone());
+        Some(module)
+    }
+}
+
+// Bir modülün fonksiyon gövdelerindeki çağrıları ve fonksiyon değerlerini niteler:
+//   helper(x)          -> lib.payments.helper(x)   (modülün kendi fonksiyonu)
+//   fees.compute(x)    -> payments.fees.compute(x) (içe aktarılan modül)
+//   xs.map(fees.rate)  -> xs.map(payments.fees.rate)
+struct Resolver<'a> {
+    prefix: &'a str,
+    functions: &'a HashSet<String>,
+    namespaces: &'a HashMap<String, Module>,
+    // Geçerli kapsamda görünen yerel isimler: değişkenler fonksiyon ve modül isimlerini gölgeler
+    locals: HashSet<String>,
+    errors: Vec<Diagnostic>,
+}
+
+impl Resolver<'_> {
+    fn visit_block(&mut self, block: &mut Block) {
+        self.scoped(|r| block.statements.iter_mut().for_each(|s| r.visit_stmt(s)));
+    }
+
+    // Blok, lambda ve match kolu içinde bağlanan isimler kapsam bitince görünmez olur
+    fn scoped(&mut self, visit: impl FnOnce(&mut Self)) {
+        let outer = self.locals.clone();
+        visit(self);
+        self.locals = outer;
+    }
+
+    fn visit_stmt(&mut self, stmt: &mut Statement) {
+        match &mut stmt.kind {
+            StmtKind::Let(LetStmt { name, value, .. }) => {
+                self.visit_expr(value);
+                self.locals.insert(name.clone());
+            }
+            StmtKind::Assign { value, .. } | StmtKind::ExprStmt(value) | StmtKind::Return(Some(value)) => self.visit_expr(value),
+            StmtKind::If { condition, then_block, else_block } => {
+                self.visit_expr(condition);
+                self.visit_block(then_block);
+                if let Some(b) = else_block { self.visit_block(b); }
+            }
+            StmtKind::While { condition, body } => { self.visit_expr(condition); self.visit_block(body); }
+            StmtKind::For { var, start, end, step, body } => {
+                self.visit_expr(start);
+                self.visit_expr(end);
+                if let Some(s) = step { self.visit_expr(s); }
+                self.scoped(|r| {
+                    r.locals.insert(var.clone());
+                    r.visit_block(body);
+                });
+            }
+            StmtKind::ForIn { binding, iterable, body } => {
+                self.visit_expr(iterable);
+                self.scoped(|r| {
+                    r.locals.extend(binding.names().into_iter().map(str::to_string));
+                    r.visit_block(body);
+                });
+            }
+            StmtKind::ScopeBlock { body, .. } => self.visit_block(body),
+            StmtKind::ValidateBlock { on_fail, success_scope, .. } => {
+                if let Some(b) = on_fail { self.visit_block(b); }
+                self.visit_block(success_scope);
+            }
+            StmtKind::ValidateLet { name, on_fail, .. } => {
+                self.visit_block(on_fail);
+                self.locals.insert(name.clone());
+            }
+            StmtKind::Return(None) | StmtKind::AwaitAll => {}
+        }
+    }
+
+    fn visit_expr(&mut self, expr: &mut Expr) {
+        // Önce namespace çağrısı düz çağrıya çevrilir, sonra argümanlar gezilir
+        if let ExprKind::MethodCall(target, method, args) = &mut expr.kind {
+            if let ExprKind::Identifier(namespace) = &target.kind {
+                if let Some(module) = self.namespaces.get(namespace).filter(|_| !self.locals.contains(namespace)) {
+                    if !module.functions.contains(method.as_str()) {
+                        self.errors.push(Diagnostic::error("import", format!("module `{}` has no function `{}`", namespace, method), expr.span));
+                    }
+                    expr.kind = ExprKind::Call(qualify(&module.prefix, method), std::mem::take(args));
+                    if let ExprKind::Call(_, args) = &mut expr.kind { args.iter_mut().for_each(|a| self.visit_expr(a)); }
+                    return;
+                }
+            }
+        }
+        // Namespace üzerinden fonksiyon değeri: `fees.rate` -> `payments.fees.rate`
+        if let ExprKind::Field(target, name) = &expr.kind {
+            if let ExprKind::Identifier(namespace) = &target.kind {
+                if let Some(module) = self.namespaces.get(namespace).filter(|_| !self.locals.contains(namespace)) {
+                    if !module.functions.contains(name.as_str()) {
+                        self.errors.push(Diagnostic::error("import", format!("module `{}` has no function `{}`", namespace, name), expr.span));
+                    }
+                    expr.kind = ExprKind::Identifier(qualify(&module.prefix, name));
+                    return;
+                }
+            }
+        }
+        match &mut expr.kind {
+            // Modülün kendi fonksiyonu değer olarak: `xs.map(helper)`
+            ExprKind::Identifier(name) => {
+                if !self.prefix.is_empty() && self.functions.contains(name.as_str()) && !self.locals.contains(name.as_str()) {
+                    *name = qualify(self.prefix, name);
+                }
+            }
+            ExprKind::Literal(_) | ExprKind::Ctor(_, None) => {}
+            ExprKind::Call(name, args) => {
+                // Noktalı isimler builtin servislerdir (DB.log); diğerleri bu modülün fonksiyonları
+                // ya da modülde tanımlı değilse `len` gibi builtin'ler.
+                // Giriş dosyasının fonksiyonları modüllerden görünmez.
+                let builtin = builtins::lookup(name).is_some() && !self.functions.contains(name.as_str());
+                // Yerel bir değişkenle yapılan çağrı fonksiyon değeri çağrısıdır: `f(x)`
+                let value = !self.functions.contains(name.as_str()) && self.locals.contains(name.as_str());
+                if !self.prefix.is_empty() && !name.contains('.') && !builtin && !value {
+                    if !self.functions.contains(name.as_str()) {
+                        self.errors.push(Diagnostic::error("import", format!("cannot find function `{}` in module `{}`", name, self.prefix), expr.span)
+                            .with_note("functions of other modules are called through their namespace, e.g. `payments.fee(x)`"));
+                    }
+                    *name = qualify(self.prefix, name);
+                }
+                args.iter_mut().for_each(|a| self.visit_expr(a));
+            }
+            ExprKind::Binary(l, _, r) | ExprKind::Index(l, r) => { self.visit_expr(l); self.visit_expr(r); }
+            ExprKind::Slice(arr, from, to) => { self.visit_expr(arr); self.visit_expr(from); self.visit_expr(to); }
+            ExprKind::ArrayLiteral(items) => items.iter_mut().for_each(|a| self.visit_expr(a)),
+            ExprKind::MethodCall(target, _, args) => {
+                self.visit_expr(target);
+                args.iter_mut().for_each(|a| self.visit_expr(a));
+            }
+            ExprKind::Interpolated(parts) => parts.iter_mut().for_each(|p| if let StrPart::Expr(e) = p { self.visit_expr(e) }),
+            ExprKind::Infra(call) => {
+                call.args.iter_mut().for_each(|a| self.visit_expr(a));
+                if let Some(f) = &mut call.config.fallback { self.visit_expr(f); }
+            }
+            ExprKind::Spawn(e) | ExprKind::Await(e) | ExprKind::JsonField(e, _) | ExprKind::Field(e, _) | ExprKind::Ctor(_, Some(e)) | ExprKind::Unary(_, e) => self.visit_expr(e),
+            ExprKind::StructLiteral(_, fields) => fields.iter_mut().for_each(|(_, v)| self.visit_expr(v)),
+            ExprKind::MapLiteral(entries) => entries.iter_mut().for_each(|(k, v)| { self.visit_expr(k); self.visit_expr(v); }),
+            ExprKind::Lambda(params, body) => self.scoped(|r| {
+                r.locals.extend(params.iter().map(|(p, _)| p.clone()));
+                r.visit_expr(body);
+            }),
+            ExprKind::Match(scrutinee, arms) => {
+                self.visit_expr(scrutinee);
+                for arm in arms {
+                    self.scoped(|r| {
+                        if let Pattern::Ctor(_, Some(binding)) = &arm.pattern { r.locals.insert(binding.clone()); }
+                        match &mut arm.body {
+                            ArmBody::Expr(e) => r.visit_expr(e),
+                            ArmBody::Block(b) => r.visit_block(b),
+                        }
+                    });
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Geçici dizinde (yol, içerik) dosyalarından oluşan bir proje; giriş dosyası ilkidir
+    fn project(name: &str, files: &[(&str, &str)]) -> (Program, SourceMap, Vec<String>) {
+        let dir = std::env::temp_dir().join(format!("gojo-modules-{}-{}", name, std::process::id()));
+        for (path, text) in files {
+            let path = dir.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, text).unwrap();
+        }
+        let entry = dir.join(files[0].0);
+        let (program, sources, errors) = load(&entry, files[0].1.to_string());
+        fs::remove_dir_all(&dir).unwrap();
+        (program, sources, errors.into_iter().map(|d| d.message).collect())
+    }
+
+    fn calls(func: &FunctionDef) -> Vec<String> {
+        let StmtKind::Return(Some(Expr { kind: ExprKind::Call(name, args), .. })) = &func.body.statements[0].kind else { panic!("{:?}", func.body) };
+        let mut out = vec![name.clone()];
+        out.extend(args.iter().filter_map(|a| match &a.kind { ExprKind::Call(n, _) => Some(n.clone()), _ => None }));
+        out
+    }
+
+    #[test]
+    fn qualifies_module_functions_and_namespaced_calls() {
+        let (program, sources, errors) = project("ns", &[
+            ("main.gj", "use payments.fees\nimport \"lib/util.gj\"\n\ndeterministic fn f() -> i64 { return fees.compute(util.one()) }\n"),
+            ("payments/fees.gj", "import \"../lib/util.gj\"\ndeterministic fn rate() -> i64 { return 3 }\ndeterministic fn compute(n: i64) -> i64 { return util.one(rate()) }\n"),
+            ("lib/util.gj", "deterministic fn one() -> i64 { return 1 }\n"),
+        ]);
+        assert!(errors.is_empty(), "{:?}", errors);
+        let names: Vec<&str> = program.functions().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, ["lib.util.one", "payments.fees.rate", "payments.fees.compute", "f"]);
+        let by_name = |n: &str| program.functions().find(|f| f.name == n).unwrap();
+        assert_eq!(calls(by_name("f")), ["payments.fees.compute", "lib.util.one"]);
+        assert_eq!(calls(by_name("payments.fees.compute")), ["lib.util.one", "payments.fees.rate"]);
+        // lib/util.gj iki kez import edildi ama bir kez yüklendi
+        let file = sources.lookup(by_name("lib.util.one").span).unwrap().0;
+        assert!(file.name.ends_with("util.gj"), "{}", file.name);
+    }
+
+    #[test]
+    fn qualifies_functions_used_as_values() {
+        let (program, _, errors) = project("values", &[
+            ("main.gj", "use fees
+deterministic fn f(xs: Array<i64>) -> Array<i64> { return xs.map(fees.double) }
+"),
+            ("fees.gj", "deterministic fn double(n: i64) -> i64 { return n * 2 }
+deterministic fn g(xs: Array<i64>, k: fn(i64) -> i64) -> Array<i64> { return xs.map(|double| k(double)).map(double) }
+"),
+        ]);
+        assert!(errors.is_empty(), "{:?}", errors);
+        let source = |n: &str| format!("{:?}", program.functions().find(|f| f.name == n).unwrap().body);
+        assert!(source("f").contains("Identifier(\"fees.double\")"), "{}", source("f"));
+        // `k` yerel bir değişken, lambda parametresi `double` da fonksiyonu gölgeler
+        let g = source("fees.g");
+        assert!(g.contains("Call(\"k\"") && g.contains("Identifier(\"double\")"), "{}", g);
+    }
+
+    #[test]
+    fn reports_cycles_and_unknown_names() {
+        let (_, _, errors) = project("cycle", &[
+            ("main.gj", "use a\nuse missing\ndeterministic fn f() -> i64 { return a.nope() }\n"),
+            ("a.gj", "use b\ndeterministic fn fa() -> i64 { return helper() }\n"),
+            ("b.gj", "use a\ndeterministic fn fb() -> i64 { return 1 }\n"),
+        ]);
+        assert_eq!(errors.len(), 4, "{:?}", errors);
+        assert!(errors[0].starts_with("cannot find module"), "{:?}", errors);
+        assert_eq!(errors[1], "module `a` has no function `nope`");
+        assert_eq!(errors[2], "cannot find function `helper` in module `a`");
+        let cycle: Vec<&str> = errors[3].trim_start_matches("import cycle: ").split(" -> ").map(|p| p.rsplit('/').next().unwrap()).collect();
+        assert_eq!(cycle, ["a.gj", "b.gj", "a.gj"]);
+    }
+
+
+    #[test]
+    fn locals_shadow_namespaces_only_in_their_scope() {
+        let (program, _, errors) = project("shadow", &[
+            ("main.gj", "import \"lib/pay.gj\"\ndeterministic fn f(flag: bool) -> i64 {\n    if flag {\n        let pay = [1, 2]\n        return pay.get(0)\n    }\n    return pay.fee()\n}\n"),
+            ("lib/pay.gj", "deterministic fn fee() -> i64 { return 1 }\n"),
+        ]);
+        assert!(errors.is_empty(), "{:?}", errors);
+        let body = format!("{:?}", program.functions().find(|f| f.name == "f").unwrap().body);
+        // Blok içindeki `pay` yerel dizidir; blok dışında yine modülü gösterir
+        assert!(body.contains("kind: Identifier(\"pay\")") && body.contains("\"get\""), "{}", body);
+        assert!(body.contains("Call(\"lib.pay.fee\""), "{}", body);
+    }
+}
@@ -1,222 +1,1191 @@
 use nom::{
-    bytes::complete::{tag, take_while},
-    character::complete::{alpha1, char, digit1, multispace1, not_line_ending},
-    combinator::{map, map_res, opt, recognize, value, verify},
-    sequence::{delimited, pair, preceded, tuple, terminated},
+    bytes::complete::{self as bytes, take_while},
+    character::complete::{self as chars, alpha1, digit1, multispace1, not_line_ending, satisfy},
+    combinator::{map, map_res, not, opt, recognize, value, verify},
+    sequence::{delimited, pair, preceded, separated_pair, tuple, terminated},
     branch::alt,
-    multi::{separated_list0, many0, many1},
+    multi::{separated_list0, separated_list1, many0, many1},
     IResult,
 };
+use std::cell::{Cell, RefCell};
 use crate::ast::*;
+use crate::diagnostics::{line_col, Diagnostic};
+
+// Span hesabı için: nom her zaman orijinal girdinin bir son ekini (suffix) döndürür,
+// bu yüzden offset = toplam uzunluk - kalan uzunluk. Çok dosyalı programlarda her dosya
+// kendi taban offset'inden başlar (bkz. diagnostics::SourceMap).
+thread_local! {
+    static SOURCE_LEN: Cell<usize> = const { Cell::new(0) };
+    static BASE: Cell<usize> = const { Cell::new(0) };
+    // Hata mesajları için kaynağın kendisi (satır:sütun ve bulunan token)
+    static SOURCE: RefCell<String> = const { RefCell::new(String::new()) };
+    // Girdide en ileri başarısız olunan yer ve orada beklenenler ("`}`", "expression"...)
+    static EXPECTED: RefCell<(usize, Vec<String>)> = const { RefCell::new((0, Vec::new())) };
+    // Kurtarılan (recover edilen) sözdizimi hataları
+    static ERRORS: RefCell<Vec<Diagnostic>> = const { RefCell::new(Vec::new()) };
+    // Koşul konumunda (if/while/for/match başı) `Limit {` struct literal değil, bloğun başıdır
+    static STRUCT_LITERALS: Cell<bool> = const { Cell::new(true) };
+}
+
+pub fn offset(input: &str) -> usize { BASE.with(|b| b.get()) + SOURCE_LEN.with(|l| l.get()) - input.len() }
+
+fn begin(input: &str) { begin_at(input, 0) }
+
+fn begin_at(input: &str, base: usize) {
+    BASE.with(|b| b.set(base));
+    SOURCE_LEN.with(|l| l.set(input.len()));
+    SOURCE.with(|s| *s.borrow_mut() = input.to_string());
+    ERRORS.with(|e| e.borrow_mut().clear());
+    reset_expected();
+}
+
+// Konuma göre sıralı, tekrarsız hatalar (geri izleme aynı bloğu iki kez okuyabilir)
+fn finish() -> Vec<Diagnostic> {
+    let mut errors = ERRORS.with(|e| std::mem::take(&mut *e.borrow_mut()));
+    errors.sort_by_key(|d| d.span.start);
+    errors.dedup_by(|a, b| a.span.start == b.span.start && a.message == b.message);
+    errors
+}
+
+fn report(error: Diagnostic) { ERRORS.with(|e| e.borrow_mut().push(error)); }
+
+fn reset_expected() { EXPECTED.with(|e| *e.borrow_mut() = (0, Vec::new())); }
+
+fn expect(at: usize, what: String) {
+    EXPECTED.with(|e| {
+        let (furthest, items) = &mut *e.borrow_mut();
+        if at > *furthest || items.is_empty() {
+            *furthest = at;
+            items.clear();
+        }
+        if at == *furthest && !items.contains(&what) { items.push(what); }
+    })
+}
+
+// Beklenen token'lar: eşleşmezse hata mesajı için kaydedilir. Bakıp vazgeçilen yerlerde
+// (operatörler, son ekler) kayıt tutmayan `chars::char` / `bytes::tag` kullanılır.
+fn char<'a>(c: char) -> impl FnMut(&'a str) -> IResult<&'a str, char> {
+    move |input: &'a str| chars::char(c)(input).inspect_err(|_| expect(offset(input), format!("`{}`", c)))
+}
+
+fn tag<'a>(t: &'static str) -> impl FnMut(&'a str) -> IResult<&'a str, &'a str> {
+    move |input: &'a str| bytes::tag(t)(input).inspect_err(|_| expect(offset(input), format!("`{}`", t)))
+}
+
+// Daha ilerlemeden başarısız olan bir kuralın içindeki beklentileri tek bir isimle değiştirir
+fn label<'a, O, F>(what: &'static str, mut inner: F) -> impl FnMut(&'a str) -> IResult<&'a str, O>
+where F: FnMut(&'a str) -> IResult<&'a str, O> {
+    move |input: &'a str| {
+        let start = offset(sp::<nom::error::Error<&str>>(input).map_or(input, |(rest, _)| rest));
+        let before = EXPECTED.with(|e| { let e = e.borrow(); if e.0 == start { e.1.len() } else { 0 } });
+        inner(input).inspect_err(|_| {
+            EXPECTED.with(|e| {
+                let (furthest, items) = &mut *e.borrow_mut();
+                if *furthest == start { items.truncate(before); }
+            });
+            expect(start, what.to_string());
+        })
+    }
+}
+
+// `at` konumundaki token (hata mesajında "found ..." kısmı)
+fn token_at(at: usize) -> (String, usize) {
+    let at = at - BASE.with(|b| b.get());
+    SOURCE.with(|s| {
+        let rest = &s.borrow()[at.min(s.borrow().len())..];
+        let Some(first) = rest.chars().next() else { return ("end of file".to_string(), 0) };
+        let len = if is_ident_char(first) { rest.find(|c: char| !is_ident_char(c)).unwrap_or(rest.len()) } else { first.len_utf8() };
+        (format!("`{}`", &rest[..len]), len)
+    })
+}
+
+// En ileri hata konumundan "expected `)` or `,`, found `}`" mesajı
+fn expected_error(input: &str) -> Diagnostic {
+    let here = offset(sp::<nom::error::Error<&str>>(input).map_or(input, |(rest, _)| rest));
+    let (at, items) = EXPECTED.with(|e| e.borrow().clone());
+    let (at, items) = if items.is_empty() || at < here { (here, Vec::new()) } else { (at, items) };
+    let (found, len) = token_at(at);
+    let message = match items.as_slice() {
+        [] => format!("unexpected {}", found),
+        [one] => format!("expected {}, found {}", one, found),
+        [init @ .., last] => format!("expected {} or {}, found {}", init.join(", "), last, found),
+    };
+    Diagnostic::error("syntax", message, Span::new(at, at + len.max(1)))
+}
 
 fn sp<'a, E: nom::error::ParseError<&'a str>>(input: &'a str) -> IResult<&'a str, (), E> {
-    value((), many0(alt((value((), multispace1), value((), pair(tag("//"), not_line_ending))))))(input)
+    value((), many0(alt((value((), multispace1), value((), pair(bytes::tag("//"), not_line_ending))))))(input)
 }
 
-fn ws<'a, F: 'a, O, E: nom::error::ParseError<&'a str>>(inner: F) -> impl FnMut(&'a str) -> IResult<&'a str, O, E>
-where F: FnMut(&'a str) -> IResult<&'a str, O, E> {
+fn ws<'a, F, O, E: nom::error::ParseError<&'a str>>(inner: F) -> impl FnMut(&'a str) -> IResult<&'a str, O, E>
+where F: 'a + FnMut(&'a str) -> IResult<&'a str, O, E> {
     delimited(sp, inner, sp)
 }
 
+// Satır sonundaki `//` yorumunun başladığı yer (string içindekiler hariç)
+// Yığın: `"` metin içi, `{` metnin içindeki `${ ... }` kodu (içinde yine metin olabilir)
+fn comment_start(line: &str) -> Option<usize> {
+    let mut stack = Vec::new();
+    let bytes = line.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        let in_string = stack.last() == Some(&b'"');
+        match bytes[i] {
+            b'\\' if in_string => i += 1,
+            b'"' if in_string => { stack.pop(); }
+            b'"' => stack.push(b'"'),
+            b'$' if in_string && bytes.get(i + 1) == Some(&b'{') => { stack.push(b'{'); i += 1; }
+            b'{' if !stack.is_empty() => stack.push(b'{'),
+            b'}' if !stack.is_empty() => { stack.pop(); }
+            b'/' if stack.is_empty() && bytes.get(i + 1) == Some(&b'/') => return Some(i),
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+// Kaynaktaki tüm `//` yorumları (sp bunları atlar; formatter geri yerleştirir)
+pub fn comments(source: &str) -> Vec<Comment> {
+    let mut out = Vec::new();
+    let mut line_start = 0;
+    for line in source.split_inclusive('\n') {
+        if let Some(pos) = comment_start(line) {
+            let text = line[pos..].trim_end();
+            let start = line_start + pos;
+            out.push(Comment { text: text.to_string(), span: Span::new(start, start + text.len()) });
+        }
+        line_start += line.len();
+    }
+    out
+}
+
+// Tüketilen metnin sonundaki boşluk ve yorumları at (span sadece kodu kapsasın)
+fn trim_trivia(mut s: &str) -> &str {
+    loop {
+        s = s.trim_end();
+        let line_start = s.rfind('\n').map(|i| i + 1).unwrap_or(0);
+        match comment_start(&s[line_start..]) {
+            Some(pos) => s = &s[..line_start + pos],
+            None => return s,
+        }
+    }
+}
+
+// Baştaki boşlukları atlar, inner'ın kapladığı byte aralığını da döndürür
+fn spanned<'a, O, F>(mut inner: F) -> impl FnMut(&'a str) -> IResult<&'a str, (O, Span)>
+where F: FnMut(&'a str) -> IResult<&'a str, O> {
+    move |input: &'a str| {
+        let (input, _) = sp(input)?;
+        let (rest, out) = inner(input)?;
+        let consumed = trim_trivia(&input[..input.len() - rest.len()]);
+        let start = offset(input);
+        Ok((rest, (out, Span::new(start, start + consumed.len()))))
+    }
+}
+
 // YENİ: [1, 2, 3] okuyan fonksiyon
-fn parse_array_literal(input: &str) -> IResult<&str, Expr> {
+fn parse_array_literal(input: &str) -> IResult<&str, ExprKind> {
     map(delimited(
         ws(char('[')),
-        separated_list0(ws(char(',')), parse_expr),
+        separated_list0(ws(char(',')), parse_nested),
         ws(char(']'))
-    ), Expr::ArrayLiteral)(input)
+    ), ExprKind::ArrayLiteral)(input)
 }
 
 fn identifier(input: &str) -> IResult<&str, String> {
-    verify(
-        map(recognize(pair(alt((alpha1, tag("_"))), take_while(|c: char| c.is_alphanumeric() || c == '_'))), |s: &str| s.to_string()),
-        |s: &String| !["if", "else", "let", "while", "for", "in", "by", "scope", "spawn", "call", "json", "validate", "true", "false", "return", "nondeterministic", "deterministic", "fn", "Untrusted", "i64", "Void"].contains(&s.as_str())
-    )(input)
+    label("identifier", verify(
+        map(recognize(pair(alt((alpha1, bytes::tag("_"))), take_while(|c: char| c.is_alphanumeric() || c == '_'))), |s: &str| s.to_string()),
+        |s: &String| !["if", "else", "let", "while", "for", "in", "by", "scope", "spawn", "await", "call", "json", "validate", "true", "false", "return", "nondeterministic", "deterministic", "fn", "schema", "struct", "match", "Ok", "Err", "Some", "None", "Untrusted", "i64", "f64", "u8", "bool", "Void"].contains(&s.as_str())
+    ))(input)
 }
 
+fn is_ident_char(c: char) -> bool { c.is_alphanumeric() || c == '_' }
+
+// Anahtar kelime: devamında isim karakteri gelmemeli (`matches` bir `match` değildir)
+fn keyword<'a>(kw: &'static str) -> impl FnMut(&'a str) -> IResult<&'a str, &'a str> {
+    terminated(tag(kw), not(satisfy(is_ident_char)))
+}
+
+// Servis çağrısı: DB.log, HTTP.get (küçük harfle başlayan `arr.get(0)` bir metot çağrısıdır)
 fn dot_identifier(input: &str) -> IResult<&str, String> {
-    map(recognize(pair(identifier, pair(char('.'), identifier))), |s: &str| s.to_string())(input)
+    map(recognize(pair(type_name, pair(chars::char('.'), identifier))), |s: &str| s.to_string())(input)
 }
 
 fn number(input: &str) -> IResult<&str, i64> {
-    map_res(recognize(pair(opt(char('-')), digit1)), |s: &str| s.parse::<i64>())(input)
+    map_res(recognize(pair(opt(chars::char('-')), digit1)), |s: &str| s.parse::<i64>())(input)
+}
+
+// 1.5, -0.25, 2e10, 1.5e-3: nokta ya da üs şart (`0..3` bir aralık olarak kalır); 1e400 gibi sonsuzlar reddedilir
+fn float(input: &str) -> IResult<&str, f64> {
+    let exponent = || recognize(tuple((alt((chars::char('e'), chars::char('E'))), opt(alt((chars::char('+'), chars::char('-')))), digit1)));
+    verify(map_res(recognize(tuple((
+        opt(chars::char('-')),
+        digit1,
+        alt((recognize(pair(pair(chars::char('.'), digit1), opt(exponent()))), exponent())),
+    ))), |s: &str| s.parse::<f64>()), |f: &f64| f.is_finite())(input)
 }
 
+// `\` sonrası: \n \t \r \0 \" \\ \$ \u{1F600}. Bilinmeyen dizi raporlanır, karakter olduğu gibi alınır.
+fn escape(input: &str) -> (&str, char) {
+    let at = offset(input) - 1;
+    let mut chars = input.chars();
+    let Some(c) = chars.next() else { return (input, '\\') };
+    let simple = match c {
+        'n' => Some('\n'), 't' => Some('\t'), 'r' => Some('\r'), '0' => Some('\0'),
+        '"' => Some('"'), '\\' => Some('\\'), '$' => Some('$'),
+        _ => None,
+    };
+    if let Some(decoded) = simple { return (chars.as_str(), decoded); }
+    if c == 'u' {
+        let rest = chars.as_str();
+        let code = rest.strip_prefix('{').and_then(|r| r.find('}').map(|end| (&r[..end], &r[end + 1..])));
+        if let Some((hex, after)) = code {
+            if let Some(decoded) = u32::from_str_radix(hex, 16).ok().and_then(char::from_u32) { return (after, decoded); }
+            report(Diagnostic::error("syntax", format!("invalid unicode escape `\\u{{{}}}`", hex), Span::new(at, offset(after))));
+            return (after, '\u{FFFD}');
+        }
+    }
+    report(Diagnostic::error("syntax", format!("unknown escape sequence `\\{}`", c), Span::new(at, at + 1 + c.len_utf8()))
+        .with_note("valid escapes are \\n \\t \\r \\0 \\\" \\\\ \\$ and \\u{..}"));
+    (chars.as_str(), c)
+}
+
+// "fib(${n}) = ${sonuc}": metin parçaları ve `${ }` içindeki ifadeler
+fn string_parts(input: &str) -> IResult<&str, Vec<StrPart>> {
+    let (mut rest, _) = chars::char('"')(input)?;
+    let mut parts = Vec::new();
+    let mut text = String::new();
+    loop {
+        let mut chars = rest.chars();
+        match chars.next() {
+            Some('"') => { rest = chars.as_str(); break; }
+            Some('\\') => {
+                let (after, c) = escape(chars.as_str());
+                text.push(c);
+                rest = after;
+            }
+            Some('$') if chars.as_str().starts_with('{') => {
+                if !text.is_empty() { parts.push(StrPart::Text(std::mem::take(&mut text))); }
+                let (after, e) = parse_nested(&rest[2..])?;
+                // `}` sonrası metnin parçasıdır: boşluk yutulmaz
+                let (after, _) = preceded(sp, char('}'))(after)?;
+                parts.push(StrPart::Expr(e));
+                rest = after;
+            }
+            Some(c) => { text.push(c); rest = chars.as_str(); }
+            None => { char('"')(rest)?; }
+        }
+    }
+    if !text.is_empty() || parts.is_empty() { parts.push(StrPart::Text(text)); }
+    Ok((rest, parts))
+}
+
+fn string_expr(input: &str) -> IResult<&str, ExprKind> {
+    label("string", map(string_parts, |mut parts| match parts.as_mut_slice() {
+        [StrPart::Text(s)] => ExprKind::Literal(Literal::Str(std::mem::take(s))),
+        _ => ExprKind::Interpolated(parts),
+    }))(input)
+}
+
+// Sabit metin gereken yerler (json anahtarı, regex): enterpolasyon kabul edilmez
 fn string_literal(input: &str) -> IResult<&str, String> {
-    map(delimited(char('"'), take_while(|c| c != '"'), char('"')), |s: &str| s.to_string())(input)
+    label("string", map_res(string_parts, |mut parts| match parts.as_mut_slice() {
+        [StrPart::Text(s)] => Ok(std::mem::take(s)),
+        _ => Err(()),
+    }))(input)
+}
+
+fn parse_json_field(input: &str) -> IResult<&str, ExprKind> {
+    map(tuple((
+        ws(tag("json")), ws(char('(')), parse_nested, ws(char(',')), ws(string_literal), ws(char(')'))
+    )), |(_, _, source, _, key, _)| ExprKind::JsonField(Box::new(source), key))(input)
+}
+
+// Süre: `200ms`, `2s` ya da birimsiz (ms). u64 milisaniyeye sığmayan süre raporlanır.
+fn duration_ms(input: &str) -> IResult<&str, u64> {
+    let (rest, (digits, unit)) = pair(digit1, opt(alt((bytes::tag("ms"), bytes::tag("s")))))(input)?;
+    let scale = if unit == Some("s") { 1000 } else { 1 };
+    match digits.parse::<u64>().ok().and_then(|n| n.checked_mul(scale)) {
+        Some(ms) => Ok((rest, ms)),
+        None => {
+            let text = &input[..input.len() - rest.len()];
+            report(Diagnostic::error("syntax", format!("duration `{}` is too large", text), Span::new(offset(input), offset(rest)))
+                .with_note(format!("durations are limited to {} ms", u64::MAX)));
+            Ok((rest, u64::MAX))
+        }
+    }
 }
 
-fn parse_json_field(input: &str) -> IResult<&str, Expr> {
+fn parse_backoff(input: &str) -> IResult<&str, Backoff> {
+    alt((
+        map(preceded(ws(tag("fixed")), delimited(ws(char('(')), duration_ms, ws(char(')')))), Backoff::Fixed),
+        map(preceded(ws(tag("exponential")), opt(delimited(ws(char('(')), duration_ms, ws(char(')'))))), |base| {
+            Backoff::Exponential(base.unwrap_or(InfraConfig::DEFAULT_BACKOFF_MS))
+        }),
+    ))(input)
+}
+
+enum InfraEntry { Timeout(u64), Retry(u32), Backoff(Backoff), Fallback(Expr) }
+
+fn parse_infra_entry(input: &str) -> IResult<&str, InfraEntry> {
+    terminated(alt((
+        map(preceded(pair(ws(tag("timeout")), ws(char(':'))), ws(duration_ms)), InfraEntry::Timeout),
+        map(preceded(pair(ws(tag("retry")), ws(char(':'))), ws(map_res(digit1, |s: &str| s.parse::<u32>()))), InfraEntry::Retry),
+        map(preceded(pair(ws(tag("backoff")), ws(char(':'))), parse_backoff), InfraEntry::Backoff),
+        map(preceded(pair(ws(tag("fallback")), ws(char(':'))), parse_nested), InfraEntry::Fallback),
+    )), opt(ws(char(','))))(input)
+}
+
+fn parse_infra_config(input: &str) -> IResult<&str, InfraConfig> {
+    map(delimited(ws(char('{')), many0(parse_infra_entry), ws(char('}'))), |entries| {
+        let mut config = InfraConfig::default();
+        for entry in entries {
+            match entry {
+                InfraEntry::Timeout(ms) => config.timeout_ms = Some(ms),
+                InfraEntry::Retry(n) => config.retry = n,
+                InfraEntry::Backoff(b) => config.backoff = b,
+                InfraEntry::Fallback(e) => config.fallback = Some(Box::new(e)),
+            }
+        }
+        config
+    })(input)
+}
+
+fn parse_infra_expr(input: &str) -> IResult<&str, ExprKind> {
     map(tuple((
-        ws(tag("json")), ws(char('(')), parse_expr, ws(char(',')), ws(string_literal), ws(char(')'))
-    )), |(_, _, source, _, key, _)| Expr::JsonField(Box::new(source), key))(input)
+        ws(tag("call")), identifier, ws(char('.')), identifier, ws(char('(')), separated_list0(ws(char(',')), parse_nested), ws(char(')')),
+        parse_infra_config
+    )), |(_, s, _, m, _, a, _, config)| ExprKind::Infra(Box::new(InfraCall { service: s, method: m, args: a, config })))(input)
+}
+
+fn ctor(input: &str) -> IResult<&str, Ctor> {
+    alt((
+        value(Ctor::Ok, keyword("Ok")),
+        value(Ctor::Err, keyword("Err")),
+        value(Ctor::Some, keyword("Some")),
+        value(Ctor::None, keyword("None")),
+    ))(input)
 }
 
-fn parse_infra_expr(input: &str) -> IResult<&str, Expr> {
+// Ok(x), Err(e), Some(x), None
+fn parse_ctor(input: &str) -> IResult<&str, ExprKind> {
+    let (input, c) = ctor(input)?;
+    if !c.has_payload() { return Ok((input, ExprKind::Ctor(c, None))); }
+    map(delimited(ws(char('(')), parse_nested, ws(char(')'))), move |e| ExprKind::Ctor(c, Some(Box::new(e))))(input)
+}
+
+fn parse_pattern(input: &str) -> IResult<&str, Pattern> {
+    let (input, c) = alt((map(ctor, Some), value(None, keyword("_"))))(input)?;
+    match c {
+        None => Ok((input, Pattern::Wildcard)),
+        Some(c) if !c.has_payload() => Ok((input, Pattern::Ctor(c, None))),
+        // Ok(_) değeri bağlamaz
+        Some(c) => map(delimited(ws(char('(')), identifier, ws(char(')'))), move |b| Pattern::Ctor(c, (b != "_").then_some(b)))(input),
+    }
+}
+
+fn parse_match_arm(input: &str) -> IResult<&str, MatchArm> {
+    map(terminated(spanned(tuple((
+        parse_pattern, ws(tag("=>")),
+        alt((
+            // `{ "a": n }` bir map'tir, blok değil (boş `{}` bloktur)
+            map(verify(map(spanned(parse_map_literal), |(kind, span)| Expr::new(kind, span)), |e| matches!(&e.kind, ExprKind::MapLiteral(entries) if !entries.is_empty())), ArmBody::Expr),
+            map(parse_block, ArmBody::Block),
+            map(parse_nested, ArmBody::Expr),
+        )),
+    ))), opt(ws(char(',')))), |((pattern, _, body), span)| MatchArm { pattern, body, span })(input)
+}
+
+fn parse_match(input: &str) -> IResult<&str, ExprKind> {
     map(tuple((
-        ws(tag("call")), identifier, ws(char('.')), identifier, ws(char('(')), separated_list0(ws(char(',')), parse_expr), ws(char(')')), 
-        ws(char('{')), ws(tag("timeout")), ws(char(':')), number, ws(char('}'))
-    )), |(_, s, _, m, _, a, _, _, _, _, t, _)| Expr::Infra(InfraCall { service: s, method: m, args: a, config: InfraConfig { timeout_ms: t as u64 } }))(input)
+        ws(keyword("match")), parse_condition, ws(char('{')), many1(ws(parse_match_arm)), ws(char('}'))
+    )), |(_, scrutinee, _, arms, _)| ExprKind::Match(Box::new(scrutinee), arms))(input)
 }
 
-fn parse_spawn(input: &str) -> IResult<&str, Expr> {
-    map(preceded(ws(tag("spawn")), parse_expr), |e| Expr::Spawn(Box::new(e)))(input)
+fn parse_spawn(input: &str) -> IResult<&str, ExprKind> {
+    map(preceded(ws(tag("spawn")), parse_expr), |e| ExprKind::Spawn(Box::new(e)))(input)
 }
 
-fn parse_call_expr(input: &str) -> IResult<&str, Expr> {
+// await task: spawn'dan dönen handle'ın sonucunu bekler
+fn parse_await(input: &str) -> IResult<&str, ExprKind> {
+    map(preceded(ws(keyword("await")), parse_expr), |e| ExprKind::Await(Box::new(e)))(input)
+}
+
+// await all: içinde bulunulan scope'un tüm task'larını bekler
+fn parse_await_all(input: &str) -> IResult<&str, StmtKind> {
+    value(StmtKind::AwaitAll, pair(ws(keyword("await")), ws(keyword("all"))))(input)
+}
+
+fn parse_call_expr(input: &str) -> IResult<&str, ExprKind> {
     map(tuple((
-        opt(ws(tag("call"))), 
-        alt((dot_identifier, identifier)), 
-        ws(char('(')), 
-        separated_list0(ws(char(',')), parse_expr), 
+        opt(ws(tag("call"))),
+        alt((dot_identifier, identifier)),
+        ws(chars::char('(')),
+        separated_list0(ws(char(',')), parse_nested),
         ws(char(')'))
-    )), |(_, n, _, a, _)| Expr::Call(n, a))(input)
+    )), |(_, n, _, a, _)| ExprKind::Call(n, a))(input)
 }
 
 fn parse_primary(input: &str) -> IResult<&str, Expr> {
     alt((
-        parse_spawn,
-        parse_infra_expr, 
-        parse_json_field,
-        parse_call_expr,  
-        parse_array_literal, // YENİ: Liste
-        map(number, |n| Expr::Literal(Literal::Int(n))),
-        map(string_literal, |s| Expr::Literal(Literal::Str(s))),
-        map(tag("true"), |_| Expr::Literal(Literal::Bool(true))),
-        map(tag("false"), |_| Expr::Literal(Literal::Bool(false))),
-        map(identifier, Expr::Identifier),
-        delimited(ws(char('(')), parse_expr, ws(char(')'))),
+        map(spanned(alt((
+            parse_spawn,
+            parse_await,
+            parse_match,
+            parse_ctor,
+            parse_infra_expr,
+            parse_json_field,
+            parse_call_expr,
+            parse_array_literal, // YENİ: Liste
+            parse_struct_literal,
+            parse_map_literal,
+            parse_lambda,
+            map(float, |f| ExprKind::Literal(Literal::Float(f))),
+            map(number, |n| ExprKind::Literal(Literal::Int(n))),
+            string_expr,
+            map(tag("true"), |_| ExprKind::Literal(Literal::Bool(true))),
+            map(tag("false"), |_| ExprKind::Literal(Literal::Bool(false))),
+            map(identifier, ExprKind::Identifier),
+        ))), |(kind, span)| Expr::new(kind, span)),
+        delimited(ws(char('(')), parse_nested, ws(char(')'))),
     ))(input)
 }
 
-// YENİ: x[0] gibi index erişimlerini çözen atom
+enum Postfix { Index(Expr), Slice(Expr, Expr), Method(String, Vec<Expr>), Field(String) }
+
+// YENİ: x[0] ve u.name gibi erişimleri çözen atom
 fn parse_atom(input: &str) -> IResult<&str, Expr> {
     let (input, mut expr) = parse_primary(input)?;
-    let (input, indices) = many0(delimited(ws(char('[')), parse_expr, ws(char(']'))))(input)?;
-    for idx in indices {
-        expr = Expr::Index(Box::new(expr), Box::new(idx));
+    let (input, postfixes) = many0(spanned(alt((
+        map(delimited(ws(chars::char('[')), separated_pair(parse_nested, ws(bytes::tag("..")), parse_nested), ws(char(']'))), |(from, to)| Postfix::Slice(from, to)),
+        map(delimited(ws(chars::char('[')), parse_nested, ws(char(']'))), Postfix::Index),
+        map(tuple((chars::char('.'), identifier, ws(chars::char('(')), separated_list0(ws(char(',')), parse_nested), ws(char(')')))),
+            |(_, name, _, args, _)| Postfix::Method(name, args)),
+        map(preceded(chars::char('.'), identifier), Postfix::Field),
+    ))))(input)?;
+    for (postfix, postfix_span) in postfixes {
+        let span = expr.span.to(postfix_span);
+        let kind = match postfix {
+            Postfix::Index(idx) => ExprKind::Index(Box::new(expr), Box::new(idx)),
+            Postfix::Slice(from, to) => ExprKind::Slice(Box::new(expr), Box::new(from), Box::new(to)),
+            Postfix::Method(name, args) => ExprKind::MethodCall(Box::new(expr), name, args),
+            Postfix::Field(name) => ExprKind::Field(Box::new(expr), name),
+        };
+        expr = Expr::new(kind, span);
     }
     Ok((input, expr))
 }
 
-fn parse_factor(input: &str) -> IResult<&str, Expr> {
-    let (input, mut left) = parse_atom(input)?;
-    let (input, ops) = many0(pair(ws(alt((char('*'), char('/')))), parse_atom))(input)?;
-    for (op_char, right) in ops {
-        let op = match op_char { '*' => BinaryOp::Mul, '/' => BinaryOp::Div, _ => unreachable!() };
-        left = Expr::Binary(Box::new(left), op, Box::new(right));
-    }
-    Ok((input, left))
-}
-
-fn parse_term(input: &str) -> IResult<&str, Expr> {
-    let (input, mut left) = parse_factor(input)?;
-    let (input, ops) = many0(pair(ws(alt((char('+'), char('-')))), parse_factor))(input)?;
-    for (op_char, right) in ops {
-        let op = match op_char { '+' => BinaryOp::Add, '-' => BinaryOp::Sub, _ => unreachable!() };
-        left = Expr::Binary(Box::new(left), op, Box::new(right));
-    }
-    Ok((input, left))
-}
-
-fn parse_expr(input: &str) -> IResult<&str, Expr> {
-    let (input, mut left) = parse_term(input)?;
-    let (input, ops) = many0(pair(ws(alt((
-        tag("=="), tag("!="), 
-        tag(">="), tag("<="), // YENİLER (Sıralama önemli)
-        tag(">"), tag("<")
-    ))), parse_term))(input)?;
-    
-    for (op_str, right) in ops {
-        let op = match op_str { 
-            "==" => BinaryOp::Eq, "!=" => BinaryOp::Neq, 
-            ">=" => BinaryOp::Gte, "<=" => BinaryOp::Lte,
-            ">" => BinaryOp::Gt, "<" => BinaryOp::Lt, 
-            _ => unreachable!() 
-        };
-        left = Expr::Binary(Box::new(left), op, Box::new(right));
+// Tip isimleri büyük harfle başlar: `if x { ... }` struct literal sanılmasın
+fn type_name(input: &str) -> IResult<&str, String> {
+    verify(identifier, |s: &String| s.starts_with(|c: char| c.is_ascii_uppercase()))(input)
+}
+
+fn parse_struct_literal(input: &str) -> IResult<&str, ExprKind> {
+    map(tuple((
+        verify(type_name, |_: &str| STRUCT_LITERALS.with(|s| s.get())), ws(chars::char('{')),
+        separated_list0(ws(char(',')), map(tuple((ws(identifier), ws(char(':')), parse_nested)), |(n, _, v)| (n, v))),
+        opt(ws(char(','))), ws(char('}'))
+    )), |(name, _, fields, _, _)| ExprKind::StructLiteral(name, fields))(input)
+}
+
+// { "a": 1, "b": 2 }  (boş map: {})
+fn parse_map_literal(input: &str) -> IResult<&str, ExprKind> {
+    map(tuple((
+        ws(chars::char('{')),
+        separated_list0(ws(char(',')), map(tuple((parse_nested, ws(char(':')), parse_nested)), |(k, _, v)| (k, v))),
+        opt(ws(char(','))), ws(char('}'))
+    )), |(_, entries, _, _)| ExprKind::MapLiteral(entries))(input)
+}
+
+// |x| x * 2, |acc: i64, x| acc + x, || 0
+fn parse_lambda(input: &str) -> IResult<&str, ExprKind> {
+    map(tuple((
+        ws(chars::char('|')),
+        separated_list0(ws(char(',')), pair(ws(identifier), opt(preceded(ws(char(':')), parse_type)))),
+        ws(char('|')),
+        parse_expr
+    )), |(_, params, _, body)| ExprKind::Lambda(params, Box::new(body)))(input)
+}
+
+fn binary(left: Expr, op: BinaryOp, right: Expr) -> Expr {
+    let span = left.span.to(right.span);
+    Expr::new(ExprKind::Binary(Box::new(left), op, Box::new(right)), span)
+}
+
+fn binary_op(input: &str) -> IResult<&str, BinaryOp> {
+    ws(alt((
+        value(BinaryOp::Or, bytes::tag("||")), value(BinaryOp::And, bytes::tag("&&")),
+        value(BinaryOp::Eq, bytes::tag("==")), value(BinaryOp::Neq, bytes::tag("!=")),
+        value(BinaryOp::Gte, bytes::tag(">=")), value(BinaryOp::Lte, bytes::tag("<=")), // Sıralama önemli
+        value(BinaryOp::Gt, bytes::tag(">")), value(BinaryOp::Lt, bytes::tag("<")),
+        value(BinaryOp::Add, chars::char('+')), value(BinaryOp::Sub, chars::char('-')),
+        value(BinaryOp::Mul, chars::char('*')), value(BinaryOp::Div, chars::char('/')), value(BinaryOp::Mod, chars::char('%')),
+    )))(input)
+}
+
+// !ok, -x (-5 gibi sayılar literal olarak kalır)
+fn parse_unary(input: &str) -> IResult<&str, Expr> {
+    if let Ok(result) = parse_atom(input) { return Ok(result); }
+    let (rest, (op, op_span)) = spanned(alt((value(UnaryOp::Not, chars::char('!')), value(UnaryOp::Neg, chars::char('-')))))(input)?;
+    let (rest, operand) = parse_unary(rest)?;
+    let span = op_span.to(operand.span);
+    Ok((rest, Expr::new(ExprKind::Unary(op, Box::new(operand)), span)))
+}
+
+// Öncelik tırmanma: sağ operand yalnızca daha sıkı bağlanan operatörleri alır (sol birleşmeli)
+fn parse_binary(input: &str, min_prec: u8) -> IResult<&str, Expr> {
+    parse_operands(input, min_prec).map(|(rest, (expr, _))| (rest, expr))
+}
+
+// İfadeyle birlikte en dıştaki düğümün parantezsiz bir karşılaştırma olup olmadığını döner;
+// zincir hangi öncelik seviyesinde kurulursa kurulsun (`a == b < c`, `a < b == c`) yakalanır
+fn parse_operands(input: &str, min_prec: u8) -> IResult<&str, (Expr, bool)> {
+    let (mut input, mut left) = parse_unary(input)?;
+    let mut compared = false;
+    loop {
+        let Ok((rest, (op, op_span))) = spanned(binary_op)(input) else { break };
+        let prec = op.precedence();
+        if prec < min_prec { break; }
+        let Ok((rest, (right, right_compared))) = parse_operands(rest, prec + 1) else { break };
+        if op.is_comparison() && (compared || right_compared) {
+            report(Diagnostic::error("syntax", "comparison operators cannot be chained".to_string(), op_span)
+                .with_note("combine the comparisons with `&&`, e.g. `a < b && b < c`, or add parentheses"));
+        }
+        compared = op.is_comparison();
+        left = binary(left, op, right);
+        input = rest;
     }
-    Ok((input, left))
+    Ok((input, (left, compared)))
 }
 
-fn parse_let(input: &str) -> IResult<&str, Statement> {
-    map(tuple((ws(tag("let")), identifier, ws(char('=')), parse_expr)), |(_, n, _, v)| Statement::Let(LetStmt { name: n, value: v }))(input)
+fn parse_expr(input: &str) -> IResult<&str, Expr> { label("expression", |i| parse_binary(i, 1))(input) }
+
+fn struct_literals<'a, O>(allowed: bool, mut parser: impl FnMut(&'a str) -> IResult<&'a str, O>) -> impl FnMut(&'a str) -> IResult<&'a str, O> {
+    move |input| {
+        let outer = STRUCT_LITERALS.with(|s| s.replace(allowed));
+        let result = parser(input);
+        STRUCT_LITERALS.with(|s| s.set(outer));
+        result
+    }
 }
 
-fn parse_assign(input: &str) -> IResult<&str, Statement> {
-    map(tuple((identifier, ws(char('=')), parse_expr)), |(n, _, v)| Statement::Assign { name: n, value: v })(input)
+// `if n < Limit { }`: Rust'taki gibi koşulda struct literal ancak parantez içinde yazılabilir
+fn parse_condition(input: &str) -> IResult<&str, Expr> { struct_literals(false, parse_expr)(input) }
+
+// Parantez, köşeli parantez ve süslü parantez içi yine her ifadeyi alır: `if f(User { id: 1 }) { }`
+fn parse_nested(input: &str) -> IResult<&str, Expr> { struct_literals(true, parse_expr)(input) }
+
+fn parse_let(input: &str) -> IResult<&str, StmtKind> {
+    map(tuple((ws(tag("let")), identifier, opt(preceded(ws(char(':')), parse_type)), ws(char('=')), parse_expr)),
+        |(_, n, ty, _, v)| StmtKind::Let(LetStmt { name: n, ty, value: v }))(input)
 }
 
-fn parse_return(input: &str) -> IResult<&str, Statement> {
+fn parse_assign(input: &str) -> IResult<&str, StmtKind> {
+    map(tuple((identifier, ws(char('=')), parse_expr)), |(n, _, v)| StmtKind::Assign { name: n, value: v })(input)
+}
+
+fn parse_return(input: &str) -> IResult<&str, StmtKind> {
     map(tuple((
         ws(tag("return")),
         opt(parse_expr)
-    )), |(_, expr)| Statement::Return(expr))(input)
+    )), |(_, expr)| StmtKind::Return(expr))(input)
 }
 
-fn parse_while(input: &str) -> IResult<&str, Statement> {
-    map(tuple((ws(tag("while")), parse_expr, ws(char('{')), parse_block_content, ws(char('}')))), |(_, cond, _, stmts, _)| Statement::While { condition: cond, body: Block { statements: stmts } })(input)
+fn parse_while(input: &str) -> IResult<&str, StmtKind> {
+    map(tuple((ws(tag("while")), parse_condition, parse_block)), |(_, cond, body)| StmtKind::While { condition: cond, body })(input)
 }
 
-fn parse_for(input: &str) -> IResult<&str, Statement> {
+fn parse_for(input: &str) -> IResult<&str, StmtKind> {
     map(tuple((
-        ws(tag("for")), identifier, ws(tag("in")), parse_expr, ws(tag("..")), parse_expr,
-        opt(preceded(ws(tag("by")), parse_expr)),
-        ws(char('{')), parse_block_content, ws(char('}'))
-    )), |(_, var, _, start, _, end, step, _, stmts, _)| 
-        Statement::For { var, start, end, step, body: Block { statements: stmts } }
+        ws(tag("for")), identifier, ws(tag("in")), parse_condition, ws(tag("..")), parse_condition,
+        opt(preceded(ws(tag("by")), parse_condition)),
+        parse_block
+    )), |(_, var, _, start, _, end, step, body)|
+        StmtKind::For { var, start, end, step, body }
     )(input)
 }
 
-fn parse_if(input: &str) -> IResult<&str, Statement> {
+// `for x in 0..n` parse_for'dadır; aralık olmayan `for x in xs` buraya düşer
+fn parse_for_in(input: &str) -> IResult<&str, StmtKind> {
     map(tuple((
-        ws(tag("if")), parse_expr, ws(char('{')), parse_block_content, ws(char('}')),
+        ws(tag("for")),
+        alt((
+            map(tuple((ws(chars::char('(')), identifier, ws(char(',')), identifier, ws(char(')')))), |(_, k, _, v, _)| ForBinding::Entry(k, v)),
+            map(separated_pair(identifier, ws(chars::char(',')), identifier), |(i, x)| ForBinding::Indexed(i, x)),
+            map(identifier, ForBinding::Item),
+        )),
+        ws(tag("in")), parse_condition, parse_block
+    )), |(_, binding, _, iterable, body)| StmtKind::ForIn { binding, iterable, body })(input)
+}
+
+fn parse_if(input: &str) -> IResult<&str, StmtKind> {
+    map(tuple((
+        ws(tag("if")), parse_condition, parse_block,
         opt(preceded(ws(tag("else")), alt((
-            map(parse_if, |stmt| match stmt { Statement::If { .. } => vec![stmt], _ => unreachable!() }),
-            delimited(ws(char('{')), parse_block_content, ws(char('}')))
+            map(spanned(parse_if), |(kind, span)| Block { statements: vec![Statement::new(kind, span)], span }),
+            parse_block
         ))))
-    )), |(_, cond, _, then_stmts, _, else_stmts)| {
-        Statement::If { condition: cond, then_block: Block { statements: then_stmts }, else_block: else_stmts.map(|stmts| Block { statements: stmts }) }
+    )), |(_, cond, then_block, else_block)| {
+        StmtKind::If { condition: cond, then_block, else_block }
+    })(input)
+}
+
+fn parse_scope(input: &str) -> IResult<&str, StmtKind> {
+    map(tuple((ws(tag("scope")), identifier, parse_block)), |(_, n, body)| StmtKind::ScopeBlock { name: n, body })(input)
+}
+
+enum ValidateEntry { Schema(String), OnFail(Block), Success(Block) }
+
+// on_fail tek bir ifade (return ...) ya da blok olabilir
+fn parse_on_fail(input: &str) -> IResult<&str, Block> {
+    alt((parse_block, map(parse_statement, |stmt| { let span = stmt.span; Block { statements: vec![stmt], span } })))(input)
+}
+
+fn parse_validate_entry(input: &str) -> IResult<&str, ValidateEntry> {
+    terminated(alt((
+        map(preceded(pair(ws(tag("schema")), ws(char(':'))), ws(identifier)), ValidateEntry::Schema),
+        map(preceded(pair(ws(tag("on_fail")), ws(char(':'))), parse_on_fail), ValidateEntry::OnFail),
+        map(preceded(pair(ws(tag("success")), ws(char(':'))), parse_block), ValidateEntry::Success),
+    )), opt(ws(char(','))))(input)
+}
+
+type ValidateParts = (String, Option<String>, Option<Block>, Option<Block>);
+
+fn parse_validate_parts(input: &str) -> IResult<&str, ValidateParts> {
+    map(tuple((ws(tag("validate")), identifier, ws(char('{')), many0(parse_validate_entry), ws(char('}')))), |(_, target, _, entries, _)| {
+        let (mut schema, mut on_fail, mut success) = (None, None, None);
+        for entry in entries {
+            match entry {
+                ValidateEntry::Schema(s) => schema = Some(s),
+                ValidateEntry::OnFail(b) => on_fail = Some(b),
+                ValidateEntry::Success(b) => success = Some(b),
+            }
+        }
+        (target, schema, on_fail, success)
     })(input)
 }
 
-fn parse_scope(input: &str) -> IResult<&str, Statement> {
-    map(tuple((ws(tag("scope")), identifier, ws(char('{')), parse_block_content, ws(char('}')))), |(_, n, _, s, _)| Statement::ScopeBlock { name: n, body: Block { statements: s } })(input)
+fn parse_validate(input: &str) -> IResult<&str, StmtKind> {
+    map(verify(parse_validate_parts, |(_, _, _, success)| success.is_some()), |(target, schema, on_fail, success)| {
+        StmtKind::ValidateBlock { target, schema, on_fail: on_fail.map(Box::new), success_scope: Box::new(success.unwrap()) }
+    })(input)
 }
 
-fn parse_validate(input: &str) -> IResult<&str, Statement> {
-    map(tuple((ws(tag("validate")), identifier, ws(char('{')), ws(tag("success")), ws(char(':')), ws(char('{')), parse_block_content, ws(char('}')), ws(char('}')))), 
-    |(_, t, _, _, _, _, s, _, _)| Statement::ValidateBlock { target: t, schema: "Schema".to_string(), on_fail: Box::new(Block{statements:vec![]}), success_scope: Box::new(Block { statements: s }) })(input)
+fn parse_validate_let(input: &str) -> IResult<&str, StmtKind> {
+    map(tuple((
+        ws(tag("let")), identifier, ws(char('=')),
+        verify(parse_validate_parts, |(_, _, on_fail, success)| on_fail.is_some() && success.is_none())
+    )), |(_, name, _, (target, schema, on_fail, _))| StmtKind::ValidateLet { name, target, schema, on_fail: Box::new(on_fail.unwrap()) })(input)
 }
 
 fn parse_statement(input: &str) -> IResult<&str, Statement> {
-    alt((parse_let, parse_if, parse_while, parse_for, parse_scope, parse_validate, parse_assign, parse_return, map(terminated(parse_expr, opt(ws(char(';')))), |e| Statement::ExprStmt(e))))(input)
+    label("statement", map(spanned(struct_literals(true, alt((parse_validate_let, parse_let, parse_await_all, parse_if, parse_while, parse_for, parse_for_in, parse_scope, parse_validate, parse_assign, parse_return, map(terminated(parse_expr, opt(ws(chars::char(';')))), StmtKind::ExprStmt))))),
+        |(kind, span)| Statement::new(kind, span)))(input)
+}
+
+// Her deyim kendi beklentileriyle başlar: önceki deyimin bakıp vazgeçtikleri mesaja karışmasın
+fn parse_block_content(mut input: &str) -> IResult<&str, Vec<Statement>> {
+    let mut statements = Vec::new();
+    loop {
+        reset_expected();
+        match ws(parse_statement)(input) {
+            Ok((rest, stmt)) => {
+                statements.push(stmt);
+                input = rest;
+            }
+            Err(nom::Err::Error(_)) => return Ok((input, statements)),
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+// Sıradaki dosya seviyesi tanımın başı
+fn starts_item(input: &str) -> bool {
+    ["deterministic", "nondeterministic", "schema", "struct", "import", "use"].iter().any(|kw| keyword(kw)(input).is_ok())
+}
+
+// Hatalı deyimi atlar: süslü parantez derinliği 0'da satır sonuna ya da bloğu kapatan `}`'e kadar.
+// Kapanmamış `(`/`[` sayılmaz: yoksa tek bir eksik `)` dosyanın geri kalanını yutar.
+fn skip_statement(input: &str) -> &str {
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut escaped = false;
+    for (i, c) in input.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_string => escaped = true,
+            '"' => in_string = !in_string,
+            _ if in_string => {}
+            '/' if depth == 0 && input[i..].starts_with("//") => return &input[input[i..].find('\n').map_or(input.len(), |n| i + n)..],
+            '{' => depth += 1,
+            '}' if depth == 0 => return &input[i..],
+            '}' => depth -= 1,
+            '\n' if depth == 0 => return &input[i..],
+            _ => {}
+        }
+    }
+    ""
 }
 
-fn parse_block_content(input: &str) -> IResult<&str, Vec<Statement>> { many0(ws(parse_statement))(input) }
+// Hatalı deyimler raporlanıp atlanır; blok kapanmadan dosya biterse ya da yeni bir tanım
+// başlarsa "expected `}`" hatasıyla o ana kadar okunan kısım döndürülür
+fn parse_block(input: &str) -> IResult<&str, Block> {
+    let (input, _) = sp(input)?;
+    let start = offset(input);
+    let (mut input, _) = char('{')(input)?;
+    let mut statements = Vec::new();
+    loop {
+        let (rest, mut parsed) = parse_block_content(input)?;
+        statements.append(&mut parsed);
+        let (rest, _) = sp(rest)?;
+        if let Ok((rest, _)) = chars::char::<_, nom::error::Error<&str>>('}')(rest) {
+            return Ok((rest, Block { statements, span: Span::new(start, offset(rest)) }));
+        }
+        if rest.is_empty() || starts_item(rest) {
+            let (line, col) = SOURCE.with(|s| line_col(&s.borrow(), start - BASE.with(|b| b.get())));
+            let at = offset(rest);
+            report(Diagnostic::error("syntax", format!("expected `}}` to close block started at {}:{}", line, col), Span::new(at, at + 1)));
+            return Ok((rest, Block { statements, span: Span::new(start, at) }));
+        }
+        report(expected_error(rest));
+        input = skip_statement(rest);
+    }
+}
 
 fn parse_type(input: &str) -> IResult<&str, TypeRef> {
-    ws(alt((
+    label("type", ws(alt((
         map(tag("Untrusted"), |_| TypeRef::Untrusted),
         map(tag("i64"), |_| TypeRef::Integer),
+        map(keyword("f64"), |_| TypeRef::Float),
+        map(keyword("u8"), |_| TypeRef::Byte),
+        map(keyword("bool"), |_| TypeRef::Bool),
         map(tag("Void"), |_| TypeRef::Void),
+        map(keyword("String"), |_| TypeRef::String),
         // YENİ: Dizi Tipi
         map(tuple((ws(tag("Array")), ws(char('<')), parse_type, ws(char('>')))), |(_, _, t, _)| TypeRef::Array(Box::new(t))),
-        map(identifier, |s| TypeRef::Custom(s))
-    )))(input)
+        map(tuple((ws(tag("Result")), ws(char('<')), parse_type, ws(char(',')), parse_type, ws(char('>')))), |(_, _, ok, _, err, _)| TypeRef::Result(Box::new(ok), Box::new(err))),
+        map(tuple((ws(tag("Option")), ws(char('<')), parse_type, ws(char('>')))), |(_, _, t, _)| TypeRef::Option(Box::new(t))),
+        map(tuple((ws(keyword("Map")), ws(char('<')), parse_type, ws(char(',')), parse_type, ws(char('>')))), |(_, _, k, _, v, _)| TypeRef::Map(Box::new(k), Box::new(v))),
+        // fn(i64, i64) -> i64; dönüş tipi yazılmazsa Void
+        map(tuple((ws(keyword("fn")), ws(char('(')), separated_list0(ws(char(',')), parse_type), ws(char(')')), opt(preceded(ws(tag("->")), parse_type)))),
+            |(_, _, params, _, ret)| TypeRef::Func(params, Box::new(ret.unwrap_or(TypeRef::Void)))),
+        map(identifier, TypeRef::Custom)
+    ))))(input)
+}
+
+fn parse_param(input: &str) -> IResult<&str, Param> {
+    map(spanned(tuple((ws(identifier), ws(char(':')), parse_type))), |((n, _, t), span)| Param { name: n, param_type: t, span })(input)
 }
 
 fn parse_function(input: &str) -> IResult<&str, FunctionDef> {
-    map(tuple((
+    map(spanned(tuple((
         ws(alt((map(tag("deterministic"), |_| Purity::Deterministic), map(tag("nondeterministic"), |_| Purity::Nondeterministic)))),
-        ws(tag("fn")), ws(identifier), ws(char('(')),
-        separated_list0(ws(char(',')), map(tuple((ws(identifier), ws(char(':')), parse_type)), |(n, _, t)| Param { name: n, param_type: t })),
-        ws(char(')')), 
-        opt(preceded(ws(tag("->")), parse_type)), 
-        ws(char('{')), parse_block_content, ws(char('}'))
-    )), |(p, _, n, _, params, _, ret, _, stmts, _)| FunctionDef { name: n, purity: p, params, return_type: ret.map(|r| r).unwrap_or(TypeRef::Void), body: Block { statements: stmts } })(input)
+        map(opt(ws(keyword("wrapping"))), |w| if w.is_some() { Overflow::Wrapping } else { Overflow::Checked }),
+        ws(tag("fn")), spanned(identifier), ws(char('(')),
+        separated_list0(ws(char(',')), parse_param),
+        ws(char(')')),
+        opt(preceded(ws(tag("->")), parse_type)),
+        parse_block
+    ))), |((purity, overflow, _, (name, name_span), _, params, _, ret, body), span)| {
+        FunctionDef { name, purity, overflow, params, return_type: ret.unwrap_or(TypeRef::Void), body, span, name_span }
+    })(input)
+}
+
+fn parse_constraint(input: &str) -> IResult<&str, Constraint> {
+    let range_args = |name: &'static str| tuple((ws(tag(name)), ws(char('(')), ws(number), ws(char(',')), ws(number), ws(char(')'))));
+    alt((
+        map(range_args("len"), |(_, _, min, _, max, _)| Constraint::Len(min, max)),
+        map(range_args("range"), |(_, _, min, _, max, _)| Constraint::Range(min, max)),
+        map(tuple((ws(tag("regex")), ws(char('(')), ws(string_literal), ws(char(')')))), |(_, _, re, _)| Constraint::Regex(re)),
+    ))(input)
+}
+
+fn parse_schema_field(input: &str) -> IResult<&str, SchemaField> {
+    map(spanned(tuple((ws(identifier), ws(char(':')), parse_type, many0(parse_constraint)))), |((name, _, field_type, constraints), span)| SchemaField { name, field_type, constraints, span })(input)
+}
+
+// schema PaymentSchema { amount: i64 range(1, 10000), currency: String len(3, 3) }
+fn parse_schema(input: &str) -> IResult<&str, SchemaDef> {
+    map(spanned(tuple((
+        ws(tag("schema")), spanned(identifier), ws(char('{')),
+        many0(terminated(parse_schema_field, opt(ws(char(','))))),
+        ws(char('}'))
+    ))), |((_, (name, name_span), _, fields, _), span)| SchemaDef { name, fields, span, name_span })(input)
+}
+
+fn parse_struct_field(input: &str) -> IResult<&str, StructField> {
+    map(spanned(tuple((ws(identifier), ws(char(':')), parse_type))), |((name, _, field_type), span)| StructField { name, field_type, span })(input)
+}
+
+// struct User { id: i64, name: String }
+fn parse_struct(input: &str) -> IResult<&str, StructDef> {
+    map(spanned(tuple((
+        ws(tag("struct")), spanned(identifier), ws(char('{')),
+        many0(terminated(parse_struct_field, opt(ws(char(','))))),
+        ws(char('}'))
+    ))), |((_, (name, name_span), _, fields, _), span)| StructDef { name, fields, span, name_span })(input)
+}
+
+// import "lib/payments.gj" | use payments.fees
+// Anahtar kelime ham tag ile denenir: tanım beklenen yerdeki hata mesajına eklenmez
+fn parse_import(input: &str) -> IResult<&str, Import> {
+    let file = preceded(terminated(bytes::tag("import"), not(satisfy(is_ident_char))), map(ws(string_literal), ImportTarget::File));
+    let module = preceded(
+        terminated(bytes::tag("use"), not(satisfy(is_ident_char))),
+        map(ws(label("module path", separated_list1(chars::char('.'), identifier))), ImportTarget::Module),
+    );
+    map(spanned(alt((file, module))), |(target, span)| Import { target, span })(input)
+}
+
+fn parse_item(input: &str) -> IResult<&str, Item> {
+    alt((map(parse_function, Item::Function), map(parse_schema, Item::Schema), map(parse_struct, Item::Struct)))(input)
+}
+
+// Tüm dosya: hatalı tanımlar raporlanıp bir sonraki tanıma atlanır. Hatalar olsa bile
+// okunabilen kısım (araçlar için) ve konuma göre sıralı tüm sözdizimi hataları döner.
+pub fn parse_program(input: &str) -> (Program, Vec<Diagnostic>) { parse_module(input, 0) }
+
+// Modül dosyası: span'ler `base`'den başlar, böylece farklı dosyaların konumları çakışmaz
+pub fn parse_module(input: &str, base: usize) -> (Program, Vec<Diagnostic>) {
+    begin_at(input, base);
+    let mut items = Vec::new();
+    let mut imports = Vec::new();
+    let mut rest = input;
+    loop {
+        rest = sp::<nom::error::Error<&str>>(rest).map_or(rest, |(r, _)| r);
+        if rest.is_empty() { break; }
+        reset_expected();
+        if let Ok((r, import)) = parse_import(rest) {
+            if !items.is_empty() {
+                report(Diagnostic::error("syntax", "imports must come before all definitions", import.span));
+            }
+            imports.push(import);
+            rest = r;
+            continue;
+        }
+        match parse_item(rest) {
+            Ok((r, item)) => {
+                items.push(item);
+                rest = r;
+            }
+            Err(_) => {
+                report(expected_error(rest));
+                rest = skip_item(rest);
+            }
+        }
+    }
+    // Boş dosya: en az bir tanım gerekir
+    if items.is_empty() && imports.is_empty() && ERRORS.with(|e| e.borrow().is_empty()) && parse_item(rest).is_err() {
+        report(expected_error(rest));
+    }
+    (Program { items, imports }, finish())
 }
 
-pub fn parse_program(input: &str) -> IResult<&str, Vec<FunctionDef>> {
-    many1(ws(parse_function))(input)
-}
\ No newline at end of file
+// Hatalı tanımı atlar: bir sonraki satır başı tanıma kadar
+fn skip_item(input: &str) -> &str {
+    let mut lines = input.split_inclusive('\n');
+    let mut at = lines.next().map_or(0, str::len);
+    for line in lines {
+        if starts_item(line.trim_start()) { return &input[at..]; }
+        at += line.len();
+    }
+    ""
+}
+
+// Girdi tamamen tüketilmeli; kurtarılan hatalar varsa ilki döner
+fn complete<T>(input: &str, result: IResult<&str, T>) -> Result<T, Diagnostic> {
+    let first = finish().into_iter().next();
+    match result {
+        Ok((rest, value)) if rest.trim().is_empty() => first.map_or(Ok(value), Err),
+        Ok((rest, _)) => Err(first.unwrap_or_else(|| expected_error(rest))),
+        Err(_) => Err(first.unwrap_or_else(|| expected_error(input))),
+    }
+}
+
+// REPL girdisi: önce tanım, sonra deyim denenir
+pub fn parse_snippet(input: &str) -> Result<Snippet, Diagnostic> {
+    begin(input);
+    complete(input, ws(alt((map(parse_item, Snippet::Item), map(parse_statement, Snippet::Stmt))))(input))
+}
+
+pub fn parse_expression(input: &str) -> Result<Expr, Diagnostic> {
+    begin(input);
+    complete(input, ws(parse_expr)(input))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn messages(source: &str) -> Vec<String> {
+        parse_program(source).1.into_iter().map(|d| d.message).collect()
+    }
+
+    #[test]
+    fn collects_errors_and_keeps_the_rest() {
+        let source = "deterministic fn f(n: i64) -> i64 {\n    let x = \n    return n\n}\n\ndeterministic fn g(a: i64 -> i64 { return a }\n\nnondeterministic fn main(x: Untrusted) {\n    DB.log(\"x\"\n    DB.log(\"y\")\n}\n";
+        let (program, errors) = parse_program(source);
+        let names: Vec<&str> = program.items.iter().map(Item::name).collect();
+        assert_eq!(names, ["f", "main"]);
+        let messages: Vec<&str> = errors.iter().map(|d| d.message.as_str()).collect();
+        assert_eq!(messages, [
+            "expected `validate` or expression, found `return`",
+            "expected `,` or `)`, found `-`",
+            "expected `)`, found `DB`",
+        ]);
+    }
+
+    #[test]
+    fn reports_unclosed_blocks() {
+        let source = "deterministic fn f(n: i64) -> i64 {\n    if n > 1 {\n        return n\n\ndeterministic fn g() -> i64 { return 1 }\n";
+        let (program, errors) = parse_program(source);
+        assert_eq!(program.items.len(), 2);
+        let messages: Vec<&str> = errors.iter().map(|d| d.message.as_str()).collect();
+        assert_eq!(messages, ["expected `}` to close block started at 2:14", "expected `}` to close block started at 1:35"]);
+    }
+
+    // (a || (b && (c < (d + ((e * (-f)) % g)))))
+    fn tree(expr: &Expr) -> String {
+        match &expr.kind {
+            ExprKind::Binary(l, op, r) => format!("({} {} {})", tree(l), op.symbol(), tree(r)),
+            ExprKind::Unary(op, e) => format!("({}{})", op.symbol(), tree(e)),
+            ExprKind::Identifier(name) => name.clone(),
+            ExprKind::Literal(Literal::Int(n)) => n.to_string(),
+            ExprKind::Literal(Literal::Float(f)) => format!("{:?}", f),
+            other => format!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn climbs_operator_precedence() {
+        let parse = |source| tree(&parse_expression(source).unwrap());
+        assert_eq!(parse("a || b && c < d + e * -f % g"), "(a || (b && (c < (d + ((e * (-f)) % g)))))");
+        assert_eq!(parse("a - b - c == !d"), "(((a - b) - c) == (!d))");
+        assert_eq!(parse("-5 - -x"), "(-5 - (-x))");
+        assert_eq!(parse("(a < b) == c"), "((a < b) == c)");
+    }
+
+    #[test]
+    fn rejects_chained_comparisons() {
+        let source = "deterministic fn f(a: i64) -> i64 {\n    if 1 < a < 3 { return 1 }\n    return 0\n}\n";
+        let (program, errors) = parse_program(source);
+        assert_eq!(program.items.len(), 1);
+        let messages: Vec<&str> = errors.iter().map(|d| d.message.as_str()).collect();
+        assert_eq!(messages, ["comparison operators cannot be chained"]);
+        assert_eq!(errors[0].span.start, source.find("< 3").unwrap());
+    }
+
+    #[test]
+    fn rejects_comparison_chains_across_operators() {
+        let errors = |e: &str| {
+            let source = format!("deterministic fn f(a: i64, b: i64, c: i64) -> bool {{\n    return {}\n}}\n", e);
+            parse_program(&source).1.into_iter().map(|d| d.message).collect::<Vec<_>>()
+        };
+        for chain in ["a == b < c", "a != b >= c", "a < b == c", "a + 1 <= b != c * 2", "a == b || b < c == true"] {
+            assert_eq!(errors(chain), ["comparison operators cannot be chained"], "{}", chain);
+        }
+        for ok in ["(a == b) < c", "a == (b < c)", "a < b && b == c", "a + b * c >= c"] {
+            assert!(errors(ok).is_empty(), "{}: {:?}", ok, errors(ok));
+        }
+    }
+
+    #[test]
+    fn decodes_escapes_and_interpolation() {
+        let expr = parse_expression(r#""a\tb\"c\u{e7}\${x}""#).unwrap();
+        assert!(matches!(expr.kind, ExprKind::Literal(Literal::Str(ref s)) if s == "a\tb\"c\u{e7}${x}"), "{:?}", expr.kind);
+        let expr = parse_expression(r#""fib(${n}) = ${f(n) + 1}""#).unwrap();
+        let ExprKind::Interpolated(parts) = expr.kind else { panic!("{:?}", expr.kind) };
+        let shape: Vec<String> = parts.iter().map(|p| match p {
+            StrPart::Text(s) => s.clone(),
+            StrPart::Expr(e) => tree(e),
+        }).collect();
+        assert!(matches!(shape.as_slice(), [a, n, b, _] if a == "fib(" && n == "n" && b == ") = "), "{:?}", shape);
+        let expr = parse_expression(r#""${a} ${ b } c""#).unwrap();
+        let ExprKind::Interpolated(parts) = expr.kind else { panic!("{:?}", expr.kind) };
+        assert!(matches!(parts.as_slice(), [_, StrPart::Text(s), _, StrPart::Text(t)] if s == " " && t == " c"), "{:?}", parts);
+    }
+
+    #[test]
+    fn rejects_unknown_escapes() {
+        let source = "nondeterministic fn main(x: Untrusted) {\n    DB.log(\"a\\qb\")\n}\n";
+        let (_, errors) = parse_program(source);
+        let messages: Vec<&str> = errors.iter().map(|d| d.message.as_str()).collect();
+        assert_eq!(messages, ["unknown escape sequence `\\q`"]);
+        assert_eq!(errors[0].span.start, source.find("\\q").unwrap());
+    }
+
+    #[test]
+    fn parses_imports_before_definitions() {
+        let source = "use payments.fees\nimport \"lib/util.gj\"\n\ndeterministic fn f() -> i64 { return 1 }\nuse late\n";
+        let (program, errors) = parse_program(source);
+        let targets: Vec<&ImportTarget> = program.imports.iter().map(|i| &i.target).collect();
+        assert_eq!(targets, [
+            &ImportTarget::Module(vec!["payments".to_string(), "fees".to_string()]),
+            &ImportTarget::File("lib/util.gj".to_string()),
+            &ImportTarget::Module(vec!["late".to_string()]),
+        ]);
+        assert_eq!(program.imports.iter().map(Import::namespace).collect::<Vec<_>>(), ["fees", "util", "late"]);
+        let messages: Vec<&str> = errors.iter().map(|d| d.message.as_str()).collect();
+        assert_eq!(messages, ["imports must come before all definitions"]);
+    }
+
+    #[test]
+    fn parses_floats_and_primitive_types() {
+        let parse = |source| tree(&parse_expression(source).unwrap());
+        assert_eq!(parse("1.5 * -2.0 + 3e2 - 2.5E-1"), "(((1.5 * -2.0) + 300.0) - 0.25)");
+        let source = "deterministic fn f(a: f64, b: u8, c: bool, d: String) -> f64 {\n    for i in 0..3 { }\n    return a\n}\n";
+        let (program, errors) = parse_program(source);
+        assert!(errors.is_empty(), "{:?}", errors);
+        let f = program.functions().next().unwrap();
+        let types: Vec<&TypeRef> = f.params.iter().map(|p| &p.param_type).collect();
+        assert_eq!(types, [&TypeRef::Float, &TypeRef::Byte, &TypeRef::Bool, &TypeRef::String]);
+        assert_eq!(f.return_type, TypeRef::Float);
+    }
+
+    #[test]
+    fn parses_map_literals_and_for_in() {
+        let expr = parse_expression(r#"{ "a": 1, "b": 2, }"#).unwrap();
+        let ExprKind::MapLiteral(entries) = &expr.kind else { panic!("{:?}", expr.kind) };
+        assert_eq!(entries.len(), 2);
+        let source = "deterministic fn f(m: Map<String, i64>) -> i64 {\n    for (k, v) in m { }\n    let n = match m[\"a\"] {\n        Some(x) => { \"b\": x },\n        None => { },\n    }\n    return 0\n}\n";
+        let (program, errors) = parse_program(source);
+        assert!(errors.is_empty(), "{:?}", errors);
+        let f = program.functions().next().unwrap();
+        assert_eq!(f.params[0].param_type, TypeRef::Map(Box::new(TypeRef::String), Box::new(TypeRef::Integer)));
+        let StmtKind::ForIn { binding, .. } = &f.body.statements[0].kind else { panic!("{:?}", f.body.statements[0].kind) };
+        assert_eq!(binding.names(), ["k", "v"]);
+        let StmtKind::Let(LetStmt { value, .. }) = &f.body.statements[1].kind else { panic!() };
+        let ExprKind::Match(_, arms) = &value.kind else { panic!("{:?}", value.kind) };
+        assert!(matches!(&arms[0].body, ArmBody::Expr(e) if matches!(e.kind, ExprKind::MapLiteral(_))));
+        assert!(matches!(&arms[1].body, ArmBody::Block(_)));
+    }
+
+    #[test]
+    fn parses_slices_and_array_loops() {
+        let parse = |source| tree(&parse_expression(source).unwrap());
+        assert!(parse("xs[1..n - 1]").starts_with("Slice("), "{}", parse("xs[1..n - 1]"));
+        assert!(parse("xs[i + 1]").starts_with("Index("));
+        let source = "deterministic fn f(xs: Array<i64>) -> i64 {\n    for i in 0..3 { }\n    for x in xs { }\n    for i, x in xs[1..3] { }\n    return 0\n}\n";
+        let (program, errors) = parse_program(source);
+        assert!(errors.is_empty(), "{:?}", errors);
+        let kinds: Vec<&StmtKind> = program.functions().next().unwrap().body.statements.iter().map(|s| &s.kind).collect();
+        assert!(matches!(kinds[0], StmtKind::For { .. }));
+        assert!(matches!(kinds[1], StmtKind::ForIn { binding: ForBinding::Item(x), .. } if x == "x"));
+        assert!(matches!(kinds[2], StmtKind::ForIn { binding: ForBinding::Indexed(i, x), iterable: Expr { kind: ExprKind::Slice(..), .. }, .. } if i == "i" && x == "x"));
+    }
+
+    #[test]
+    fn parses_closures_and_function_types() {
+        let parse = |source| tree(&parse_expression(source).unwrap());
+        assert_eq!(parse("xs.fold(0, |acc, x: i64| acc + x * 2)").matches("Lambda").count(), 1);
+        let expr = parse_expression("|acc, x: i64| acc + x * 2").unwrap();
+        let ExprKind::Lambda(params, body) = &expr.kind else { panic!("{:?}", expr.kind) };
+        assert_eq!(params, &[("acc".to_string(), None), ("x".to_string(), Some(TypeRef::Integer))]);
+        assert_eq!(tree(body), "(acc + (x * 2))");
+        assert_eq!(parse("a || b"), "(a || b)");
+        let source = "deterministic fn f(g: fn(i64, bool) -> i64, h: fn()) -> fn(i64) -> i64 {
+    return |x| g(x, true)
+}
+";
+        let (program, errors) = parse_program(source);
+        assert!(errors.is_empty(), "{:?}", errors);
+        let f = program.functions().next().unwrap();
+        assert_eq!(f.params[0].param_type, TypeRef::Func(vec![TypeRef::Integer, TypeRef::Bool], Box::new(TypeRef::Integer)));
+        assert_eq!(f.params[1].param_type, TypeRef::Func(vec![], Box::new(TypeRef::Void)));
+        assert_eq!(f.return_type.to_string(), "fn(i64) -> i64");
+    }
+
+    #[test]
+    fn rejects_trailing_input() {
+        assert_eq!(messages("deterministic fn f() -> i64 { return 1 }\n}"), ["expected `deterministic`, `nondeterministic`, `schema` or `struct`, found `}`"]);
+        assert!(parse_expression("1 + 2 )").is_err());
+        assert!(parse_snippet("let x = 1").is_ok());
+    }
+
+    #[test]
+    fn parses_schemas_and_validate_forms() {
+        let source = "schema Payment {\n    amount: i64 range(1, 10000),\n    currency: String len(3, 3) regex(\"^[A-Z]+$\")\n}\nnondeterministic fn main(raw: Untrusted) {\n    validate raw { schema: Payment, on_fail: return, success: { } }\n    let p = validate raw { schema: Payment, on_fail: { return } }\n}\n";
+        let (program, errors) = parse_program(source);
+        assert!(errors.is_empty(), "{:?}", errors);
+        let schema = program.schemas().next().unwrap();
+        assert_eq!(schema.name, "Payment");
+        assert!(matches!(schema.fields[0].constraints.as_slice(), [Constraint::Range(1, 10000)]));
+        assert!(matches!(schema.fields[1].constraints.as_slice(), [Constraint::Len(3, 3), Constraint::Regex(re)] if re == "^[A-Z]+$"));
+        let kinds: Vec<&StmtKind> = program.functions().next().unwrap().body.statements.iter().map(|s| &s.kind).collect();
+        assert!(matches!(kinds[0], StmtKind::ValidateBlock { target, schema: Some(s), on_fail: Some(_), .. } if target == "raw" && s == "Payment"));
+        assert!(matches!(kinds[1], StmtKind::ValidateLet { name, on_fail, .. } if name == "p" && on_fail.statements.len() == 1));
+    }
+
+    #[test]
+    fn rejects_incomplete_validate() {
+        // Blok biçimi success ister, let biçimi on_fail ister ve success alamaz
+        for body in ["validate raw { schema: P }", "let p = validate raw { schema: P }", "let p = validate raw { on_fail: return, success: { } }"] {
+            let source = format!("nondeterministic fn main(raw: Untrusted) {{\n    {}\n}}\n", body);
+            assert!(!parse_program(&source).1.is_empty(), "{}", body);
+        }
+        assert!(!parse_program("schema P { amount: i64 range(1) }\n").1.is_empty());
+    }
+
+    fn infra_config(source: &str) -> InfraConfig {
+        let expr = parse_expression(source).unwrap();
+        let ExprKind::Infra(call) = expr.kind else { panic!("{:?}", expr.kind) };
+        call.config
+    }
+
+    #[test]
+    fn parses_infra_call_config() {
+        let config = infra_config("call HTTP.get(\"http://x\") { timeout: 2s, retry: 3, backoff: exponential(50ms), fallback: \"{}\" }");
+        assert_eq!((config.timeout_ms, config.retry), (Some(2000), 3));
+        assert_eq!(config.backoff, Backoff::Exponential(50));
+        assert!(matches!(config.fallback.as_deref(), Some(Expr { kind: ExprKind::Literal(Literal::Str(s)), .. }) if s == "{}"));
+        // Birimsiz süre milisaniyedir; yazılmayanlar varsayılanı alır
+        let config = infra_config("call Util.now() { timeout: 250, backoff: fixed(1s) }");
+        assert_eq!((config.timeout_ms, config.retry), (Some(250), 0));
+        assert_eq!(config.backoff, Backoff::Fixed(1000));
+        let config = infra_config("call Util.now() { }");
+        assert_eq!(config.timeout_ms, None);
+        assert_eq!(config.backoff, Backoff::Fixed(InfraConfig::DEFAULT_BACKOFF_MS));
+    }
+
+    #[test]
+    fn rejects_durations_that_overflow() {
+        let source = "nondeterministic fn main() {\n    let t = call Util.now() { timeout: 18446744073709552s }\n}\n";
+        let (_, errors) = parse_program(source);
+        let messages: Vec<&str> = errors.iter().map(|d| d.message.as_str()).collect();
+        assert_eq!(messages, ["duration `18446744073709552s` is too large"]);
+        assert_eq!(errors[0].span.start, source.find("1844").unwrap());
+        assert_eq!(messages_of("call Util.now() { timeout: 99999999999999999999 }"), ["duration `99999999999999999999` is too large"]);
+        assert!(parse_program("nondeterministic fn main() {\n    let t = call Util.now() { timeout: 18446744073709551s }\n}\n").1.is_empty());
+    }
+
+    fn messages_of(expr: &str) -> Vec<String> {
+        messages(&format!("nondeterministic fn main() {{\n    let t = {}\n}}\n", expr))
+    }
+
+
+    #[test]
+    fn conditions_do_not_start_struct_literals() {
+        let body = |source: &str| {
+            let (program, errors) = parse_program(source);
+            assert!(errors.is_empty(), "{}: {:?}", source, errors);
+            let func = program.functions().next().unwrap();
+            func.body.statements[0].kind.clone()
+        };
+        let StmtKind::If { condition, then_block, .. } = body("deterministic fn f(n: i64, Limit: i64) -> i64 {\n    if n < Limit { }\n    return 0\n}\n") else { panic!() };
+        assert_eq!(tree(&condition), "(n < Limit)");
+        assert!(then_block.statements.is_empty());
+        let StmtKind::While { condition, .. } = body("deterministic fn f(n: i64, Max: i64) -> i64 {\n    while n == Max { }\n    return 0\n}\n") else { panic!() };
+        assert_eq!(tree(&condition), "(n == Max)");
+        // Parantez ve argüman listeleri içinde struct literal yine yazılabilir
+        for source in [
+            "deterministic fn f(u: User) -> i64 {\n    if u == (User { id: 1 }) { return 1 }\n    return 0\n}\n",
+            "deterministic fn f(u: User) -> i64 {\n    if same(u, User { id: 1 }) { return 1 }\n    return 0\n}\n",
+            "deterministic fn f(n: i64) -> i64 {\n    for i in 0..[User { id: n }].len() { }\n    return 0\n}\n",
+        ] {
+            let (_, errors) = parse_program(source);
+            assert!(errors.is_empty(), "{}: {:?}", source, errors);
+        }
+    }
+}
@@ -0,0 +1,244 @@
+use crate::ast::*;
+use crate::diagnostics::{SourceFile, SourceMap};
+use crate::driver;
+use crate::modules;
+use crate::interp::{Session, Value};
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+
+// gojo repl: let/ifade/deyim ve fn/struct/schema tanımlarını satır satır kabul eder.
+// Her girdi dosyadaki programla aynı analiz pass'lerinden geçer ve yorumlayıcıyla
+// çalıştırılır; değişkenler ve tanımlar oturum boyunca korunur.
+
+const HELP: &str = "Komutlar:\n\
+    \x20 :type <ifade>    ifadenin tipini gösterir (çalıştırmaz)\n\
+    \x20 :ast <ifade>     ifadenin AST'sini gösterir\n\
+    \x20 :load <dosya>    dosyadaki fn/struct/schema tanımlarını yükler\n\
+    \x20 :help            bu mesaj\n\
+    \x20 :quit            çıkış";
+
+struct Repl {
+    items: Vec<Item>,
+    // Değişken tanımlayan deyimler: sonraki girdiler bunların tipleriyle denetlenir
+    bindings: Vec<Statement>,
+    session: Session,
+}
+
+pub fn run() -> Result<(), String> {
+    let mut repl = Repl { items: Vec::new(), bindings: Vec::new(), session: Session::new()? };
+    println!("Gojo REPL (komutlar için :help, çıkmak için :quit)");
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    loop {
+        // Süslü parantezler kapanana kadar girdi devam eder (çok satırlı fn tanımları)
+        let mut input = String::new();
+        loop {
+            print!("{}", if input.is_empty() { "gojo> " } else { "  ... " });
+            io::stdout().flush().map_err(|e| e.to_string())?;
+            match lines.next() {
+                Some(line) => input.push_str(&line.map_err(|e| e.to_string())?),
+                None => { println!(); return Ok(()); }
+            }
+            input.push('\n');
+            if open_braces(&input) <= 0 { break; }
+        }
+
+        let input = input.trim();
+        match input.split_once(char::is_whitespace).unwrap_or((input, "")) {
+            ("", _) => {}
+            (":quit" | ":q", _) => return Ok(()),
+            (":help", _) => println!("{}", HELP),
+            (":type", expr) => repl.show_type(expr.trim()),
+            (":ast", expr) => match driver::parse_expr(expr.trim()) {
+                Ok(e) => println!("{:#?}", e),
+                Err(d) => print!("{}", d.render("<repl>", expr.trim())),
+            },
+            (":load", path) => repl.load(path.trim()),
+            (cmd, _) if cmd.starts_with(':') => println!("Bilinmeyen komut: {} (:help)", cmd),
+            _ => repl.eval(input),
+        }
+    }
+}
+
+// Metin içindekiler hariç açık `{` sayısı
+fn open_braces(input: &str) -> i32 {
+    let mut depth = 0;
+    let mut in_string = false;
+    for c in input.chars() {
+        match c {
+            '"' => in_string = !in_string,
+            '{' if !in_string => depth += 1,
+            '}' if !in_string => depth -= 1,
+            _ => {}
+        }
+    }
+    depth
+}
+
+impl Repl {
+    fn program(&self) -> Program { Program { items: self.items.clone(), imports: Vec::new() } }
+
+    // Girdi, önceki tanımlarla birlikte sentetik bir fonksiyonun gövdesi olur
+    fn snippet(&self, stmt: Statement) -> FunctionDef {
+        let span = stmt.span;
+        let mut statements = self.bindings.clone();
+        statements.push(stmt);
+        FunctionDef {
+            name: "repl".to_string(),
+            purity: Purity::Nondeterministic,
+            overflow: Overflow::Checked,
+            params: Vec::new(),
+            return_type: TypeRef::Void,
+            body: Block { statements, span },
+            span,
+            name_span: span,
+        }
+    }
+
+    fn eval(&mut self, source: &str) {
+        match driver::parse_snippet(source) {
+            Ok(Snippet::Item(item)) => self.define(vec![item], &SourceMap::single(SourceFile::new("<repl>", source))),
+            Ok(Snippet::Stmt(stmt)) => self.exec(stmt, source),
+            Err(d) => print!("{}", d.render("<repl>", source)),
+        }
+    }
+
+    // Tanımlar aynı isimli eskisinin yerine geçer; program bütün olarak yeniden analiz edilir
+    fn define(&mut self, new_items: Vec<Item>, sources: &SourceMap) {
+        let mut items = self.items.clone();
+        for item in &new_items {
+            match items.iter().position(|i| i.name() == item.name()) {
+                Some(pos) => items[pos] = item.clone(),
+                None => items.push(item.clone()),
+            }
+        }
+        let program = Program { items, imports: Vec::new() };
+        if let Err(d) = driver::analyze(&program) {
+            print!("{}", sources.render(&d));
+            return;
+        }
+        self.items = program.items;
+        for item in &new_items {
+            let kind = match item { Item::Function(_) => "fn", Item::Schema(_) => "schema", Item::Struct(_) => "struct" };
+            println!("{} {} tanımlandı", kind, item.name());
+        }
+    }
+
+    fn exec(&mut self, stmt: Statement, source: &str) {
+        let program = self.program();
+        let (types, ty) = match driver::analyze_snippet(&program, &self.snippet(stmt.clone())) {
+            Ok(checked) => checked,
+            Err(d) => { print!("{}", d.render("<repl>", source)); return; }
+        };
+        match self.session.exec(&program, &types, &stmt) {
+            Ok(Some(Value::Void)) => {}
+            Ok(Some(value)) => match ty {
+                Some(t) => println!("{:?} : {}", value, t),
+                None => println!("{:?}", value),
+            },
+            Ok(None) => {
+                let name = match &stmt.kind {
+                    StmtKind::Let(l) => {
+                        let t = l.ty.clone().or_else(|| types.get(&l.value).cloned());
+                        Some((l.name.clone(), t))
+                    }
+                    StmtKind::ValidateLet { name, schema, .. } => Some((name.clone(), Some(schema.clone().map(TypeRef::Custom).unwrap_or(TypeRef::String)))),
+                    _ => None,
+                };
+                if let Some((name, t)) = name {
+                    let value = self.session.get(&name).map(|v| format!("{:?}", v)).unwrap_or_default();
+                    match t {
+                        Some(t) => println!("{}: {} = {}", name, t, value),
+                        None => println!("{} = {}", name, value),
+                    }
+                    self.bindings.push(stmt);
+                }
+            }
+            Err(e) => gojo_runtime::report_error(&e),
+        }
+    }
+
+    fn show_type(&self, source: &str) {
+        let expr = match driver::parse_expr(source) {
+            Ok(e) => e,
+            Err(d) => { print!("{}", d.render("<repl>", source)); return; }
+        };
+        let span = expr.span;
+        match driver::analyze_snippet(&self.program(), &self.snippet(Statement::new(StmtKind::ExprStmt(expr), span))) {
+            Ok((_, Some(t))) => println!("{}", t),
+            Ok((_, None)) => println!("(tip bilinmiyor)"),
+            Err(d) => print!("{}", d.render("<repl>", source)),
+        }
+    }
+
+    fn load(&mut self, path: &str) {
+        let content = match fs::read_to_string(path) {
+            Ok(c) => c,
+            Err(e) => { println!("Dosya okunamadi ({}): {}", path, e); return; }
+        };
+        // Dosyanın import ettiği modüller de yüklenir (fonksiyonları `payments.f` adıyla)
+        let (program, sources, errors) = modules::load(Path::new(path), content);
+        if errors.is_empty() {
+            self.define(program.items, &sources);
+        } else {
+            errors.iter().for_each(|d| print!("{}", sources.render(d)));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn repl() -> Repl { Repl { items: Vec::new(), bindings: Vec::new(), session: Session::new().unwrap() } }
+
+    fn int(repl: &Repl, name: &str) -> Option<i64> {
+        match repl.session.get(name) { Some(Value::Int(n)) => Some(n), _ => None }
+    }
+
+    #[test]
+    fn counts_open_braces_outside_strings() {
+        assert_eq!(open_braces("deterministic fn f() -> i64 {\n"), 1);
+        assert_eq!(open_braces("let s = \"{{\""), 0);
+        assert_eq!(open_braces("if true { DB.log(\"}\") }"), 0);
+    }
+
+    #[test]
+    fn keeps_bindings_and_definitions_between_inputs() {
+        let mut repl = repl();
+        repl.eval("deterministic fn double(n: i64) -> i64 { return n * 2 }");
+        repl.eval("let x = double(21)");
+        repl.eval("let y = x + 1");
+        assert_eq!((int(&repl, "x"), int(&repl, "y")), (Some(42), Some(43)));
+        assert_eq!(repl.bindings.len(), 2);
+
+        // Aynı isimli tanım eskisinin yerine geçer
+        repl.eval("deterministic fn double(n: i64) -> i64 { return n + n + 1 }");
+        repl.eval("let z = double(1)");
+        assert_eq!(int(&repl, "z"), Some(3));
+        assert_eq!(repl.items.len(), 1);
+    }
+
+    #[test]
+    fn rejected_inputs_leave_the_session_unchanged() {
+        let mut repl = repl();
+        repl.eval("let x = 1");
+        repl.eval("let y = x + \"a\"");
+        repl.eval("let w = undefined + 1");
+        repl.eval("let v = (1 +");
+        assert!(["y", "w", "v"].iter().all(|name| repl.session.get(name).is_none()));
+        assert_eq!(repl.bindings.len(), 1);
+
+        // Analizden geçmeyen tanım eskisini silmez
+        repl.eval("deterministic fn f() -> i64 { return 1 }");
+        repl.eval("deterministic fn f() -> i64 { DB.log(1)\n return 2 }");
+        repl.eval("let r = f()");
+        assert_eq!(int(&repl, "r"), Some(1));
+
+        // Çalışma zamanı hatası değişkeni tanımlamaz
+        repl.eval("let big = 9223372036854775807 + x");
+        assert!(repl.session.get("big").is_none());
+        assert_eq!(repl.bindings.len(), 2);
+    }
+}
@@ -0,0 +1,123 @@
+// Dizi erişimi. Derlenmiş kod `arr[i]` için `Site::checked("run.gj:3:12").index(&arr, i)` üretir:
+// sınır dışı ve negatif indeksler (sondan sayma yoktur) panic yerine konumlu bir Gojo hatasıdır.
+// `arr.get(i)` aynı kuralla Option döndürür; `arr[1..3]` dilimi de aynı şekilde denetlenir.
+
+use crate::ops::Site;
+
+pub fn index<T>(items: &[T], i: i64) -> Result<&T, String> {
+    usize::try_from(i).ok().and_then(|at| items.get(at))
+        .ok_or_else(|| format!("index out of bounds: the len is {} but the index is {}", items.len(), i))
+}
+
+// Rust'ın dilim kuralı: [from, to) aralığı, from <= to <= len
+pub fn slice<T>(items: &[T], from: i64, to: i64) -> Result<&[T], String> {
+    let len = items.len();
+    let Ok(start) = usize::try_from(from) else { return Err(format!("range start index {} out of range for slice of length {}", from, len)) };
+    let end = match usize::try_from(to) {
+        Ok(end) if end <= len => end,
+        _ => return Err(format!("range end index {} out of range for slice of length {}", to, len)),
+    };
+    if start > end { return Err(format!("slice index starts at {} but ends at {}", from, to)); }
+    Ok(&items[start..end])
+}
+
+impl Site {
+    #[inline(always)]
+    pub fn index<T: Clone>(self, items: &[T], i: i64) -> T {
+        match index(items, i) {
+            Ok(v) => v.clone(),
+            Err(msg) => crate::task::runtime_error(format!("{} at {}", msg, self.at)),
+        }
+    }
+
+    #[inline(always)]
+    pub fn slice<T: Clone>(self, items: &[T], from: i64, to: i64) -> Vec<T> {
+        match slice(items, from, to) {
+            Ok(s) => s.to_vec(),
+            Err(msg) => crate::task::runtime_error(format!("{} at {}", msg, self.at)),
+        }
+    }
+}
+
+// Gojo fonksiyon değerleri `Arc<dyn Fn(..) -> R + Send + Sync>` olarak taşınır: kopyalanabilir ve task'lara verilebilir
+pub use std::sync::Arc;
+
+// Gojo dizi metotları (Vec<T> üzerinde)
+pub trait GojoArray<T> {
+    fn g_get(&self, i: i64) -> Option<T>;
+    fn g_contains(&self, item: T) -> bool where T: PartialEq;
+    fn g_push(&mut self, item: T);
+    fn g_pop(&mut self) -> Option<T>;
+    fn g_sort(&mut self) where T: Ord;
+    fn g_reverse(&mut self);
+    // Elemanlar fonksiyona kopya olarak verilir; dizi değişmez
+    fn g_map<U>(&self, f: impl Fn(T) -> U) -> Vec<U>;
+    fn g_filter(&self, f: impl Fn(T) -> bool) -> Vec<T>;
+    fn g_fold<A>(&self, init: A, f: impl Fn(A, T) -> A) -> A;
+}
+
+impl<T: Clone> GojoArray<T> for Vec<T> {
+    fn g_get(&self, i: i64) -> Option<T> { index(self, i).ok().cloned() }
+    fn g_contains(&self, item: T) -> bool where T: PartialEq { self.contains(&item) }
+    fn g_push(&mut self, item: T) { self.push(item) }
+    fn g_pop(&mut self) -> Option<T> { self.pop() }
+    fn g_sort(&mut self) where T: Ord { self.sort() }
+    fn g_reverse(&mut self) { self.reverse() }
+    fn g_map<U>(&self, f: impl Fn(T) -> U) -> Vec<U> { self.iter().cloned().map(f).collect() }
+    fn g_filter(&self, f: impl Fn(T) -> bool) -> Vec<T> { self.iter().filter(|x| f((*x).clone())).cloned().collect() }
+    fn g_fold<A>(&self, init: A, f: impl Fn(A, T) -> A) -> A { self.iter().cloned().fold(init, f) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_negative_and_out_of_range_indexes() {
+        let items = vec![10, 20, 30];
+        assert_eq!(index(&items, 2), Ok(&30));
+        assert_eq!(index(&items, 3), Err("index out of bounds: the len is 3 but the index is 3".to_string()));
+        assert_eq!(index(&items, -1), Err("index out of bounds: the len is 3 but the index is -1".to_string()));
+    }
+
+    #[test]
+    fn get_returns_option() {
+        let items = vec!["a".to_string()];
+        assert_eq!(items.g_get(0), Some("a".to_string()));
+        assert_eq!(items.g_get(-1), None);
+        assert_eq!(items.g_get(1), None);
+    }
+
+    #[test]
+    fn slices_follow_rust_rules() {
+        let items = vec![10, 20, 30];
+        assert_eq!(slice(&items, 1, 3), Ok(&[20, 30][..]));
+        assert_eq!(slice(&items, 3, 3), Ok(&[][..]));
+        assert_eq!(slice(&items, -1, 2), Err("range start index -1 out of range for slice of length 3".to_string()));
+        assert_eq!(slice(&items, 0, 4), Err("range end index 4 out of range for slice of length 3".to_string()));
+        assert_eq!(slice(&items, 2, 1), Err("slice index starts at 2 but ends at 1".to_string()));
+    }
+
+    #[test]
+    fn methods_modify_in_place() {
+        let mut items = vec![3, 1, 2];
+        items.g_push(0);
+        assert!(items.g_contains(0));
+        items.g_sort();
+        assert_eq!(items, [0, 1, 2, 3]);
+        items.g_reverse();
+        assert_eq!(items.g_pop(), Some(0));
+        assert_eq!(items, [3, 2, 1]);
+        assert_eq!(Vec::<i64>::new().g_pop(), None);
+    }
+
+    #[test]
+    fn higher_order_methods_take_function_values() {
+        let items = vec![1, 2, 3, 4];
+        let double: Arc<dyn Fn(i64) -> i64 + Send + Sync> = Arc::new(|x| x * 2);
+        assert_eq!(items.g_map(&*double), [2, 4, 6, 8]);
+        assert_eq!(items.g_filter(|x| x % 2 == 0), [2, 4]);
+        assert_eq!(items.g_fold(String::new(), |acc, x| format!("{}{}", acc, x)), "1234");
+        assert_eq!(items, [1, 2, 3, 4]);
+    }
+}
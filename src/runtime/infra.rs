@@ -0,0 +1,90 @@
+use crate::color::{RESET, YELLOW};
+use std::future::Future;
+use std::time::Duration;
+
+// ALTYAPI ÇAĞRISI (call S.m(...) { timeout, retry, backoff, fallback })
+#[derive(Debug, Clone, Copy)]
+pub enum Backoff { Fixed(u64), Exponential(u64) }
+
+impl Backoff {
+    // `attempt`. yeniden denemeden önce beklenecek süre (ms), attempt >= 1
+    pub fn delay_ms(self, attempt: u32) -> u64 {
+        match self {
+            Backoff::Fixed(ms) => ms,
+            Backoff::Exponential(base) => base.saturating_mul(1u64 << (attempt - 1).min(32)),
+        }
+    }
+}
+
+// Servis sonucu Result<T, String>'e çevrilir: zaman aşımı ile servisin kendi hatası tek tipte birleşir
+pub trait InfraOutput { type Ok; fn into_result(self) -> Result<Self::Ok, String>; }
+impl InfraOutput for () { type Ok = (); fn into_result(self) -> Result<(), String> { Ok(self) } }
+impl InfraOutput for i64 { type Ok = i64; fn into_result(self) -> Result<i64, String> { Ok(self) } }
+impl InfraOutput for bool { type Ok = bool; fn into_result(self) -> Result<bool, String> { Ok(self) } }
+impl InfraOutput for String { type Ok = String; fn into_result(self) -> Result<String, String> { Ok(self) } }
+impl<T> InfraOutput for Result<T, String> { type Ok = T; fn into_result(self) -> Result<T, String> { self } }
+
+// Her deneme timeout ile sınırlanır; zaman aşımı ya da servis hatasında yeniden denenir
+pub async fn infra_call<R: InfraOutput, F, Fut>(name: &str, timeout_ms: u64, retry: u32, backoff: Backoff, mut call: F) -> Result<R::Ok, String>
+where F: FnMut() -> Fut, Fut: Future<Output = R> {
+    let mut last_err = String::new();
+    for attempt in 0..=retry {
+        if attempt > 0 {
+            tokio::time::sleep(Duration::from_millis(backoff.delay_ms(attempt))).await;
+        }
+        last_err = match tokio::time::timeout(Duration::from_millis(timeout_ms), call()).await {
+            Ok(out) => match out.into_result() {
+                Ok(v) => return Ok(v),
+                Err(e) => e,
+            },
+            Err(_) => format!("{}: timeout after {} ms", name, timeout_ms),
+        };
+        eprintln!("  {}[Infra] {} (attempt {}/{}){}", YELLOW, last_err, attempt + 1, retry + 1, RESET);
+    }
+    Err(last_err)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    #[test]
+    fn backoff_delays() {
+        assert_eq!(Backoff::Fixed(50).delay_ms(3), 50);
+        assert_eq!(Backoff::Exponential(100).delay_ms(1), 100);
+        assert_eq!(Backoff::Exponential(100).delay_ms(3), 400);
+        assert_eq!(Backoff::Exponential(u64::MAX).delay_ms(40), u64::MAX);
+    }
+
+    #[tokio::test]
+    async fn retries_until_the_service_succeeds() {
+        let calls = AtomicU32::new(0);
+        let result = infra_call("S.m", 100, 3, Backoff::Fixed(1), || {
+            let n = calls.fetch_add(1, Ordering::SeqCst);
+            async move { if n < 2 { Err("down".to_string()) } else { Ok(n as i64) } }
+        }).await;
+        assert_eq!(result, Ok(2));
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn gives_up_after_the_last_attempt() {
+        let calls = AtomicU32::new(0);
+        let result: Result<String, String> = infra_call("S.m", 100, 1, Backoff::Fixed(1), || {
+            calls.fetch_add(1, Ordering::SeqCst);
+            async { Err::<String, String>("down".to_string()) }
+        }).await;
+        assert_eq!(result, Err("down".to_string()));
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn each_attempt_is_bounded_by_the_timeout() {
+        let result = infra_call("S.slow", 10, 0, Backoff::Fixed(1), || async {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            1i64
+        }).await;
+        assert_eq!(result, Err("S.slow: timeout after 10 ms".to_string()));
+    }
+}
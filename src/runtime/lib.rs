@@ -0,0 +1,50 @@
+// Gojo çalışma zamanı kütüphanesi.
+// Derleyicinin ürettiği her program `use gojo_runtime::prelude::*;` ile bu crate'e bağlanır;
+// servisler (DB, Console, Util, HTTP), schema doğrulama, altyapı çağrıları ve
+// yapılandırılmış eşzamanlılık burada tek başına derlenir ve test edilir.
+
+mod services;
+mod schema;
+mod infra;
+mod task;
+// Yorumlayıcı denetimli i64 işlemlerini (ops::add, ops::div, ...) ve dizi erişimini doğrudan kullanır
+pub mod ops;
+pub mod array;
+pub mod map;
+
+// Üretilen kodun kendi bağımlılığı olmasın diye serde/tokio bu crate üzerinden gelir
+pub use serde;
+pub use serde_json;
+pub use tokio;
+
+pub use array::{Arc, GojoArray};
+pub use map::{BTreeMap, GojoMap};
+pub use infra::{infra_call, Backoff, InfraOutput};
+pub use ops::{GojoAdd, GojoMul, Overflow, Site};
+pub use schema::{validate_schema, FieldKind, FieldRule, FieldSpec, Validate};
+pub use services::{json_field, Console, Util, DB, HTTP};
+pub use task::{block_on, report_error, runtime_error, Task, TaskScope};
+
+// Runtime API sürümü: derleyici, ürettiği projeyi bu sürüme sabitler
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+// Terminal renkleri
+pub mod color {
+    pub const RESET: &str = "\x1b[0m";
+    pub const CYAN: &str = "\x1b[36m";
+    pub const GREEN: &str = "\x1b[32m";
+    pub const MAGENTA: &str = "\x1b[35m";
+    pub const YELLOW: &str = "\x1b[33m";
+    pub const BLUE: &str = "\x1b[34m";
+    pub const RED: &str = "\x1b[31m";
+}
+
+// Üretilen kodun ihtiyaç duyduğu her şey
+pub mod prelude {
+    pub use crate::color::*;
+    pub use crate::{infra_call, Backoff, InfraOutput};
+    pub use crate::{Arc, BTreeMap, GojoAdd, GojoArray, GojoMap, GojoMul, Site};
+    pub use crate::{validate_schema, FieldKind, FieldRule, FieldSpec, Validate};
+    pub use crate::{json_field, Console, Util, DB, HTTP};
+    pub use crate::{runtime_error, Task, TaskScope};
+}
@@ -0,0 +1,36 @@
+// Map<K, V>. Derlenmiş kod BTreeMap kullanır: anahtarlar sıralı tutulur, `for (k, v) in m`
+// her çalıştırmada aynı sırayla gezer ve map'ler deterministic fonksiyonlarda da kullanılabilir.
+// `m["a"]` ve `m.get(k)` Option döndürür; `insert` ve `remove` eski değeri döndürür.
+
+pub use std::collections::BTreeMap;
+
+// Gojo map metotları (BTreeMap<K, V> üzerinde)
+pub trait GojoMap<K, V> {
+    fn g_get(&self, key: K) -> Option<V>;
+    fn g_insert(&mut self, key: K, value: V) -> Option<V>;
+    fn g_remove(&mut self, key: K) -> Option<V>;
+}
+
+impl<K: Ord, V: Clone> GojoMap<K, V> for BTreeMap<K, V> {
+    fn g_get(&self, key: K) -> Option<V> { self.get(&key).cloned() }
+    fn g_insert(&mut self, key: K, value: V) -> Option<V> { self.insert(key, value) }
+    fn g_remove(&mut self, key: K) -> Option<V> { self.remove(&key) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn get_insert_and_remove_return_options() {
+        let mut m = BTreeMap::from([("b".to_string(), 2), ("a".to_string(), 1)]);
+        assert_eq!(m.g_get("a".to_string()), Some(1));
+        assert_eq!(m.g_get("z".to_string()), None);
+        assert_eq!(m.g_insert("a".to_string(), 10), Some(1));
+        assert_eq!(m.g_insert("c".to_string(), 3), None);
+        assert_eq!(m.g_remove("b".to_string()), Some(2));
+        assert_eq!(m.g_remove("b".to_string()), None);
+        let entries: Vec<(String, i64)> = m.into_iter().collect();
+        assert_eq!(entries, [("a".to_string(), 10), ("c".to_string(), 3)]);
+    }
+}
@@ -0,0 +1,222 @@
+// Gojo'nun aritmetik operatörleri. Derleyici iki tarafın da i64 olduğunu biliyorsa
+// doğrudan `Site` üzerinden denetimli işlemi üretir; aksi halde GojoAdd / GojoMul çağrılır.
+// İki yol da aynı kuralı uygular: taşma ve sıfıra bölme, işlemin kaynak konumunu söyleyen
+// bir Gojo çalışma zamanı hatasıdır (`wrapping` fonksiyonlarda taşma sarar).
+
+use crate::task::runtime_error;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Overflow {
+    #[default]
+    Checked,
+    Wrapping,
+}
+
+// i64 ve u8 aynı denetimli/sarmalı işlemleri paylaşır.
+// Hata mesajları Rust'ın panic mesajlarıyla aynıdır; konum çağıran tarafından eklenir
+pub trait Int: Copy + PartialEq {
+    const ZERO: Self;
+    fn checked_add(self, b: Self) -> Option<Self>;
+    fn checked_sub(self, b: Self) -> Option<Self>;
+    fn checked_mul(self, b: Self) -> Option<Self>;
+    fn checked_div(self, b: Self) -> Option<Self>;
+    fn checked_rem(self, b: Self) -> Option<Self>;
+    fn checked_neg(self) -> Option<Self>;
+    fn wrapping_add(self, b: Self) -> Self;
+    fn wrapping_sub(self, b: Self) -> Self;
+    fn wrapping_mul(self, b: Self) -> Self;
+    fn wrapping_div(self, b: Self) -> Self;
+    fn wrapping_rem(self, b: Self) -> Self;
+    fn wrapping_neg(self) -> Self;
+
+    fn add(self, b: Self, overflow: Overflow) -> Result<Self, &'static str> {
+        match overflow {
+            Overflow::Wrapping => Ok(self.wrapping_add(b)),
+            Overflow::Checked => self.checked_add(b).ok_or("attempt to add with overflow"),
+        }
+    }
+
+    fn sub(self, b: Self, overflow: Overflow) -> Result<Self, &'static str> {
+        match overflow {
+            Overflow::Wrapping => Ok(self.wrapping_sub(b)),
+            Overflow::Checked => self.checked_sub(b).ok_or("attempt to subtract with overflow"),
+        }
+    }
+
+    fn mul(self, b: Self, overflow: Overflow) -> Result<Self, &'static str> {
+        match overflow {
+            Overflow::Wrapping => Ok(self.wrapping_mul(b)),
+            Overflow::Checked => self.checked_mul(b).ok_or("attempt to multiply with overflow"),
+        }
+    }
+
+    // Sıfıra bölme her modda hatadır; sadece i64::MIN / -1 sarabilir
+    fn div(self, b: Self, overflow: Overflow) -> Result<Self, &'static str> {
+        if b == Self::ZERO { return Err("attempt to divide by zero"); }
+        match overflow {
+            Overflow::Wrapping => Ok(self.wrapping_div(b)),
+            Overflow::Checked => self.checked_div(b).ok_or("attempt to divide with overflow"),
+        }
+    }
+
+    fn rem(self, b: Self, overflow: Overflow) -> Result<Self, &'static str> {
+        if b == Self::ZERO { return Err("attempt to calculate the remainder with a divisor of zero"); }
+        match overflow {
+            Overflow::Wrapping => Ok(self.wrapping_rem(b)),
+            Overflow::Checked => self.checked_rem(b).ok_or("attempt to calculate the remainder with an overflow"),
+        }
+    }
+
+    fn neg(self, overflow: Overflow) -> Result<Self, &'static str> {
+        match overflow {
+            Overflow::Wrapping => Ok(self.wrapping_neg()),
+            Overflow::Checked => self.checked_neg().ok_or("attempt to negate with overflow"),
+        }
+    }
+}
+
+macro_rules! int {
+    ($($t:ty),*) => {$(
+        impl Int for $t {
+            const ZERO: Self = 0;
+            #[inline(always)] fn checked_add(self, b: Self) -> Option<Self> { <$t>::checked_add(self, b) }
+            #[inline(always)] fn checked_sub(self, b: Self) -> Option<Self> { <$t>::checked_sub(self, b) }
+            #[inline(always)] fn checked_mul(self, b: Self) -> Option<Self> { <$t>::checked_mul(self, b) }
+            #[inline(always)] fn checked_div(self, b: Self) -> Option<Self> { <$t>::checked_div(self, b) }
+            #[inline(always)] fn checked_rem(self, b: Self) -> Option<Self> { <$t>::checked_rem(self, b) }
+            #[inline(always)] fn checked_neg(self) -> Option<Self> { <$t>::checked_neg(self) }
+            #[inline(always)] fn wrapping_add(self, b: Self) -> Self { <$t>::wrapping_add(self, b) }
+            #[inline(always)] fn wrapping_sub(self, b: Self) -> Self { <$t>::wrapping_sub(self, b) }
+            #[inline(always)] fn wrapping_mul(self, b: Self) -> Self { <$t>::wrapping_mul(self, b) }
+            #[inline(always)] fn wrapping_div(self, b: Self) -> Self { <$t>::wrapping_div(self, b) }
+            #[inline(always)] fn wrapping_rem(self, b: Self) -> Self { <$t>::wrapping_rem(self, b) }
+            #[inline(always)] fn wrapping_neg(self) -> Self { <$t>::wrapping_neg(self) }
+        }
+    )*};
+}
+
+int!(i64, u8);
+
+// i64 işlemleri (yorumlayıcı ve Site'ın i64 metotları)
+pub fn add(a: i64, b: i64, overflow: Overflow) -> Result<i64, &'static str> { Int::add(a, b, overflow) }
+pub fn sub(a: i64, b: i64, overflow: Overflow) -> Result<i64, &'static str> { Int::sub(a, b, overflow) }
+pub fn mul(a: i64, b: i64, overflow: Overflow) -> Result<i64, &'static str> { Int::mul(a, b, overflow) }
+pub fn div(a: i64, b: i64, overflow: Overflow) -> Result<i64, &'static str> { Int::div(a, b, overflow) }
+pub fn rem(a: i64, b: i64, overflow: Overflow) -> Result<i64, &'static str> { Int::rem(a, b, overflow) }
+pub fn neg(a: i64, overflow: Overflow) -> Result<i64, &'static str> { Int::neg(a, overflow) }
+
+// `"ab" * n`: negatif tekrar boş metindir. Sonuç bir String'e sığmıyorsa (isize::MAX bayt)
+// Rust'ın `repeat` panic'i yerine hata
+pub fn repeat(text: &str, count: i64) -> Result<String, &'static str> {
+    let count = count.max(0) as usize;
+    text.len().checked_mul(count).filter(|&len| len <= isize::MAX as usize).ok_or("capacity overflow")?;
+    Ok(text.repeat(count))
+}
+
+// Derlenmiş koddaki bir aritmetik işlem: `Site::checked("run.gj:3:12").add(a, b)`
+// Hata süreci konumla sonlandırır (panic = "abort" altında mesajsız ölmek yerine)
+#[derive(Debug, Clone, Copy)]
+pub struct Site {
+    pub at: &'static str,
+    pub overflow: Overflow,
+}
+
+impl Site {
+    pub const fn checked(at: &'static str) -> Self { Self { at, overflow: Overflow::Checked } }
+    pub const fn wrapping(at: &'static str) -> Self { Self { at, overflow: Overflow::Wrapping } }
+
+    #[inline(always)]
+    fn unwrap<T>(self, result: Result<T, &'static str>) -> T {
+        match result {
+            Ok(n) => n,
+            Err(msg) => trap(msg, self.at),
+        }
+    }
+
+    #[inline(always)] pub fn add(self, a: i64, b: i64) -> i64 { self.unwrap(add(a, b, self.overflow)) }
+    #[inline(always)] pub fn sub(self, a: i64, b: i64) -> i64 { self.unwrap(sub(a, b, self.overflow)) }
+    #[inline(always)] pub fn mul(self, a: i64, b: i64) -> i64 { self.unwrap(mul(a, b, self.overflow)) }
+    #[inline(always)] pub fn div(self, a: i64, b: i64) -> i64 { self.unwrap(div(a, b, self.overflow)) }
+    #[inline(always)] pub fn rem(self, a: i64, b: i64) -> i64 { self.unwrap(rem(a, b, self.overflow)) }
+    #[inline(always)] pub fn neg(self, a: i64) -> i64 { self.unwrap(neg(a, self.overflow)) }
+
+    // u8 işlemleri: aynı kural, ayrı isim (tamsayı literal'leri i64 olarak kalsın)
+    #[inline(always)] pub fn add_u8(self, a: u8, b: u8) -> u8 { self.unwrap(Int::add(a, b, self.overflow)) }
+    #[inline(always)] pub fn sub_u8(self, a: u8, b: u8) -> u8 { self.unwrap(Int::sub(a, b, self.overflow)) }
+    #[inline(always)] pub fn mul_u8(self, a: u8, b: u8) -> u8 { self.unwrap(Int::mul(a, b, self.overflow)) }
+    #[inline(always)] pub fn div_u8(self, a: u8, b: u8) -> u8 { self.unwrap(Int::div(a, b, self.overflow)) }
+    #[inline(always)] pub fn rem_u8(self, a: u8, b: u8) -> u8 { self.unwrap(Int::rem(a, b, self.overflow)) }
+}
+
+#[cold]
+#[inline(never)]
+fn trap(msg: &str, at: &str) -> ! {
+    runtime_error(format!("{} at {}", msg, at))
+}
+
+pub trait GojoAdd<Rhs> { type Output; fn g_add(self, rhs: Rhs, site: Site) -> Self::Output; }
+impl GojoAdd<i64> for i64 { type Output = i64; #[inline(always)] fn g_add(self, rhs: i64, site: Site) -> i64 { site.add(self, rhs) } }
+impl GojoAdd<f64> for f64 { type Output = f64; #[inline(always)] fn g_add(self, rhs: f64, _: Site) -> f64 { self + rhs } }
+impl GojoAdd<String> for String { type Output = String; #[inline(always)] fn g_add(self, rhs: String, _: Site) -> String { self + &rhs } }
+impl GojoAdd<&str> for String { type Output = String; #[inline(always)] fn g_add(self, rhs: &str, _: Site) -> String { self + rhs } }
+impl GojoAdd<i64> for String { type Output = String; #[inline(always)] fn g_add(self, rhs: i64, _: Site) -> String { format!("{}{}", self, rhs) } }
+
+pub trait GojoMul<Rhs> { type Output; fn g_mul(self, rhs: Rhs, site: Site) -> Self::Output; }
+impl GojoMul<i64> for i64 { type Output = i64; #[inline(always)] fn g_mul(self, rhs: i64, site: Site) -> i64 { site.mul(self, rhs) } }
+impl GojoMul<f64> for f64 { type Output = f64; #[inline(always)] fn g_mul(self, rhs: f64, _: Site) -> f64 { self * rhs } }
+impl GojoMul<i64> for String { type Output = String; fn g_mul(self, rhs: i64, site: Site) -> String { site.unwrap(repeat(&self, rhs)) } }
+impl GojoMul<i64> for &str { type Output = String; fn g_mul(self, rhs: i64, site: Site) -> String { site.unwrap(repeat(self, rhs)) } }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AT: Site = Site::checked("test.gj:1:1");
+
+    #[test]
+    fn add_numbers_and_strings() {
+        assert_eq!(2i64.g_add(3, AT), 5);
+        assert_eq!("a".to_string().g_add("b".to_string(), AT), "ab");
+        assert_eq!("a".to_string().g_add("c", AT), "ac");
+        assert_eq!("id:".to_string().g_add(7, AT), "id:7");
+    }
+
+    #[test]
+    fn mul_numbers_and_repeat_strings() {
+        assert_eq!(6i64.g_mul(7, AT), 42);
+        assert_eq!("ab".to_string().g_mul(3, AT), "ababab");
+        assert_eq!("-".g_mul(2, AT), "--");
+        assert_eq!("x".g_mul(-1, AT), "");
+        assert_eq!(repeat("", i64::MAX), Ok(String::new()));
+        assert_eq!(repeat("ab", i64::MAX), Err("capacity overflow"));
+    }
+
+    #[test]
+    fn checked_arithmetic_reports_overflow() {
+        assert_eq!(add(i64::MAX, 1, Overflow::Checked), Err("attempt to add with overflow"));
+        assert_eq!(mul(i64::MIN, -1, Overflow::Checked), Err("attempt to multiply with overflow"));
+        assert_eq!(neg(i64::MIN, Overflow::Checked), Err("attempt to negate with overflow"));
+        assert_eq!(div(i64::MIN, -1, Overflow::Checked), Err("attempt to divide with overflow"));
+        assert_eq!(sub(-7, 3, Overflow::Checked), Ok(-10));
+        assert_eq!(rem(-7, 3, Overflow::Checked), Ok(-1));
+    }
+
+    #[test]
+    fn wrapping_arithmetic_wraps_but_still_rejects_zero_divisors() {
+        assert_eq!(add(i64::MAX, 1, Overflow::Wrapping), Ok(i64::MIN));
+        assert_eq!(div(i64::MIN, -1, Overflow::Wrapping), Ok(i64::MIN));
+        assert_eq!(rem(i64::MIN, -1, Overflow::Wrapping), Ok(0));
+        assert_eq!(div(1, 0, Overflow::Wrapping), Err("attempt to divide by zero"));
+        assert_eq!(rem(1, 0, Overflow::Wrapping), Err("attempt to calculate the remainder with a divisor of zero"));
+    }
+
+    #[test]
+    fn bytes_use_the_same_rules() {
+        assert_eq!(AT.add_u8(200, 55), 255);
+        assert_eq!(1.5f64.g_mul(2.0, AT), 3.0);
+        assert_eq!(Int::add(200u8, 56, Overflow::Checked), Err("attempt to add with overflow"));
+        assert_eq!(Int::sub(0u8, 1, Overflow::Checked), Err("attempt to subtract with overflow"));
+        assert_eq!(Int::add(200u8, 56, Overflow::Wrapping), Ok(0));
+        assert_eq!(Int::div(7u8, 0, Overflow::Wrapping), Err("attempt to divide by zero"));
+    }
+}
@@ -0,0 +1,89 @@
+use serde_json::Value;
+
+// `validate x { ... }` schema'sız çağrıldığında düz metin olduğu gibi kabul edilir
+pub trait Validate { fn validate(&self) -> Result<String, String>; }
+impl Validate for String {
+    fn validate(&self) -> Result<String, String> { Ok(self.clone()) }
+}
+
+// SCHEMA DOĞRULAMA (validate x { schema: S, ... })
+// Derleyici her schema için bir `&[FieldSpec]` sabiti üretir; yorumlayıcı aynı tabloyu AST'den kurar.
+pub enum FieldKind { Int, Str, Any }
+pub enum FieldRule<'a> { Len(i64, i64), Range(i64, i64), Regex(&'a str) }
+pub struct FieldSpec<'a> { pub name: &'a str, pub kind: FieldKind, pub rules: &'a [FieldRule<'a>] }
+
+pub fn validate_schema<T: serde::de::DeserializeOwned>(raw: &str, schema: &str, fields: &[FieldSpec<'_>]) -> Result<T, String> {
+    let value: Value = serde_json::from_str(raw).map_err(|e| format!("{}: invalid JSON ({})", schema, e))?;
+    let obj = value.as_object().ok_or_else(|| format!("{}: expected a JSON object", schema))?;
+    for field in fields {
+        let v = obj.get(field.name).ok_or_else(|| format!("{}.{}: missing field", schema, field.name))?;
+        match field.kind {
+            FieldKind::Int => {
+                let n = v.as_i64().ok_or_else(|| format!("{}.{}: expected i64", schema, field.name))?;
+                for rule in field.rules {
+                    if let FieldRule::Range(min, max) = rule {
+                        if n < *min || n > *max { return Err(format!("{}.{}: {} is outside range({}, {})", schema, field.name, n, min, max)); }
+                    }
+                }
+            }
+            FieldKind::Str => {
+                let s = v.as_str().ok_or_else(|| format!("{}.{}: expected String", schema, field.name))?;
+                for rule in field.rules {
+                    match rule {
+                        FieldRule::Len(min, max) => {
+                            let len = s.chars().count() as i64;
+                            if len < *min || len > *max { return Err(format!("{}.{}: length {} is outside len({}, {})", schema, field.name, len, min, max)); }
+                        }
+                        FieldRule::Regex(re) => {
+                            if !regex::Regex::new(re).map(|r| r.is_match(s)).unwrap_or(false) { return Err(format!("{}.{}: does not match regex({:?})", schema, field.name, re)); }
+                        }
+                        FieldRule::Range(..) => {}
+                    }
+                }
+            }
+            // Struct/dizi alanlarının tipini serde kontrol eder
+            FieldKind::Any => {}
+        }
+    }
+    serde_json::from_value(value).map_err(|e| format!("{}: {}", schema, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct User { name: String, age: i64 }
+
+    const USER: &[FieldSpec] = &[
+        FieldSpec { name: "name", kind: FieldKind::Str, rules: &[FieldRule::Len(2, 8), FieldRule::Regex("^[a-z]+$")] },
+        FieldSpec { name: "age", kind: FieldKind::Int, rules: &[FieldRule::Range(0, 150)] },
+    ];
+
+    fn check(raw: &str) -> Result<User, String> { validate_schema(raw, "User", USER) }
+
+    #[test]
+    fn accepts_valid_input() {
+        assert_eq!(check(r#"{"name": "gojo", "age": 30}"#), Ok(User { name: "gojo".to_string(), age: 30 }));
+    }
+
+    #[test]
+    fn reports_the_failing_field() {
+        assert_eq!(check(r#"{"name": "gojo"}"#), Err("User.age: missing field".to_string()));
+        assert_eq!(check(r#"{"name": "gojo", "age": "30"}"#), Err("User.age: expected i64".to_string()));
+        assert_eq!(check(r#"{"name": "gojo", "age": 200}"#), Err("User.age: 200 is outside range(0, 150)".to_string()));
+        assert_eq!(check(r#"{"name": "g", "age": 1}"#), Err("User.name: length 1 is outside len(2, 8)".to_string()));
+        assert_eq!(check(r#"{"name": "Gojo", "age": 1}"#), Err("User.name: does not match regex(\"^[a-z]+$\")".to_string()));
+    }
+
+    #[test]
+    fn rejects_non_objects() {
+        assert_eq!(check("[1]"), Err("User: expected a JSON object".to_string()));
+        assert!(check("{").unwrap_err().starts_with("User: invalid JSON"));
+    }
+
+    #[test]
+    fn plain_strings_pass_through() {
+        assert_eq!("raw".to_string().validate(), Ok("raw".to_string()));
+    }
+}
@@ -0,0 +1,79 @@
+use crate::color::{BLUE, CYAN, RESET};
+use std::io::{self, Write};
+
+// Gojo'nun yerleşik servisleri. Metotlar `Servis::metot` olarak çağrılır;
+// saflık ve taint bilgisi derleyicideki builtins tablosundadır.
+
+pub struct DB;
+impl DB {
+    pub async fn log<T: std::fmt::Display>(msg: T) { println!("  {}[DB] Log: {}{}", CYAN, msg, RESET); }
+}
+
+pub struct Console;
+impl Console {
+    pub async fn read(prompt: String) -> String {
+        print!("  {}[Console]  {}: {} ", BLUE, prompt, RESET);
+        io::stdout().flush().unwrap();
+        let mut buffer = String::new();
+        io::stdin().read_line(&mut buffer).unwrap();
+        buffer.trim().to_string()
+    }
+}
+
+pub struct Util;
+impl Util {
+    #[inline(always)]
+    pub fn to_int(s: String) -> i64 { s.trim().parse::<i64>().unwrap_or(0) }
+    #[inline(always)]
+    pub async fn now() -> i64 {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as i64
+    }
+}
+
+pub struct HTTP;
+impl HTTP {
+    pub async fn get(url: String) -> Result<String, String> {
+        let client = reqwest::Client::builder().user_agent("GojoLang/1.0").build().map_err(|e| format!("HTTP.get: {}", e))?;
+        let res = client.get(&url).send().await.map_err(|e| format!("HTTP.get {}: {}", url, e))?;
+        res.text().await.map_err(|e| format!("HTTP.get {}: {}", url, e))
+    }
+}
+
+// json(kaynak, "anahtar"): metin değerler tırnaksız, diğerleri JSON olarak döner
+pub fn json_field(raw: &str, key: &str) -> Result<String, String> {
+    let value: serde_json::Value = serde_json::from_str(raw).map_err(|e| format!("json: {}", e))?;
+    match value.get(key) {
+        Some(serde_json::Value::String(s)) => Ok(s.clone()),
+        Some(other) => Ok(other.to_string()),
+        None => Err(format!("json: missing key {:?}", key)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn to_int_trims_and_defaults_to_zero() {
+        assert_eq!(Util::to_int(" 42\n".to_string()), 42);
+        assert_eq!(Util::to_int("-7".to_string()), -7);
+        assert_eq!(Util::to_int("abc".to_string()), 0);
+    }
+
+    #[test]
+    fn json_field_reads_strings_and_values() {
+        let raw = r#"{"name": "gojo", "age": 3, "tags": ["a"]}"#;
+        assert_eq!(json_field(raw, "name"), Ok("gojo".to_string()));
+        assert_eq!(json_field(raw, "age"), Ok("3".to_string()));
+        assert_eq!(json_field(raw, "tags"), Ok(r#"["a"]"#.to_string()));
+    }
+
+    #[test]
+    fn json_field_reports_missing_key_and_bad_json() {
+        assert_eq!(json_field("{}", "id"), Err("json: missing key \"id\"".to_string()));
+        assert!(json_field("not json", "id").unwrap_err().starts_with("json: "));
+    }
+}
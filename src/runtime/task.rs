@@ -0,0 +1,114 @@
+use crate::color::{RED, RESET};
+use std::future::{Future, IntoFuture};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+
+// Gojo çalışma zamanı hatası: panic yerine kaynağı söyleyen mesajla çık
+pub fn runtime_error(msg: String) -> ! {
+    report_error(&msg);
+    std::process::exit(1)
+}
+
+pub fn report_error(msg: &str) {
+    eprintln!("  {}[Gojo] runtime error: {}{}", RED, msg, RESET);
+}
+
+// Senkron kodda (closure gövdesi) async bir servis çağrısını bekler. Worker thread'i
+// bloklanırken diğer task'lar başka thread'lerde sürer; çok thread'li runtime gerekir
+// (#[tokio::main] ve yorumlayıcı böyle çalışır).
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    tokio::task::block_in_place(|| tokio::runtime::Handle::current().block_on(future))
+}
+
+// YAPILANDIRILMIŞ EŞZAMANLILIK: her scope kendi task'larının sahibidir.
+// Normal çıkışta join_all ile hepsi beklenir; erken çıkışta (return) TaskScope düşer
+// ve JoinSet'in Drop'u kalan task'ları iptal eder. Yetim task kalmaz.
+pub struct TaskScope { name: Arc<str>, set: tokio::task::JoinSet<()> }
+
+impl TaskScope {
+    pub fn new(name: &str) -> Self { Self { name: name.into(), set: tokio::task::JoinSet::new() } }
+
+    pub fn spawn<T: Send + 'static>(&mut self, fut: impl Future<Output = T> + Send + 'static) -> Task<T> {
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        self.set.spawn(async move { let _ = tx.send(fut.await); });
+        Task { scope: self.name.clone(), rx: Arc::new(Mutex::new(Some(rx))) }
+    }
+
+    // await all
+    pub async fn join_all(&mut self) {
+        while let Some(res) = self.set.join_next().await {
+            if let Err(e) = res {
+                if e.is_panic() { runtime_error(format!("task in scope `{}` panicked", self.name)); }
+            }
+        }
+    }
+}
+
+// spawn'ın döndürdüğü handle: `await task` sonucu verir (yalnızca bir kez)
+pub struct Task<T> { scope: Arc<str>, rx: Arc<Mutex<Option<tokio::sync::oneshot::Receiver<T>>>> }
+
+impl<T> Clone for Task<T> {
+    fn clone(&self) -> Self { Self { scope: self.scope.clone(), rx: self.rx.clone() } }
+}
+
+impl<T: Send + 'static> IntoFuture for Task<T> {
+    type Output = T;
+    type IntoFuture = Pin<Box<dyn Future<Output = T> + Send>>;
+    fn into_future(self) -> Self::IntoFuture {
+        Box::pin(async move {
+            let rx = self.rx.lock().unwrap().take();
+            match rx {
+                Some(rx) => match rx.await {
+                    Ok(v) => v,
+                    Err(_) => runtime_error(format!("task in scope `{}` was cancelled before it finished", self.scope)),
+                },
+                None => runtime_error(format!("task in scope `{}` was already awaited", self.scope)),
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn await_returns_the_task_result() {
+        let mut scope = TaskScope::new("Test");
+        let a = scope.spawn(async { 20i64 });
+        let b = scope.spawn(async { 22i64 });
+        assert_eq!(a.await + b.await, 42);
+        scope.join_all().await;
+    }
+
+    #[tokio::test]
+    async fn join_all_waits_for_every_task() {
+        let done = Arc::new(AtomicU32::new(0));
+        let mut scope = TaskScope::new("Test");
+        for ms in [5, 1, 3] {
+            let done = done.clone();
+            scope.spawn(async move {
+                tokio::time::sleep(Duration::from_millis(ms)).await;
+                done.fetch_add(1, Ordering::SeqCst);
+            });
+        }
+        scope.join_all().await;
+        assert_eq!(done.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn dropping_the_scope_cancels_its_tasks() {
+        let (tx, rx) = tokio::sync::oneshot::channel::<()>();
+        {
+            let mut scope = TaskScope::new("Test");
+            scope.spawn(async move {
+                let _tx = tx;
+                std::future::pending::<()>().await;
+            });
+        }
+        // Task hiç göndermez ve bitmez: alıcı ancak iptal task'ın future'ını düşürünce uyanır
+        assert!(rx.await.is_err());
+    }
+}
@@ -0,0 +1,98 @@
+// Yorumlayıcı ile derlenmiş program aynı çıktıyı vermeli.
+// Derleme cargo'yu çağırdığı için yavaştır; hedef dizini target/golden altında paylaşılır.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const SAMPLE: &str = r#"
+struct User { id: i64, name: String }
+
+deterministic fn fib(n: i64) -> i64 {
+    if n <= 1 { return n }
+    return fib(n - 1) + fib(n - 2)
+}
+
+deterministic fn describe(r: Result<i64, String>) -> String {
+    return match r {
+        Ok(n) => "ok ${n}",
+        Err(e) => "hata ${e}",
+    }
+}
+
+deterministic wrapping fn mix(h: i64) -> i64 { return h * 6364136223846793005 + 1 }
+
+nondeterministic fn main(input: Untrusted) -> Void {
+    let u = User { id: fib(15), name: "gojo" }
+    DB.log("${u.name}: ${u.id}")
+    let arr = [5, 3, 8, 1]
+    arr.sort()
+    for i, n in arr { DB.log("${i} -> ${n}") }
+    let doubled = arr.map(|x: i64| x * 2).filter(|x: i64| x > 4)
+    DB.log(doubled.fold(0, |acc, x| acc + x))
+    let ages: Map<String, i64> = { "ali": 30 }
+    let can = match ages.get("can") {
+        Some(a) => a,
+        None => -1,
+    }
+    DB.log(can)
+    DB.log(describe(Ok(7)))
+    DB.log(describe(Err("yok")))
+    DB.log(mix(mix(42)))
+    let label = "sonuc"
+    scope S {
+        let h = spawn fib(20)
+        let total = await h
+        DB.log(label + total)
+    }
+    for i in 10..0 by -3 { DB.log(i) }
+    let prefix = "kapanış"
+    let log = |x: i64| DB.log("${prefix} ${x}")
+    log(1)
+    let logged = arr.map(|x: i64| log(x * 10))
+    DB.log(len(logged))
+    let late = |x: i64| x + call Util.now() { timeout: 1s, fallback: 0 }
+    DB.log(late(0) > 0)
+}
+"#;
+
+// CLI'nin kendi satırlarını atar: program çıktısı "Çalıştırılıyor" satırından sonra başlar
+fn program_output(dir: &Path, args: &[&str]) -> String {
+    let out = Command::new(env!("CARGO_BIN_EXE_gojo-compiler"))
+        .args(args)
+        .current_dir(dir)
+        .env("CARGO_TARGET_DIR", Path::new(env!("CARGO_MANIFEST_DIR")).join("target").join("golden"))
+        .output()
+        .expect("gojo-compiler çalıştırılamadı");
+    let stdout = String::from_utf8_lossy(&out.stdout).into_owned();
+    assert!(out.status.success(), "{:?} başarısız:\n{}{}", args, stdout, String::from_utf8_lossy(&out.stderr));
+    let start = stdout.find("Çalıştırılıyor").expect("program çalışmadı");
+    stdout[start..].split_once('\n').map(|(_, rest)| rest.to_string()).unwrap_or_default()
+}
+
+// Aynı kaynak iki yoldan çalıştırılır; testler paralel koştuğu için her örnek kendi dizininde
+fn assert_backends_agree(name: &str, source: &str) -> String {
+    let dir: PathBuf = std::env::temp_dir().join(format!("gojo-golden-{}-{}", name, std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let file = format!("{}.gj", name);
+    std::fs::write(dir.join(&file), source).unwrap();
+
+    let interpreted = program_output(&dir, &["run", "--interp", &file]);
+    let compiled = program_output(&dir, &["run", &file, "--out-dir", "out"]);
+    std::fs::remove_dir_all(&dir).ok();
+
+    assert_eq!(interpreted, compiled);
+    interpreted
+}
+
+#[test]
+fn interpreter_matches_compiled_output() {
+    let output = assert_backends_agree("golden", SAMPLE);
+    assert!(output.contains("gojo: 610"), "{}", output);
+}
+
+// Parametresiz main: shim argüman vermez
+#[test]
+fn main_without_parameters_runs_in_both_backends() {
+    let output = assert_backends_agree("noargs", "nondeterministic fn main() {\n    DB.log(\"parametresiz\")\n}\n");
+    assert!(output.contains("parametresiz"), "{}", output);
+}